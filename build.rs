@@ -8,5 +8,6 @@ fn main() {
         // xclip and xsel paths are inserted at compile time
         println!("cargo:rerun-if-env-changed=XCLIP_PATH");
         println!("cargo:rerun-if-env-changed=XSEL_PATH");
+        println!("cargo:rerun-if-env-changed=X11_HELPER_PATH");
     }
 }
@@ -0,0 +1,264 @@
+//! Use the Android clipboard through the platform `ClipboardManager`.
+//!
+//! [`AndroidClipboardContext`] gets and sets contents through the
+//! [`ClipboardManager`][ClipboardManager] of the app, by calling into Java through JNI. This gives
+//! Rust code embedded in Android apps clipboard support, rather than only code running in a
+//! terminal such as Termux.
+//!
+//! The JVM and app context are taken from [`ndk-context`][ndk-context], which must be initialized.
+//! Runtimes such as `android-activity` and `ndk-glue` do this before `main`, apps embedding Rust
+//! code in an existing project must call `ndk_context::initialize_android_context` themselves.
+//! The calling thread is attached to the JVM for each operation if it isn't yet.
+//!
+//! Since Android 10, only the app having focus, or the default input method, may read the
+//! clipboard. Otherwise the clipboard appears to be empty.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::android::AndroidClipboardContext;
+//!
+//! let mut ctx = AndroidClipboardContext::new().unwrap();
+//! println!("{:?}", ctx.get_contents());
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+//!
+//! [ClipboardManager]: https://developer.android.com/reference/android/content/ClipboardManager
+//! [ndk-context]: https://docs.rs/ndk-context
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::panic;
+
+use jni::objects::{JObject, JString};
+use jni::{JNIEnv, JavaVM};
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Capacity of the local reference frame of each operation.
+const LOCAL_FRAME_CAPACITY: i32 = 16;
+
+/// Clipboard context using the Android `ClipboardManager`.
+///
+/// The JVM and app context are looked up on each operation, so the context may be used from
+/// any thread.
+///
+/// See module documentation for more information.
+pub struct AndroidClipboardContext;
+
+impl AndroidClipboardContext {
+    /// Construct a new context.
+    ///
+    /// Fails if the Android context isn't initialized, see module documentation.
+    pub fn new() -> crate::ClipResult<Self> {
+        // Only fails by panicking, once initialized it never is again
+        panic::catch_unwind(ndk_context::android_context).map_err(|_| Error::NotInitialized)?;
+        Ok(AndroidClipboardContext)
+    }
+}
+
+impl ClipboardProvider for AndroidClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        Ok(with_manager(|env, context, manager| {
+            let clip = env
+                .call_method(
+                    manager,
+                    "getPrimaryClip",
+                    "()Landroid/content/ClipData;",
+                    &[],
+                )?
+                .l()?;
+            if clip.is_null() || env.call_method(&clip, "getItemCount", "()I", &[])?.i()? == 0 {
+                return Ok(String::new());
+            }
+            let item = env
+                .call_method(
+                    &clip,
+                    "getItemAt",
+                    "(I)Landroid/content/ClipData$Item;",
+                    &[0.into()],
+                )?
+                .l()?;
+
+            // Converts contents such as URIs and HTML to text as well
+            let text = env
+                .call_method(
+                    &item,
+                    "coerceToText",
+                    "(Landroid/content/Context;)Ljava/lang/CharSequence;",
+                    &[context.into()],
+                )?
+                .l()?;
+            if text.is_null() {
+                return Ok(String::new());
+            }
+            let text = JString::from(
+                env.call_method(&text, "toString", "()Ljava/lang/String;", &[])?
+                    .l()?,
+            );
+            let text: String = env.get_string(&text)?.into();
+            Ok(text)
+        })?)
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        Ok(with_manager(|env, _, manager| {
+            let label = env.new_string("")?;
+            let text = env.new_string(&contents)?;
+            let clip = env
+                .call_static_method(
+                    "android/content/ClipData",
+                    "newPlainText",
+                    "(Ljava/lang/CharSequence;Ljava/lang/CharSequence;)Landroid/content/ClipData;",
+                    &[(&label).into(), (&text).into()],
+                )?
+                .l()?;
+            env.call_method(
+                manager,
+                "setPrimaryClip",
+                "(Landroid/content/ClipData;)V",
+                &[(&clip).into()],
+            )?;
+            Ok(())
+        })?)
+    }
+}
+
+impl ClipboardProviderNew for AndroidClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        AndroidClipboardContext::new()
+    }
+}
+
+/// Contents are kept by the system.
+impl ClipboardProviderExt for AndroidClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        None
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        Ok(with_manager(|env, _, manager| {
+            Ok(!env
+                .call_method(manager, "hasPrimaryClip", "()Z", &[])?
+                .z()?)
+        })?)
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        Ok(with_manager(|env, _, manager| {
+            let description = env
+                .call_method(
+                    manager,
+                    "getPrimaryClipDescription",
+                    "()Landroid/content/ClipDescription;",
+                    &[],
+                )?
+                .l()?;
+            if description.is_null() {
+                return Ok(vec![]);
+            }
+            let count = env
+                .call_method(&description, "getMimeTypeCount", "()I", &[])?
+                .i()?;
+            let mut mime_types = Vec::new();
+            for i in 0..count {
+                let mime_type = JString::from(
+                    env.call_method(
+                        &description,
+                        "getMimeType",
+                        "(I)Ljava/lang/String;",
+                        &[i.into()],
+                    )?
+                    .l()?,
+                );
+                mime_types.push(env.get_string(&mime_type)?.into());
+            }
+            Ok(mime_types)
+        })?)
+    }
+}
+
+/// Run the given function with the JNI environment, app context and `ClipboardManager`.
+///
+/// Attaches the current thread to the JVM if it isn't yet. Local references created by the
+/// function are freed afterwards, and a thrown Java exception is cleared.
+fn with_manager<F, T>(f: F) -> Result<T, Error>
+where
+    F: FnOnce(&mut JNIEnv, &JObject, &JObject) -> Result<T, Error>,
+{
+    let android =
+        panic::catch_unwind(ndk_context::android_context).map_err(|_| Error::NotInitialized)?;
+    let vm = unsafe { JavaVM::from_raw(android.vm().cast()) }?;
+    let context = unsafe { JObject::from_raw(android.context().cast()) };
+    let mut env = vm.attach_current_thread()?;
+
+    env.with_local_frame(LOCAL_FRAME_CAPACITY, |env| {
+        let result = (|| {
+            let service = env.new_string("clipboard")?;
+            let manager = env
+                .call_method(
+                    &context,
+                    "getSystemService",
+                    "(Ljava/lang/String;)Ljava/lang/Object;",
+                    &[(&service).into()],
+                )?
+                .l()?;
+            if manager.is_null() {
+                return Err(Error::NoClipboardManager);
+            }
+            f(env, &context, &manager)
+        })();
+
+        // Further JNI calls are not allowed while an exception is pending
+        if result.is_err() && env.exception_check().unwrap_or(false) {
+            let _ = env.exception_clear();
+        }
+        result
+    })
+}
+
+/// Represents Android clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The Android context of `ndk-context` isn't initialized.
+    NotInitialized,
+
+    /// The app context doesn't provide a `ClipboardManager`.
+    NoClipboardManager,
+
+    /// A JNI call failed, such as because Java threw an exception.
+    Jni(jni::errors::Error),
+}
+
+impl From<jni::errors::Error> for Error {
+    fn from(err: jni::errors::Error) -> Self {
+        Error::Jni(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotInitialized => write!(f, "Android context is not initialized"),
+            Error::NoClipboardManager => write!(f, "Android clipboard manager is not available"),
+            Error::Jni(err) => write!(f, "Failed to access Android clipboard: {}", err),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Jni(err) => Some(err),
+            _ => None,
+        }
+    }
+}
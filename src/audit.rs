@@ -0,0 +1,332 @@
+//! Keep an audit trail of clipboard operations.
+//!
+//! [`AuditClipboardContext`] wraps any clipboard context, and records each get and set operation
+//! as an [`AuditRecord`]. Records hold the time, operation, backend, content size and whether it
+//! succeeded. A content hash may be included to correlate operations, the contents themselves are
+//! never recorded.
+//!
+//! Records are passed to an [`AuditSink`], such as an [`AuditFile`] appending them to a file, or
+//! any callback.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::audit::{AuditClipboardContext, AuditFile};
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! let file = AuditFile::open("clipboard-audit.log").unwrap();
+//! let mut ctx = AuditClipboardContext::new(ClipboardContext::new().unwrap(), file)
+//!     .with_backend("x11-bin")
+//!     .with_hash();
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+//!
+//! Pass records to a callback instead:
+//!
+//! ```rust
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::audit::AuditClipboardContext;
+//! use copypasta_ext::testing::DryRunClipboardContext;
+//!
+//! let mut ctx = AuditClipboardContext::new(DryRunClipboardContext::new(), |record: &_| {
+//!     println!("{}", record);
+//! });
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::hash::Hasher;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ContentLifetime, Selection, ThreadAffinity};
+
+/// A recorded clipboard operation.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+pub enum AuditOperation {
+    /// Clipboard contents were read.
+    Get,
+
+    /// Clipboard contents were written.
+    Set,
+}
+
+impl fmt::Display for AuditOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditOperation::Get => write!(f, "get"),
+            AuditOperation::Set => write!(f, "set"),
+        }
+    }
+}
+
+/// Record of a single clipboard operation.
+///
+/// Implements [`Display`](fmt::Display) as a single line, such as
+/// `1700000000.123 set x11-bin size=42 hash=5cf2b4a8e0d9a1c7 ok`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct AuditRecord {
+    /// When the operation completed.
+    pub timestamp: SystemTime,
+
+    /// The operation.
+    pub operation: AuditOperation,
+
+    /// Name of the clipboard backend.
+    pub backend: String,
+
+    /// Content size in bytes, `None` if the operation failed before it was known.
+    pub size: Option<usize>,
+
+    /// Content hash, only if enabled. Comparable with
+    /// [`content_hash`](ClipboardProviderExt::content_hash).
+    pub hash: Option<u64>,
+
+    /// Whether the operation succeeded.
+    pub success: bool,
+}
+
+impl fmt::Display for AuditRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let timestamp = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(
+            f,
+            "{}.{:03} {} {}",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            self.operation,
+            self.backend
+        )?;
+        if let Some(size) = self.size {
+            write!(f, " size={}", size)?;
+        }
+        if let Some(hash) = self.hash {
+            write!(f, " hash={:016x}", hash)?;
+        }
+        write!(f, " {}", if self.success { "ok" } else { "failed" })
+    }
+}
+
+/// Receives audit records.
+///
+/// Implemented for [`AuditFile`], and for any `FnMut(&AuditRecord)` callback.
+pub trait AuditSink: Send {
+    /// Record the given operation.
+    fn record(&mut self, record: &AuditRecord);
+}
+
+impl<F> AuditSink for F
+where
+    F: FnMut(&AuditRecord) + Send,
+{
+    fn record(&mut self, record: &AuditRecord) {
+        self(record)
+    }
+}
+
+/// Appends audit records to a file, one line each.
+///
+/// See [`AuditRecord`] for the line format. Failing to write a record is ignored, it does not
+/// fail the clipboard operation.
+pub struct AuditFile(File);
+
+impl AuditFile {
+    /// Open the file at the given path for appending, creating it if it doesn't exist.
+    pub fn open<P>(path: P) -> crate::ClipResult<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self(file))
+    }
+}
+
+impl AuditSink for AuditFile {
+    fn record(&mut self, record: &AuditRecord) {
+        let _ = writeln!(self.0, "{}", record);
+    }
+}
+
+/// Record clipboard operations on the wrapped context.
+///
+/// See module documentation for more information.
+pub struct AuditClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    context: C,
+    sink: Box<dyn AuditSink>,
+    backend: String,
+    hash: bool,
+}
+
+impl<C> AuditClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context, passing records to the given sink.
+    ///
+    /// The backend is named after the context type by default, see
+    /// [`with_backend`](AuditClipboardContext::with_backend).
+    pub fn new<S>(context: C, sink: S) -> Self
+    where
+        S: AuditSink + 'static,
+    {
+        Self {
+            context,
+            sink: Box::new(sink),
+            backend: std::any::type_name::<C>().into(),
+            hash: false,
+        }
+    }
+
+    /// Set the backend name to record, such as `x11-bin`.
+    pub fn with_backend(mut self, backend: &str) -> Self {
+        self.backend = backend.into();
+        self
+    }
+
+    /// Include a hash of the contents in records.
+    ///
+    /// Allows correlating operations, such as finding which get returned contents set earlier,
+    /// without recording the contents. Note that the hash of short or guessable contents may be
+    /// brute forced.
+    pub fn with_hash(mut self) -> Self {
+        self.hash = true;
+        self
+    }
+
+    /// Get a reference to the inner clipboard context.
+    pub fn inner(&self) -> &C {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+
+    /// Hash the given contents, if enabled.
+    fn hash(&self, contents: &str) -> Option<u64> {
+        if !self.hash {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        hasher.write(contents.as_bytes());
+        Some(hasher.finish())
+    }
+
+    /// Pass a record of the given operation to the sink.
+    fn record(
+        &mut self,
+        operation: AuditOperation,
+        size: Option<usize>,
+        hash: Option<u64>,
+        success: bool,
+    ) {
+        self.sink.record(&AuditRecord {
+            timestamp: SystemTime::now(),
+            operation,
+            backend: self.backend.clone(),
+            size,
+            hash,
+            success,
+        });
+    }
+}
+
+impl<C> ClipboardProvider for AuditClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        let result = self.context.get_contents();
+        match &result {
+            Ok(contents) => {
+                let hash = self.hash(contents);
+                self.record(AuditOperation::Get, Some(contents.len()), hash, true);
+            }
+            Err(_) => self.record(AuditOperation::Get, None, None, false),
+        }
+        result
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        let size = contents.len();
+        let hash = self.hash(&contents);
+        let result = self.context.set_contents(contents);
+        self.record(AuditOperation::Set, Some(size), hash, result.is_ok());
+        result
+    }
+}
+
+/// Streaming is not forwarded to the inner context, so each operation is recorded.
+impl<C> ClipboardProviderExt for AuditClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.context.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.context.change_token()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.context.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.context.available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.context.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        let hash = self.hash("");
+        let result = self.context.clear_contents();
+        self.record(AuditOperation::Set, Some(0), hash, result.is_ok());
+        result
+    }
+}
@@ -0,0 +1,156 @@
+//! Minimal `xclip` replacement, to hold X11 clipboard contents after an application exits.
+//!
+//! Used by [`x11_bin`](copypasta_ext::x11_bin) if neither `xclip` nor `xsel` is installed. Supports
+//! the subset of the `xclip` interface used by this crate:
+//!
+//! - `copypasta-ext-x11-helper -sel clipboard`: set contents read from standard input
+//! - `copypasta-ext-x11-helper -sel clipboard -out`: write contents to standard output
+//!
+//! When setting, a forked process keeps serving the contents until the clipboard changes, see
+//! [`x11_fork`](copypasta_ext::x11_fork).
+//!
+//! With `-serve`, the helper stays alive and handles requests from standard input until it is
+//! closed, to avoid spawning a process for each operation. A request is a header line,
+//! `get <selection>` or `set <selection> <length>` followed by the contents. Each request is
+//! answered with `ok <length>` followed by the contents, or with `err <message>`.
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+mod x11 {
+    use std::env;
+    use std::io::{self, BufRead, Read, Write};
+    use std::process;
+
+    use copypasta_ext::copypasta::x11_clipboard::{Clipboard, Primary, X11ClipboardContext};
+    use copypasta_ext::prelude::*;
+    use copypasta_ext::x11_fork::{ForkSelection, X11ForkClipboardContext};
+
+    pub fn main() {
+        let mut primary = false;
+        let mut out = false;
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-serve" => {
+                    if let Err(err) = serve() {
+                        eprintln!("copypasta-ext-x11-helper: {}", err);
+                        process::exit(1);
+                    }
+                    return;
+                }
+                "-sel" | "-selection" => match args.next().as_deref() {
+                    Some("clipboard") => primary = false,
+                    Some("primary") => primary = true,
+                    _ => usage(),
+                },
+                "-i" | "-in" => out = false,
+                "-o" | "-out" => out = true,
+                _ => usage(),
+            }
+        }
+
+        let result = if primary {
+            run::<Primary>(out)
+        } else {
+            run::<Clipboard>(out)
+        };
+        if let Err(err) = result {
+            eprintln!("copypasta-ext-x11-helper: {}", err);
+            process::exit(1);
+        }
+    }
+
+    /// Get or set the contents of selection `S`.
+    fn run<S>(out: bool) -> copypasta_ext::ClipResult<()>
+    where
+        S: ForkSelection,
+    {
+        if out {
+            let contents = X11ClipboardContext::<S>::new()?.get_contents()?;
+            io::stdout().write_all(contents.as_bytes())?;
+        } else {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)?;
+            X11ForkClipboardContext::<S>::new_selection()?.set_contents(contents)?;
+        }
+        Ok(())
+    }
+
+    /// Handle get and set requests from standard input, until it is closed.
+    fn serve() -> copypasta_ext::ClipResult<()> {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        let (mut input, mut output) = (stdin.lock(), stdout.lock());
+
+        // Keep connections for getting, each set forks a process holding the contents
+        let mut clipboard = X11ClipboardContext::<Clipboard>::new()?;
+        let mut primary = X11ClipboardContext::<Primary>::new()?;
+
+        let mut header = String::new();
+        loop {
+            header.clear();
+            if input.read_line(&mut header)? == 0 {
+                return Ok(());
+            }
+            let request: Vec<&str> = header.split_whitespace().collect();
+            let result = match request.as_slice() {
+                ["get", "clipboard"] => clipboard.get_contents(),
+                ["get", "primary"] => primary.get_contents(),
+                ["set", selection, len] => {
+                    let mut contents = vec![0; len.parse()?];
+                    input.read_exact(&mut contents)?;
+                    set(selection, contents).map(|_| String::new())
+                }
+                _ => return Err("invalid request".into()),
+            };
+
+            match result {
+                Ok(contents) => {
+                    writeln!(output, "ok {}", contents.len())?;
+                    output.write_all(contents.as_bytes())?;
+                }
+                Err(err) => writeln!(output, "err {}", err.to_string().replace('\n', " "))?,
+            }
+            output.flush()?;
+        }
+    }
+
+    /// Set the contents of the named selection through a forked process.
+    fn set(selection: &str, contents: Vec<u8>) -> copypasta_ext::ClipResult<()> {
+        let contents = String::from_utf8(contents)?;
+        match selection {
+            "clipboard" => {
+                X11ForkClipboardContext::<Clipboard>::new_selection()?.set_contents(contents)
+            }
+            "primary" => {
+                X11ForkClipboardContext::<Primary>::new_selection()?.set_contents(contents)
+            }
+            selection => Err(format!("unsupported selection: {}", selection).into()),
+        }
+    }
+
+    /// Print usage and exit.
+    fn usage() -> ! {
+        eprintln!("usage: copypasta-ext-x11-helper [-sel clipboard|primary] [-in|-out] | -serve");
+        process::exit(2);
+    }
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+fn main() {
+    x11::main()
+}
+
+#[cfg(not(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+)))]
+fn main() {
+    eprintln!("copypasta-ext-x11-helper: X11 is not supported on this platform");
+    std::process::exit(1);
+}
@@ -0,0 +1,283 @@
+//! Read clipboard contents from a user initiated terminal paste.
+//!
+//! This provider gets clipboard contents by asking the user to paste into the terminal. It uses
+//! [bracketed paste mode][bracketed-paste] to reliably capture exactly the pasted contents. This
+//! gives a way to read the clipboard in pure TTY and SSH environments, where querying the
+//! clipboard through OSC 52 is often disabled by the terminal.
+//!
+//! While getting, the terminal is switched to raw mode. The terminal state is restored
+//! afterwards. The controlling terminal (`/dev/tty`) is used, so this works even if standard
+//! input or output are redirected. Pressing `Ctrl+C` or `Ctrl+D` before pasting cancels.
+//!
+//! Setting clipboard contents is not supported through this context and will error. Combine it
+//! with [`Osc52ClipboardContext`][osc52] to support setting as well.
+//!
+//! ## Benefits
+//!
+//! - Works in any terminal supporting bracketed paste, including over SSH.
+//!
+//! ## Drawbacks
+//!
+//! - Requires user interaction for every get.
+//! - Requires terminal that supports bracketed paste mode.
+//! - Line breaks are reported by terminals as carriage returns, and are normalized to `\n`.
+//! - Cannot set clipboard contents.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::bracketed_paste::BracketedPasteClipboardContext;
+//!
+//! let mut ctx = BracketedPasteClipboardContext::new()
+//!     .unwrap()
+//!     .with_prompt("Paste your clipboard contents now...");
+//! println!("{:?}", ctx.get_contents());
+//! ```
+//!
+//! [bracketed-paste]: https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-Bracketed-Paste-Mode
+//! [osc52]: ../osc52/struct.Osc52ClipboardContext.html
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{Error as IoError, Read, Write};
+use std::mem::MaybeUninit;
+use std::os::unix::io::AsRawFd;
+use std::string::FromUtf8Error;
+use std::time::{Duration, Instant};
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Escape sequence to enable bracketed paste mode.
+const ENABLE: &[u8] = b"\x1B[?2004h";
+
+/// Escape sequence to disable bracketed paste mode.
+const DISABLE: &[u8] = b"\x1B[?2004l";
+
+/// Sequence the terminal sends before pasted contents.
+const PASTE_START: &[u8] = b"\x1B[200~";
+
+/// Sequence the terminal sends after pasted contents.
+const PASTE_END: &[u8] = b"\x1B[201~";
+
+/// Read clipboard contents from a user initiated terminal paste.
+///
+/// See module documentation for more information.
+#[derive(Default)]
+pub struct BracketedPasteClipboardContext {
+    prompt: Option<String>,
+    timeout: Option<Duration>,
+}
+
+impl BracketedPasteClipboardContext {
+    pub fn new() -> crate::ClipResult<Self> {
+        Ok(Self::default())
+    }
+
+    /// Show the given prompt on the terminal when waiting for the user to paste.
+    pub fn with_prompt<S>(mut self, prompt: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    /// Stop waiting for the user to paste after the given timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Wait for the user to paste, returning the pasted bytes.
+    fn read_paste(&self) -> Result<Vec<u8>, Error> {
+        let mut tty = RawTty::open()?;
+        if let Some(prompt) = &self.prompt {
+            tty.write(prompt.as_bytes())?;
+            tty.write(b"\r\n")?;
+        }
+        tty.write(ENABLE)?;
+
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let mut buf = Vec::new();
+        let mut started = false;
+        loop {
+            let byte = tty.read_byte(deadline)?;
+
+            if !started {
+                // Cancel on Ctrl+C or Ctrl+D, ignore other input until the paste starts
+                if byte == 0x03 || byte == 0x04 {
+                    return Err(Error::Cancelled);
+                }
+                buf.push(byte);
+                if buf.ends_with(PASTE_START) {
+                    started = true;
+                    buf.clear();
+                }
+                continue;
+            }
+
+            buf.push(byte);
+            if buf.ends_with(PASTE_END) {
+                buf.truncate(buf.len() - PASTE_END.len());
+                return Ok(buf);
+            }
+        }
+    }
+}
+
+impl ClipboardProvider for BracketedPasteClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        let contents = String::from_utf8(self.read_paste()?).map_err(Error::NoUtf8)?;
+
+        // Terminals report line breaks as carriage return
+        Ok(contents.replace("\r\n", "\n").replace('\r', "\n"))
+    }
+
+    fn set_contents(&mut self, _contents: String) -> crate::ClipResult<()> {
+        Err(Error::Unsupported.into())
+    }
+}
+
+impl ClipboardProviderNew for BracketedPasteClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        BracketedPasteClipboardContext::new()
+    }
+}
+
+impl ClipboardProviderExt for BracketedPasteClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        Some(DisplayServer::Tty)
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
+    }
+}
+
+/// Controlling terminal in raw mode.
+///
+/// Disables bracketed paste mode and restores the original terminal state on drop.
+struct RawTty {
+    tty: File,
+    original: libc::termios,
+}
+
+impl RawTty {
+    /// Open the controlling terminal, and switch it to raw mode.
+    fn open() -> Result<Self, Error> {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .map_err(Error::NoTty)?;
+        let fd = tty.as_raw_fd();
+
+        let mut original = MaybeUninit::uninit();
+        if unsafe { libc::tcgetattr(fd, original.as_mut_ptr()) } != 0 {
+            return Err(Error::NoTty(IoError::last_os_error()));
+        }
+        let original = unsafe { original.assume_init() };
+
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+            return Err(Error::Io(IoError::last_os_error()));
+        }
+
+        Ok(Self { tty, original })
+    }
+
+    /// Write and flush the given bytes to the terminal.
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.tty.write_all(bytes).map_err(Error::Io)?;
+        self.tty.flush().map_err(Error::Io)
+    }
+
+    /// Read a single byte, waiting until the given deadline if any.
+    fn read_byte(&mut self, deadline: Option<Instant>) -> Result<u8, Error> {
+        if let Some(deadline) = deadline {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let mut fds = libc::pollfd {
+                fd: self.tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout_ms = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+            match unsafe { libc::poll(&mut fds, 1, timeout_ms) } {
+                0 => return Err(Error::Timeout),
+                n if n < 0 => return Err(Error::Io(IoError::last_os_error())),
+                _ => {}
+            }
+        }
+
+        let mut byte = [0];
+        match self.tty.read(&mut byte).map_err(Error::Io)? {
+            0 => Err(Error::Cancelled),
+            _ => Ok(byte[0]),
+        }
+    }
+}
+
+impl Drop for RawTty {
+    fn drop(&mut self) {
+        let _ = self.write(DISABLE);
+        unsafe { libc::tcsetattr(self.tty.as_raw_fd(), libc::TCSANOW, &self.original) };
+    }
+}
+
+/// Represents bracketed paste clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Setting clipboard contents is not supported.
+    Unsupported,
+
+    /// No controlling terminal is available to paste into.
+    NoTty(IoError),
+
+    /// An error occurred while reading from or writing to the terminal.
+    Io(IoError),
+
+    /// The user cancelled instead of pasting.
+    Cancelled,
+
+    /// The user did not paste in time.
+    Timeout,
+
+    /// The pasted contents could not be parsed as valid UTF-8.
+    NoUtf8(FromUtf8Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unsupported => write!(
+                f,
+                "Setting clipboard contents is not supported through this context"
+            ),
+            Error::NoTty(err) => write!(f, "No terminal available to paste into: {}", err),
+            Error::Io(err) => write!(f, "Failed to read paste from terminal: {}", err),
+            Error::Cancelled => write!(f, "Paste was cancelled"),
+            Error::Timeout => write!(f, "Timed out waiting for paste"),
+            Error::NoUtf8(err) => write!(
+                f,
+                "Failed to parse clipboard contents as valid UTF-8: {}",
+                err
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::NoTty(err) | Error::Io(err) => Some(err),
+            Error::NoUtf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
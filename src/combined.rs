@@ -1,12 +1,46 @@
+//! Combine multiple clipboard contexts.
+//!
+//! - [`CombinedClipboardContext`]: use different context for getting and setting
+//! - [`ChainClipboardContext`]: try a sequence of contexts until one succeeds
+//! - [`FallbackClipboardContext`]: use a fallback context when the preferred one fails
+//! - [`MultiSetClipboardContext`]: set contents on multiple contexts at once
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use std::io::{Read, Write};
+
 use copypasta::ClipboardProvider;
 
+use crate::content::ClipboardContent;
+use crate::display::DisplayServer;
+use crate::{
+    ChangeToken, ClipboardProviderExt, ClipboardProviderNew, ContentLifetime, Selection,
+    ThreadAffinity,
+};
+
 /// Combined, use different clipboard context for getting & setting.
 ///
 /// Useful to combine different clipboard contexts to get the best of both worlds.
 ///
 /// This may be constructed using helpers such as
 /// [`X11BinClipboardContext::new_with_x11`][new_with_x11] or
-/// [`X11BinClipboardContext::with_x11`][with_x11].
+/// [`X11BinClipboardContext::with_x11`][with_x11]. If both contexts can be constructed without
+/// arguments, [`CombinedClipboardContext::new`] may be used as well.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::copypasta::x11_clipboard::X11ClipboardContext;
+/// use copypasta_ext::x11_fork::X11ForkClipboardContext;
+/// use copypasta_ext::CombinedClipboardContext;
+///
+/// let mut ctx: CombinedClipboardContext<X11ClipboardContext, X11ForkClipboardContext> =
+///     CombinedClipboardContext::new().unwrap();
+/// println!("{:?}", ctx.get_contents());
+/// ctx.set_contents("some string".into()).unwrap();
+/// ```
 ///
 /// [new_with_x11]: ../copypasta_ext/x11_bin/struct.X11BinClipboardContext.html#method.new_with_x11
 /// [with_x11]: ../copypasta_ext/x11_bin/struct.X11BinClipboardContext.html#method.with_x11
@@ -15,15 +49,55 @@ where
     G: ClipboardProvider,
     S: ClipboardProvider;
 
-// impl<G, S> CombinedClipboardContext<G, S>
-// where
-//     G: ClipboardProvider,
-//     S: ClipboardProvider,
-// {
-//     pub fn new() -> Result<Self, Box<dyn Error>> {
-//         Ok(Self(G::new()?, S::new()?))
-//     }
-// }
+impl<G, S> CombinedClipboardContext<G, S>
+where
+    G: ClipboardProvider,
+    S: ClipboardProvider,
+{
+    /// Construct combined context, constructing both the getter and setter context.
+    pub fn new() -> crate::ClipResult<Self>
+    where
+        G: ClipboardProviderNew,
+        S: ClipboardProviderNew,
+    {
+        Ok(Self(G::new()?, S::new()?))
+    }
+
+    /// Get the context used for getting clipboard contents.
+    pub fn getter(&self) -> &G {
+        &self.0
+    }
+
+    /// Get the context used for getting clipboard contents, mutably.
+    pub fn getter_mut(&mut self) -> &mut G {
+        &mut self.0
+    }
+
+    /// Get the context used for setting clipboard contents.
+    pub fn setter(&self) -> &S {
+        &self.1
+    }
+
+    /// Get the context used for setting clipboard contents, mutably.
+    pub fn setter_mut(&mut self) -> &mut S {
+        &mut self.1
+    }
+
+    /// Unwrap, returning the getter and setter context.
+    pub fn into_inner(self) -> (G, S) {
+        (self.0, self.1)
+    }
+}
+
+impl<G, S> From<(G, S)> for CombinedClipboardContext<G, S>
+where
+    G: ClipboardProvider,
+    S: ClipboardProvider,
+{
+    fn from((get, set): (G, S)) -> Self {
+        Self(get, set)
+    }
+}
 
 impl<G, S> ClipboardProvider for CombinedClipboardContext<G, S>
 where
@@ -38,3 +112,413 @@ where
         self.1.set_contents(contents)
     }
 }
+
+/// Properties are reported from the context used for setting, as that determines how contents
+/// are stored.
+impl<G, S> ClipboardProviderExt for CombinedClipboardContext<G, S>
+where
+    G: ClipboardProviderExt,
+    S: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.1.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.1.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.0.requires_event_loop() || self.1.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.0.thread_affinity().max(self.1.thread_affinity())
+    }
+
+    fn selection(&self) -> Selection {
+        self.0.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.0.change_token()
+    }
+
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        self.0.content_hash()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.0.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.0.available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.1.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.1.clear_contents()
+    }
+
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> crate::ClipResult<()> {
+        self.1.set_contents_from(reader)
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> crate::ClipResult<()> {
+        self.0.get_contents_to(writer)
+    }
+
+    fn get_content(&mut self) -> crate::ClipResult<ClipboardContent> {
+        self.0.get_content()
+    }
+
+    fn set_content(&mut self, content: ClipboardContent) -> crate::ClipResult<()> {
+        self.1.set_content(content)
+    }
+}
+
+impl<G, S> ClipboardProviderNew for CombinedClipboardContext<G, S>
+where
+    G: ClipboardProviderNew,
+    S: ClipboardProviderNew,
+{
+    fn new() -> crate::ClipResult<Self> {
+        CombinedClipboardContext::new()
+    }
+}
+
+/// Chained, try a sequence of clipboard contexts until one succeeds.
+///
+/// Getting or setting the clipboard contents is attempted on each context in order, the result of
+/// the first context that succeeds is returned. If all contexts fail, an [`Error::Chain`] holding
+/// all errors is returned.
+///
+/// Combine with [`CombinedClipboardContext`] to use a chain for getting contents only.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::copypasta::x11_clipboard::X11ClipboardContext;
+/// use copypasta_ext::x11_bin::X11BinClipboardContext;
+/// use copypasta_ext::ChainClipboardContext;
+///
+/// let x11: X11ClipboardContext = X11ClipboardContext::new().unwrap();
+/// let mut ctx = ChainClipboardContext::new(x11).or(X11BinClipboardContext::new().unwrap());
+/// println!("{:?}", ctx.get_contents());
+/// ```
+pub struct ChainClipboardContext(Vec<Box<dyn ClipboardProvider>>);
+
+impl ChainClipboardContext {
+    /// Construct chain, starting with the given context.
+    pub fn new<C>(context: C) -> Self
+    where
+        C: ClipboardProvider + 'static,
+    {
+        Self(vec![Box::new(context)])
+    }
+
+    /// Append a context to fall back to.
+    pub fn or<C>(mut self, context: C) -> Self
+    where
+        C: ClipboardProvider + 'static,
+    {
+        self.push(context);
+        self
+    }
+
+    /// Append a context to fall back to.
+    pub fn push<C>(&mut self, context: C)
+    where
+        C: ClipboardProvider + 'static,
+    {
+        self.0.push(Box::new(context));
+    }
+
+    /// Try the given operation on each context in order.
+    fn try_each<T, F>(&mut self, mut f: F) -> crate::ClipResult<T>
+    where
+        F: FnMut(&mut dyn ClipboardProvider) -> crate::ClipResult<T>,
+    {
+        let mut errs = Vec::new();
+        for context in self.0.iter_mut() {
+            match f(context.as_mut()) {
+                Ok(result) => return Ok(result),
+                Err(err) => errs.push(err),
+            }
+        }
+        Err(Error::Chain(errs).into())
+    }
+}
+
+impl ClipboardProvider for ChainClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.try_each(|context| context.get_contents())
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.try_each(|context| context.set_contents(contents.clone()))
+    }
+}
+
+/// Fallback, use a second clipboard context when the first one fails.
+///
+/// Like a [`ChainClipboardContext`] of two contexts, but also reports the properties of the
+/// preferred context, so it can be used where a [`ClipboardProviderExt`] is required. If both
+/// contexts fail, an [`Error::Chain`] holding both errors is returned.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::copypasta::x11_clipboard::X11ClipboardContext;
+/// use copypasta_ext::x11_bin::X11BinClipboardContext;
+/// use copypasta_ext::FallbackClipboardContext;
+///
+/// let x11: X11ClipboardContext = X11ClipboardContext::new().unwrap();
+/// let mut ctx = FallbackClipboardContext(x11, X11BinClipboardContext::new().unwrap());
+/// println!("{:?}", ctx.get_contents());
+/// ```
+pub struct FallbackClipboardContext<P, F>(pub P, pub F)
+where
+    P: ClipboardProvider,
+    F: ClipboardProvider;
+
+impl<P, F> FallbackClipboardContext<P, F>
+where
+    P: ClipboardProvider,
+    F: ClipboardProvider,
+{
+    /// Try the given operation on the preferred context, then on the fallback context.
+    fn try_both<T, O>(&mut self, mut f: O) -> crate::ClipResult<T>
+    where
+        O: FnMut(&mut dyn ClipboardProvider) -> crate::ClipResult<T>,
+    {
+        let err = match f(&mut self.0) {
+            Ok(result) => return Ok(result),
+            Err(err) => err,
+        };
+        debug!(
+            "preferred clipboard context failed, using fallback: {}",
+            err
+        );
+        f(&mut self.1).map_err(|fallback_err| Error::Chain(vec![err, fallback_err]).into())
+    }
+}
+
+impl<P, F> FallbackClipboardContext<P, F>
+where
+    P: ClipboardProviderExt,
+    F: ClipboardProviderExt,
+{
+    /// Like [`try_both`](FallbackClipboardContext::try_both), for extended operations.
+    fn try_both_ext<T, O>(&mut self, mut f: O) -> crate::ClipResult<T>
+    where
+        O: FnMut(&mut dyn ClipboardProviderExt) -> crate::ClipResult<T>,
+    {
+        let err = match f(&mut self.0) {
+            Ok(result) => return Ok(result),
+            Err(err) => err,
+        };
+        debug!(
+            "preferred clipboard context failed, using fallback: {}",
+            err
+        );
+        f(&mut self.1).map_err(|fallback_err| Error::Chain(vec![err, fallback_err]).into())
+    }
+}
+
+impl<P, F> ClipboardProvider for FallbackClipboardContext<P, F>
+where
+    P: ClipboardProvider,
+    F: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.try_both(|context| context.get_contents())
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.try_both(|context| context.set_contents(contents.clone()))
+    }
+}
+
+/// Properties are reported from the preferred context. Operations use the fallback context when
+/// the preferred one fails, like getting and setting.
+impl<P, F> ClipboardProviderExt for FallbackClipboardContext<P, F>
+where
+    P: ClipboardProviderExt,
+    F: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.0.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.0.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.0.requires_event_loop() || self.1.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.0.thread_affinity().max(self.1.thread_affinity())
+    }
+
+    fn selection(&self) -> Selection {
+        self.0.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.try_both_ext(|context| context.change_token())
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.try_both_ext(|context| context.is_empty())
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.try_both_ext(|context| context.available_mime_types())
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.try_both_ext(|context| context.persist())
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.try_both_ext(|context| context.clear_contents())
+    }
+
+    fn get_content(&mut self) -> crate::ClipResult<ClipboardContent> {
+        self.try_both_ext(|context| context.get_content())
+    }
+
+    fn set_content(&mut self, content: ClipboardContent) -> crate::ClipResult<()> {
+        self.try_both_ext(|context| context.set_content(content.clone()))
+    }
+}
+
+impl<P, F> ClipboardProviderNew for FallbackClipboardContext<P, F>
+where
+    P: ClipboardProviderNew,
+    F: ClipboardProviderNew,
+{
+    fn new() -> crate::ClipResult<Self> {
+        Ok(FallbackClipboardContext(P::new()?, F::new()?))
+    }
+}
+
+/// Multi set, set contents on multiple clipboard contexts at once.
+///
+/// Contents are get from the first context only. When setting contents, they are set on all
+/// contexts in order. This is useful to set multiple selections at once, or to set both a local
+/// and remote clipboard, such as a native context combined with OSC 52.
+///
+/// Setting is attempted on every context, even if one of them fails. If any fails, an
+/// [`Error::MultiSet`] holding the errors is returned.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::copypasta::x11_clipboard::{Primary, X11ClipboardContext};
+/// use copypasta_ext::x11_fork::X11ForkClipboardContext;
+/// use copypasta_ext::MultiSetClipboardContext;
+///
+/// let clipboard: X11ForkClipboardContext = X11ForkClipboardContext::new().unwrap();
+/// let primary: X11ClipboardContext<Primary> = X11ClipboardContext::new().unwrap();
+/// let mut ctx = MultiSetClipboardContext::new(clipboard).and(primary);
+/// ctx.set_contents("some string".into()).unwrap();
+/// ```
+pub struct MultiSetClipboardContext(Vec<Box<dyn ClipboardProvider>>);
+
+impl MultiSetClipboardContext {
+    /// Construct multi set, using the given context for getting and setting contents.
+    pub fn new<C>(context: C) -> Self
+    where
+        C: ClipboardProvider + 'static,
+    {
+        Self(vec![Box::new(context)])
+    }
+
+    /// Append a context to also set contents on.
+    pub fn and<C>(mut self, context: C) -> Self
+    where
+        C: ClipboardProvider + 'static,
+    {
+        self.push(context);
+        self
+    }
+
+    /// Append a context to also set contents on.
+    pub fn push<C>(&mut self, context: C)
+    where
+        C: ClipboardProvider + 'static,
+    {
+        self.0.push(Box::new(context));
+    }
+}
+
+impl ClipboardProvider for MultiSetClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.0[0].get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        let errs: Vec<_> = self
+            .0
+            .iter_mut()
+            .filter_map(|context| context.set_contents(contents.clone()).err())
+            .collect();
+        if errs.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::MultiSet(errs).into())
+        }
+    }
+}
+
+/// Represents combined clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// All contexts in a chain failed, holds the error of each context in order.
+    Chain(Vec<Box<dyn StdError + Send + Sync + 'static>>),
+
+    /// Setting contents failed on some contexts of a multi set, holds the error of each failed
+    /// context in order.
+    MultiSet(Vec<Box<dyn StdError + Send + Sync + 'static>>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Chain(errs) => {
+                write!(f, "All clipboard contexts in chain failed")?;
+                for err in errs {
+                    write!(f, "; {}", err)?;
+                }
+                Ok(())
+            }
+            Error::MultiSet(errs) => {
+                write!(f, "Failed to set contents on some clipboard contexts")?;
+                for err in errs {
+                    write!(f, "; {}", err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Chain(errs) | Error::MultiSet(errs) => errs.last().map(|err| err.as_ref() as _),
+        }
+    }
+}
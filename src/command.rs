@@ -0,0 +1,294 @@
+//! Utilities for spawning clipboard binaries.
+//!
+//! Used by clipboard contexts that invoke external binaries to manage the clipboard, such as
+//! [`x11_bin`](crate::x11_bin) and [`wayland_bin`](crate::wayland_bin).
+
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::Arc;
+
+use crate::config::{BinaryPaths, ClipboardConfig};
+
+/// Environment variables kept when sanitizing the environment of spawned clipboard binaries.
+///
+/// These are required for clipboard binaries to be found, and to connect to the display server.
+pub const SANITIZED_ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "DISPLAY",
+    "XAUTHORITY",
+    "WAYLAND_DISPLAY",
+    "XDG_RUNTIME_DIR",
+    "XDG_SESSION_TYPE",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "WSL_DISTRO_NAME",
+    "WSL_INTEROP",
+];
+
+/// Sanitize the environment of the given command.
+///
+/// Clears the environment the command would inherit, and only passes the variables listed in
+/// [`SANITIZED_ENV_ALLOWLIST`]. Dangerous variables such as `LD_PRELOAD` and `LD_LIBRARY_PATH`
+/// are not passed to the spawned binary.
+///
+/// ```rust
+/// use std::process::Command;
+///
+/// let mut command = Command::new("xclip");
+/// copypasta_ext::command::sanitize_env(&mut command);
+/// ```
+pub fn sanitize_env(command: &mut Command) {
+    command.env_clear();
+    for key in SANITIZED_ENV_ALLOWLIST {
+        if let Some(value) = env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+}
+
+/// Reset signal dispositions of the given command to their default.
+///
+/// Signals ignored by the current process, such as `SIGCHLD` or `SIGPIPE` in daemons, would stay
+/// ignored in the spawned binary, which may cause it to hang. Their dispositions are reset, and
+/// all signals are unblocked, before the binary is executed. Clipboard contexts always do this for
+/// the binaries they spawn.
+///
+/// ```rust
+/// use std::process::Command;
+///
+/// let mut command = Command::new("xclip");
+/// copypasta_ext::command::reset_signals(&mut command);
+/// ```
+pub fn reset_signals(command: &mut Command) {
+    unsafe {
+        command.pre_exec(|| {
+            crate::process::reset_signals();
+            Ok(())
+        });
+    }
+}
+
+/// Runs clipboard binaries.
+///
+/// Clipboard contexts invoking external binaries spawn them through a runner. The default
+/// [`SystemCommandRunner`] uses [`std::process::Command`]. A custom runner may be provided to mock
+/// subprocess behavior in tests, or to route execution through a broker in sandboxed
+/// applications.
+pub trait CommandRunner: Send + Sync {
+    /// Run the given command to completion, returning its exit status.
+    ///
+    /// If `stdin` is given, it is streamed into the standard input of the command, which is
+    /// closed afterwards. If `stdout` is given, the standard output of the command is streamed
+    /// into it. Standard input is fully written before standard output is read. Standard input
+    /// and output not given should be null.
+    fn run(
+        &self,
+        command: &mut Command,
+        stdin: Option<&mut dyn Read>,
+        stdout: Option<&mut dyn Write>,
+    ) -> io::Result<ExitStatus>;
+}
+
+/// Runs clipboard binaries as regular child processes.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn run(
+        &self,
+        command: &mut Command,
+        stdin: Option<&mut dyn Read>,
+        stdout: Option<&mut dyn Write>,
+    ) -> io::Result<ExitStatus> {
+        let mut process = command
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(if stdout.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .spawn()?;
+
+        // Don't leave the process behind on failure
+        if let Err(err) = stream(&mut process, stdin, stdout) {
+            let _ = process.kill();
+            let _ = process.wait();
+            return Err(err);
+        }
+
+        process.wait()
+    }
+}
+
+/// Stream the given input and output through the process.
+fn stream(
+    process: &mut Child,
+    stdin: Option<&mut dyn Read>,
+    stdout: Option<&mut dyn Write>,
+) -> io::Result<()> {
+    // Close stdin after streaming to signal the end
+    if let Some(reader) = stdin {
+        let mut pipe = process.stdin.take().unwrap();
+        io::copy(reader, &mut pipe)?;
+    }
+    if let Some(writer) = stdout {
+        io::copy(process.stdout.as_mut().unwrap(), writer)?;
+    }
+    Ok(())
+}
+
+/// Finds clipboard binaries by name.
+///
+/// Clipboard contexts invoking external binaries find them through a lookup. The default
+/// [`PathLookup`] searches the `PATH` of the current process. A [`SearchPath`] searches an explicit
+/// list of directories instead, which matters for AppImages and daemons started with a stripped
+/// environment. Any `Fn(&str) -> Option<PathBuf>` closure may be used as custom lookup.
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use copypasta_ext::x11_bin::X11BinClipboardContext;
+///
+/// let ctx = X11BinClipboardContext::new()
+///     .unwrap()
+///     .with_binary_lookup(|bin: &str| Some(PathBuf::from("/opt/app/bin").join(bin)));
+/// assert_eq!(ctx.binary().1, Some(PathBuf::from("/opt/app/bin/xclip")));
+/// ```
+pub trait BinaryLookup: Send + Sync {
+    /// Find the binary with the given name, returning its path.
+    fn find(&self, bin: &str) -> Option<PathBuf>;
+}
+
+impl<F> BinaryLookup for F
+where
+    F: Fn(&str) -> Option<PathBuf> + Send + Sync,
+{
+    fn find(&self, bin: &str) -> Option<PathBuf> {
+        self(bin)
+    }
+}
+
+/// Finds binaries in the `PATH` of the current process.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PathLookup;
+
+impl BinaryLookup for PathLookup {
+    fn find(&self, bin: &str) -> Option<PathBuf> {
+        which::which(bin).ok()
+    }
+}
+
+/// Finds binaries in an explicit list of directories, searched in order.
+///
+/// The `PATH` of the current process is not searched.
+#[derive(Clone, Debug, Default)]
+pub struct SearchPath(pub Vec<PathBuf>);
+
+impl SearchPath {
+    /// Construct from the given directories.
+    pub fn new<I, P>(dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self(dirs.into_iter().map(Into::into).collect())
+    }
+}
+
+impl BinaryLookup for SearchPath {
+    fn find(&self, bin: &str) -> Option<PathBuf> {
+        let paths = env::join_paths(&self.0).ok()?;
+        which::which_in(bin, Some(paths), env::current_dir().unwrap_or_default()).ok()
+    }
+}
+
+/// Options for spawning clipboard binaries, shared by clipboard contexts invoking them.
+#[derive(Clone)]
+pub(crate) struct CommandOptions {
+    /// The runner to spawn binaries with.
+    pub runner: Arc<dyn CommandRunner>,
+
+    /// Sanitize the environment of spawned binaries.
+    pub sanitize_env: bool,
+
+    /// The lookup to find binaries with.
+    pub lookup: Arc<dyn BinaryLookup>,
+
+    /// Binary paths to use instead of the lookup.
+    pub paths: BinaryPaths,
+
+    /// Extra arguments appended to invocations for getting contents, not supported by all
+    /// contexts.
+    #[allow(dead_code)]
+    pub get_args: Vec<OsString>,
+
+    /// Extra arguments appended to invocations for setting contents, not supported by all
+    /// contexts.
+    #[allow(dead_code)]
+    pub set_args: Vec<OsString>,
+
+    /// Environment variables set for spawned binaries, such as to select a display.
+    pub env: Vec<(OsString, OsString)>,
+}
+
+impl CommandOptions {
+    /// Apply the options of the given configuration.
+    pub fn with_config(mut self, config: &ClipboardConfig) -> Self {
+        self.sanitize_env = self.sanitize_env || config.sanitize_env;
+        if !config.search_path.is_empty() {
+            self.lookup = Arc::new(SearchPath(config.search_path.clone()));
+        }
+        self.paths = config.binaries.clone();
+        self
+    }
+
+    /// Prepare the given command according to these options.
+    pub fn prepare(&self, command: &mut Command) {
+        reset_signals(command);
+        if self.sanitize_env {
+            sanitize_env(command);
+        }
+        for (key, value) in &self.env {
+            command.env(key, value);
+        }
+    }
+
+    /// The value of the given environment variable as seen by spawned binaries.
+    #[allow(dead_code)]
+    pub fn env_var(&self, key: &str) -> Option<OsString> {
+        match self.env.iter().rev().find(|(k, _)| k == key) {
+            Some((_, value)) => Some(value.clone()),
+            None => env::var_os(key),
+        }
+    }
+
+    /// Find the binary with the given name through the configured lookup.
+    pub fn find(&self, bin: &str) -> Option<PathBuf> {
+        self.lookup.find(bin)
+    }
+}
+
+impl Default for CommandOptions {
+    fn default() -> Self {
+        Self {
+            runner: Arc::new(SystemCommandRunner),
+            sanitize_env: false,
+            lookup: Arc::new(PathLookup),
+            paths: BinaryPaths::default(),
+            get_args: Vec::new(),
+            set_args: Vec::new(),
+            env: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,399 @@
+//! Configure clipboard contexts from one place.
+//!
+//! [`ClipboardConfig`] holds the options of all clipboard contexts, such as timeouts, binary
+//! paths and the order of backends to try. Build it programmatically, load it from environment
+//! variables at runtime with [`ClipboardConfig::from_env`], or deserialize it from a configuration
+//! file with the `serde` feature.
+//!
+//! Pass it to [`try_context_with_config`](crate::try_context_with_config) to select a context, or
+//! to the `with_config` builder of a specific context, such as
+//! `X11BinClipboardContext::with_config`. Each context applies the options relevant to it.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use copypasta_ext::config::ClipboardConfig;
+//!
+//! let config = ClipboardConfig::new()
+//!     .with_backends(&["wayland-bin", "x11-bin"])
+//!     .with_ownership_timeout(Duration::from_secs(1))
+//!     .with_env()
+//!     .unwrap();
+//! let mut ctx = copypasta_ext::try_context_with_config(&config).unwrap();
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+//!
+//! # Environment variables
+//!
+//! [`ClipboardConfig::with_env`] reads the following variables, overriding options they are set
+//! for:
+//!
+//! - `COPYPASTA_EXT_BACKENDS`: comma separated backend names, such as `x11-bin,x11`
+//! - `COPYPASTA_EXT_DISPLAY_SERVER`: display server name, such as `wayland`
+//! - `COPYPASTA_EXT_SELECTION`: `clipboard`, `primary` or `secondary`
+//! - `COPYPASTA_EXT_GET_TIMEOUT` and `COPYPASTA_EXT_OWNERSHIP_TIMEOUT`: in milliseconds
+//! - `COPYPASTA_EXT_MAX_SIZE`: in bytes
+//! - `COPYPASTA_EXT_SEARCH_PATH`: directories to find binaries in, separated like `PATH`
+//! - `COPYPASTA_EXT_SANITIZE_ENV`: `1` or `true` to sanitize the environment of binaries
+//! - `XCLIP_PATH`, `XSEL_PATH`, `WL_COPY_PATH`, `WL_PASTE_PATH` and `X11_HELPER_PATH`: binary
+//!   paths
+//!
+//! The binary path variables may also be set at compile time, which sets the default
+//! [`BinaryPaths`].
+
+use std::env;
+use std::error::Error as StdError;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::display::DisplayServer;
+use crate::Selection;
+
+/// Options for clipboard contexts.
+///
+/// See module documentation for more information.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "kebab-case")
+)]
+#[non_exhaustive]
+pub struct ClipboardConfig {
+    /// Backends to try in order when selecting a context, such as `x11-fork` or `wayland-bin`.
+    ///
+    /// Names match those in a [`SelectionReport`](crate::display::SelectionReport). Backends not
+    /// listed are skipped. Empty to try all backends in the default order.
+    pub backends: Vec<String>,
+
+    /// The display server to select a context for, `None` to detect it.
+    pub display_server: Option<DisplayServer>,
+
+    /// The selection to access.
+    pub selection: Selection,
+
+    /// Timeout for getting contents, on contexts supporting it.
+    pub get_timeout: Option<Duration>,
+
+    /// Wait until set contents are served, up to this timeout, on contexts supporting it.
+    pub ownership_timeout: Option<Duration>,
+
+    /// Maximum size in bytes of contents to set, `None` for no limit.
+    pub max_size: Option<usize>,
+
+    /// Paths of clipboard binaries.
+    pub binaries: BinaryPaths,
+
+    /// Directories to find clipboard binaries in, instead of `PATH`. Empty to use `PATH`.
+    pub search_path: Vec<PathBuf>,
+
+    /// Sanitize the environment of spawned clipboard binaries.
+    pub sanitize_env: bool,
+}
+
+impl ClipboardConfig {
+    /// Construct with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct with default options, overridden by environment variables.
+    ///
+    /// See [`with_env`](ClipboardConfig::with_env).
+    pub fn from_env() -> Result<Self, Error> {
+        Self::new().with_env()
+    }
+
+    /// Override options by the environment variables that are set.
+    ///
+    /// See module documentation for the variables. Errors if a variable holds an invalid value.
+    ///
+    /// ```rust
+    /// use copypasta_ext::Selection;
+    /// use copypasta_ext::config::ClipboardConfig;
+    ///
+    /// std::env::set_var("COPYPASTA_EXT_SELECTION", "primary");
+    /// let config = ClipboardConfig::new().with_env().unwrap();
+    /// assert_eq!(config.selection, Selection::Primary);
+    /// ```
+    pub fn with_env(mut self) -> Result<Self, Error> {
+        if let Some(backends) = var("COPYPASTA_EXT_BACKENDS")? {
+            self.backends = backends
+                .split(',')
+                .map(str::trim)
+                .filter(|backend| !backend.is_empty())
+                .map(Into::into)
+                .collect();
+        }
+        if let Some(value) = var("COPYPASTA_EXT_DISPLAY_SERVER")? {
+            let display_server = parse_display_server(&value)
+                .ok_or_else(|| Error::invalid("COPYPASTA_EXT_DISPLAY_SERVER", value))?;
+            self.display_server = Some(display_server);
+        }
+        if let Some(value) = var("COPYPASTA_EXT_SELECTION")? {
+            self.selection = parse_selection(&value)
+                .ok_or_else(|| Error::invalid("COPYPASTA_EXT_SELECTION", value))?;
+        }
+        if let Some(timeout) = var_parse::<u64>("COPYPASTA_EXT_GET_TIMEOUT")? {
+            self.get_timeout = Some(Duration::from_millis(timeout));
+        }
+        if let Some(timeout) = var_parse::<u64>("COPYPASTA_EXT_OWNERSHIP_TIMEOUT")? {
+            self.ownership_timeout = Some(Duration::from_millis(timeout));
+        }
+        if let Some(max_size) = var_parse("COPYPASTA_EXT_MAX_SIZE")? {
+            self.max_size = Some(max_size);
+        }
+        if let Some(search_path) = var_os("COPYPASTA_EXT_SEARCH_PATH") {
+            self.search_path = env::split_paths(&search_path).collect();
+        }
+        if let Some(value) = var("COPYPASTA_EXT_SANITIZE_ENV")? {
+            self.sanitize_env = match value.as_str() {
+                "1" | "true" => true,
+                "0" | "false" => false,
+                _ => return Err(Error::invalid("COPYPASTA_EXT_SANITIZE_ENV", value)),
+            };
+        }
+        self.binaries = self.binaries.with_env();
+        Ok(self)
+    }
+
+    /// Try the given backends in order when selecting a context.
+    pub fn with_backends<S>(mut self, backends: &[S]) -> Self
+    where
+        S: AsRef<str>,
+    {
+        self.backends = backends.iter().map(|b| b.as_ref().into()).collect();
+        self
+    }
+
+    /// Select a context for the given display server, instead of detecting it.
+    pub fn with_display_server(mut self, display_server: DisplayServer) -> Self {
+        self.display_server = Some(display_server);
+        self
+    }
+
+    /// Access the given selection.
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Time out getting contents after the given duration, on contexts supporting it.
+    pub fn with_get_timeout(mut self, timeout: Duration) -> Self {
+        self.get_timeout = Some(timeout);
+        self
+    }
+
+    /// Wait until set contents are served, up to the given timeout, on contexts supporting it.
+    pub fn with_ownership_timeout(mut self, timeout: Duration) -> Self {
+        self.ownership_timeout = Some(timeout);
+        self
+    }
+
+    /// Refuse to set contents larger than the given size in bytes.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Use the given paths of clipboard binaries.
+    pub fn with_binaries(mut self, binaries: BinaryPaths) -> Self {
+        self.binaries = binaries;
+        self
+    }
+
+    /// Find clipboard binaries in the given directories, instead of `PATH`.
+    pub fn with_search_path<I, P>(mut self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.search_path = dirs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sanitize the environment of spawned clipboard binaries.
+    pub fn with_sanitized_env(mut self) -> Self {
+        self.sanitize_env = true;
+        self
+    }
+
+    /// Whether the given backend should be tried when selecting a context.
+    pub(crate) fn allows_backend(&self, backend: &str) -> bool {
+        self.backends.is_empty() || self.backends.iter().any(|b| b == backend)
+    }
+}
+
+/// Paths of clipboard binaries.
+///
+/// A binary without a path is found through `PATH` or the configured search path. Defaults to
+/// the paths set at compile time through the `XCLIP_PATH`, `XSEL_PATH`, `WL_COPY_PATH`,
+/// `WL_PASTE_PATH` and `X11_HELPER_PATH` variables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "kebab-case")
+)]
+#[non_exhaustive]
+pub struct BinaryPaths {
+    /// Path of `xclip`.
+    pub xclip: Option<PathBuf>,
+
+    /// Path of `xsel`.
+    pub xsel: Option<PathBuf>,
+
+    /// Path of `wl-copy`.
+    pub wl_copy: Option<PathBuf>,
+
+    /// Path of `wl-paste`.
+    pub wl_paste: Option<PathBuf>,
+
+    /// Path of the helper binary shipped with this crate, see the `x11-helper` feature.
+    pub x11_helper: Option<PathBuf>,
+}
+
+impl BinaryPaths {
+    /// Construct without any paths, to find all binaries at runtime.
+    pub fn none() -> Self {
+        Self {
+            xclip: None,
+            xsel: None,
+            wl_copy: None,
+            wl_paste: None,
+            x11_helper: None,
+        }
+    }
+
+    /// Override paths by the environment variables that are set at runtime.
+    fn with_env(mut self) -> Self {
+        let paths = [
+            ("XCLIP_PATH", &mut self.xclip),
+            ("XSEL_PATH", &mut self.xsel),
+            ("WL_COPY_PATH", &mut self.wl_copy),
+            ("WL_PASTE_PATH", &mut self.wl_paste),
+            ("X11_HELPER_PATH", &mut self.x11_helper),
+        ];
+        for (key, path) in paths {
+            if let Some(value) = var_os(key) {
+                *path = Some(value.into());
+            }
+        }
+        self
+    }
+}
+
+impl Default for BinaryPaths {
+    fn default() -> Self {
+        let path = |value: Option<&str>| {
+            value
+                .filter(|path| !path.trim().is_empty())
+                .map(PathBuf::from)
+        };
+        Self {
+            xclip: path(option_env!("XCLIP_PATH")),
+            xsel: path(option_env!("XSEL_PATH")),
+            wl_copy: path(option_env!("WL_COPY_PATH")),
+            wl_paste: path(option_env!("WL_PASTE_PATH")),
+            x11_helper: path(option_env!("X11_HELPER_PATH")),
+        }
+    }
+}
+
+/// Get a non-empty environment variable.
+fn var_os(key: &str) -> Option<OsString> {
+    env::var_os(key).filter(|value| !value.is_empty())
+}
+
+/// Get a non-empty environment variable as string.
+fn var(key: &'static str) -> Result<Option<String>, Error> {
+    var_os(key)
+        .map(|value| {
+            value
+                .into_string()
+                .map_err(|value| Error::invalid(key, value.to_string_lossy()))
+        })
+        .transpose()
+}
+
+/// Get and parse a non-empty environment variable.
+fn var_parse<T>(key: &'static str) -> Result<Option<T>, Error>
+where
+    T: std::str::FromStr,
+{
+    var(key)?
+        .map(|value| value.trim().parse().map_err(|_| Error::invalid(key, value)))
+        .transpose()
+}
+
+/// Parse a display server name, as serialized with the `serde` feature.
+fn parse_display_server(name: &str) -> Option<DisplayServer> {
+    match name.trim().to_lowercase().as_str() {
+        "x11" => Some(DisplayServer::X11),
+        "wayland" => Some(DisplayServer::Wayland),
+        "macos" => Some(DisplayServer::MacOs),
+        "windows" => Some(DisplayServer::Windows),
+        "tty" => Some(DisplayServer::Tty),
+        "wsl" => Some(DisplayServer::Wsl),
+        _ => None,
+    }
+}
+
+/// Parse a selection name, as serialized with the `serde` feature.
+fn parse_selection(name: &str) -> Option<Selection> {
+    match name.trim().to_lowercase().as_str() {
+        "clipboard" => Some(Selection::Clipboard),
+        "primary" => Some(Selection::Primary),
+        "secondary" => Some(Selection::Secondary),
+        _ => None,
+    }
+}
+
+/// Represents clipboard configuration related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An environment variable holds an invalid value.
+    InvalidEnv {
+        /// The environment variable.
+        key: &'static str,
+
+        /// The invalid value.
+        value: String,
+    },
+}
+
+impl Error {
+    fn invalid<V>(key: &'static str, value: V) -> Self
+    where
+        V: Into<String>,
+    {
+        Error::InvalidEnv {
+            key,
+            value: value.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidEnv { key, value } => {
+                write!(
+                    f,
+                    "Invalid value for {} environment variable: {:?}",
+                    key, value
+                )
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
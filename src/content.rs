@@ -0,0 +1,578 @@
+//! Clipboard content type detection.
+//!
+//! Clipboard contents fetched as raw bytes don't carry their type. [`sniff`] guesses the type
+//! of such contents based on magic bytes and simple heuristics, to pick a sensible default
+//! representation.
+//!
+//! [`ClipboardContent`] represents whatever is on the clipboard as a single value, see
+//! [`get_content`](crate::ClipboardProviderExt::get_content) and
+//! [`set_content`](crate::ClipboardProviderExt::set_content).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use copypasta_ext::content::{self, ContentType, ImageFormat};
+//!
+//! assert_eq!(content::sniff(b"some string"), ContentType::Text);
+//! assert_eq!(content::sniff(b"<b>some string</b>"), ContentType::Html);
+//! assert_eq!(content::sniff(b"{\\rtf1\\ansi some string}"), ContentType::Rtf);
+//! assert_eq!(content::sniff(b"\x89PNG\r\n\x1a\n"), ContentType::Image(ImageFormat::Png));
+//! assert_eq!(content::sniff(b"file:///tmp/a.txt\r\n"), ContentType::UriList);
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::path::PathBuf;
+use std::str;
+use std::string::FromUtf8Error;
+
+use crate::uri;
+
+/// Guessed type of clipboard contents.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum ContentType {
+    /// Plain text.
+    Text,
+
+    /// HTML markup.
+    Html,
+
+    /// Rich Text Format.
+    Rtf,
+
+    /// An image, holds the detected format.
+    Image(ImageFormat),
+
+    /// A list of URIs, one per line, such as copied files.
+    UriList,
+
+    /// Binary contents of unknown type.
+    Unknown,
+}
+
+impl ContentType {
+    /// The MIME type for this content type.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ContentType::Text => "text/plain",
+            ContentType::Html => "text/html",
+            ContentType::Rtf => "text/rtf",
+            ContentType::Image(format) => format.mime_type(),
+            ContentType::UriList => "text/uri-list",
+            ContentType::Unknown => "application/octet-stream",
+        }
+    }
+
+    /// Whether this content type is textual, and can be represented as string.
+    pub fn is_text(self) -> bool {
+        matches!(
+            self,
+            ContentType::Text | ContentType::Html | ContentType::Rtf | ContentType::UriList
+        )
+    }
+}
+
+/// Image format detected in clipboard contents.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum ImageFormat {
+    /// PNG image.
+    Png,
+
+    /// JPEG image.
+    Jpeg,
+
+    /// GIF image.
+    Gif,
+
+    /// BMP image.
+    Bmp,
+
+    /// WebP image.
+    WebP,
+
+    /// TIFF image.
+    Tiff,
+}
+
+impl ImageFormat {
+    /// The MIME type for this image format.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::Bmp => "image/bmp",
+            ImageFormat::WebP => "image/webp",
+            ImageFormat::Tiff => "image/tiff",
+        }
+    }
+
+    /// Detect image format from magic bytes.
+    fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if bytes.starts_with(b"\xFF\xD8\xFF") {
+            Some(ImageFormat::Jpeg)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else if bytes.starts_with(b"BM") && bytes.len() >= 14 {
+            Some(ImageFormat::Bmp)
+        } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+            Some(ImageFormat::WebP)
+        } else if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+            Some(ImageFormat::Tiff)
+        } else {
+            None
+        }
+    }
+}
+
+/// A clipboard item, offered in one or more representations.
+///
+/// Applications commonly offer the same contents in multiple representations, such as HTML
+/// along with a plain text alternative, so receiving applications can pick the one they support
+/// best. Representations are identified by MIME type, and ordered by preference.
+///
+/// Used with platforms supporting multiple items and representations, see
+/// `MacOsClipboardProviderExt::set_items` and `WindowsClipboardProviderExt::set_items`.
+///
+/// ```rust
+/// use copypasta_ext::content::ClipboardItem;
+///
+/// let item = ClipboardItem::new()
+///     .with_representation("text/html", b"<b>some string</b>".to_vec())
+///     .with_text("some string");
+/// assert_eq!(item.text(), Some("some string"));
+/// assert_eq!(item.mime_types().collect::<Vec<_>>(), vec!["text/html", "text/plain"]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClipboardItem {
+    representations: Vec<(String, Vec<u8>)>,
+}
+
+impl ClipboardItem {
+    /// Construct an item without representations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a representation with the given MIME type.
+    ///
+    /// Replaces an existing representation with the same MIME type, keeping its position.
+    pub fn with_representation(mut self, mime_type: &str, data: Vec<u8>) -> Self {
+        match self
+            .representations
+            .iter_mut()
+            .find(|(existing, _)| existing == mime_type)
+        {
+            Some((_, existing)) => *existing = data,
+            None => self.representations.push((mime_type.into(), data)),
+        }
+        self
+    }
+
+    /// Add a plain text representation.
+    pub fn with_text(self, text: &str) -> Self {
+        self.with_representation(ContentType::Text.mime_type(), text.as_bytes().to_vec())
+    }
+
+    /// Get the representation with the given MIME type.
+    pub fn get(&self, mime_type: &str) -> Option<&[u8]> {
+        self.representations
+            .iter()
+            .find(|(existing, _)| existing == mime_type)
+            .map(|(_, data)| data.as_slice())
+    }
+
+    /// Get the plain text representation, if any and if valid UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        self.get(ContentType::Text.mime_type())
+            .and_then(|data| str::from_utf8(data).ok())
+    }
+
+    /// Iterate over the MIME types of all representations, in order of preference.
+    pub fn mime_types(&self) -> impl Iterator<Item = &str> {
+        self.representations
+            .iter()
+            .map(|(mime_type, _)| mime_type.as_str())
+    }
+
+    /// All representations as MIME type and data, in order of preference.
+    pub fn representations(&self) -> &[(String, Vec<u8>)] {
+        &self.representations
+    }
+
+    /// Whether this item has no representations.
+    pub fn is_empty(&self) -> bool {
+        self.representations.is_empty()
+    }
+}
+
+/// Clipboard contents of any supported kind.
+///
+/// Lets applications handle whatever is on the clipboard generically. Platforms that offer
+/// multiple representations are mapped to the richest supported kind, text-only platforms are
+/// detected through [`sniff`].
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use copypasta_ext::content::ClipboardContent;
+///
+/// let content = ClipboardContent::from_text("file:///tmp/a.txt\r\n".into());
+/// assert_eq!(content, ClipboardContent::Files(vec![PathBuf::from("/tmp/a.txt")]));
+/// assert_eq!(content.mime_type(), "text/uri-list");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClipboardContent {
+    /// Plain text.
+    Text(String),
+
+    /// HTML markup, with an optional plain text alternative.
+    Html {
+        /// The HTML markup.
+        html: String,
+
+        /// Plain text alternative, for applications not supporting HTML.
+        alt_text: Option<String>,
+    },
+
+    /// An encoded image, such as PNG.
+    Image {
+        /// The image MIME type, such as `image/png`.
+        mime_type: String,
+
+        /// The encoded image data.
+        data: Vec<u8>,
+    },
+
+    /// A list of file paths, such as copied in a file manager.
+    Files(Vec<PathBuf>),
+
+    /// Contents of any other type.
+    Bytes {
+        /// The MIME type.
+        mime_type: String,
+
+        /// The raw data.
+        data: Vec<u8>,
+    },
+}
+
+impl ClipboardContent {
+    /// Detect the kind of the given textual clipboard contents.
+    ///
+    /// Lists of file URIs become [`Files`](ClipboardContent::Files), HTML markup becomes
+    /// [`Html`](ClipboardContent::Html) and anything else [`Text`](ClipboardContent::Text).
+    pub fn from_text(text: String) -> Self {
+        match sniff(text.as_bytes()) {
+            ContentType::UriList => match uri::parse_file_paths(&text) {
+                Some(paths) => ClipboardContent::Files(paths),
+                None => ClipboardContent::Text(text),
+            },
+            ContentType::Html => ClipboardContent::Html {
+                html: text,
+                alt_text: None,
+            },
+            _ => ClipboardContent::Text(text),
+        }
+    }
+
+    /// Get the richest supported kind from the given clipboard items.
+    ///
+    /// File paths are collected from all items, as some platforms put each file in its own item.
+    /// Otherwise the first item is used. Returns `None` if there are no representations.
+    pub fn from_items(items: &[ClipboardItem]) -> Option<Self> {
+        let paths: Option<Vec<PathBuf>> = items
+            .iter()
+            .map(|item| {
+                item.get(ContentType::UriList.mime_type())
+                    .and_then(|data| str::from_utf8(data).ok())
+                    .and_then(uri::parse_file_paths)
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|paths| paths.into_iter().flatten().collect());
+        match paths {
+            Some(paths) if !paths.is_empty() => Some(ClipboardContent::Files(paths)),
+            _ => items.first().and_then(Self::from_item),
+        }
+    }
+
+    /// Get the richest supported kind from the given clipboard item.
+    ///
+    /// Prefers files, then HTML, then images, then plain text. Falls back to the first
+    /// representation as [`Bytes`](ClipboardContent::Bytes).
+    ///
+    /// ```rust
+    /// use copypasta_ext::content::{ClipboardContent, ClipboardItem};
+    ///
+    /// let item = ClipboardItem::new()
+    ///     .with_text("some string")
+    ///     .with_representation("text/html", b"<b>some string</b>".to_vec());
+    /// assert_eq!(
+    ///     ClipboardContent::from_item(&item),
+    ///     Some(ClipboardContent::Html {
+    ///         html: "<b>some string</b>".into(),
+    ///         alt_text: Some("some string".into()),
+    ///     }),
+    /// );
+    /// ```
+    pub fn from_item(item: &ClipboardItem) -> Option<Self> {
+        let text = |mime_type| {
+            item.get(mime_type)
+                .and_then(|data| str::from_utf8(data).ok())
+        };
+
+        if let Some(paths) = text(ContentType::UriList.mime_type()).and_then(uri::parse_file_paths)
+        {
+            return Some(ClipboardContent::Files(paths));
+        }
+        if let Some(html) = text(ContentType::Html.mime_type()) {
+            return Some(ClipboardContent::Html {
+                html: html.into(),
+                alt_text: item.text().map(Into::into),
+            });
+        }
+        if let Some((mime_type, data)) = item
+            .representations()
+            .iter()
+            .find(|(mime_type, _)| mime_type.starts_with("image/"))
+        {
+            return Some(ClipboardContent::Image {
+                mime_type: mime_type.clone(),
+                data: data.clone(),
+            });
+        }
+        if let Some(text) = item.text() {
+            return Some(ClipboardContent::Text(text.into()));
+        }
+        item.representations()
+            .first()
+            .map(|(mime_type, data)| ClipboardContent::Bytes {
+                mime_type: mime_type.clone(),
+                data: data.clone(),
+            })
+    }
+
+    /// Convert into a clipboard item, with a plain text alternative where possible.
+    ///
+    /// Files are put in a single item as `text/uri-list`.
+    pub fn to_item(&self) -> ClipboardItem {
+        let item = ClipboardItem::new();
+        match self {
+            ClipboardContent::Text(text) => item.with_text(text),
+            ClipboardContent::Html { html, alt_text } => {
+                let item = item.with_representation(self.mime_type(), html.as_bytes().to_vec());
+                match alt_text {
+                    Some(alt_text) => item.with_text(alt_text),
+                    None => item,
+                }
+            }
+            ClipboardContent::Files(_) => {
+                let uris = self.to_text().unwrap_or_default();
+                item.with_representation(self.mime_type(), uris.into_bytes())
+            }
+            ClipboardContent::Image { mime_type, data }
+            | ClipboardContent::Bytes { mime_type, data } => {
+                item.with_representation(mime_type, data.clone())
+            }
+        }
+    }
+
+    /// Convert into clipboard items, putting each file in its own item.
+    ///
+    /// Platforms supporting multiple items, such as macOS, expect files this way.
+    pub fn to_items(&self) -> Vec<ClipboardItem> {
+        match self {
+            ClipboardContent::Files(paths) => paths
+                .iter()
+                .map(|path| {
+                    let uri = uri::file_uri(path).into_bytes();
+                    ClipboardItem::new().with_representation(self.mime_type(), uri)
+                })
+                .collect(),
+            _ => vec![self.to_item()],
+        }
+    }
+
+    /// Get a textual representation, for clipboards that only hold text.
+    ///
+    /// HTML is represented by its plain text alternative if any, files as `text/uri-list`.
+    /// Returns `None` for images, and for other contents that aren't valid UTF-8 text.
+    ///
+    /// ```rust
+    /// use copypasta_ext::content::ClipboardContent;
+    ///
+    /// let content = ClipboardContent::Html {
+    ///     html: "<b>some string</b>".into(),
+    ///     alt_text: Some("some string".into()),
+    /// };
+    /// assert_eq!(content.to_text().as_deref(), Some("some string"));
+    /// ```
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            ClipboardContent::Text(text) => Some(text.clone()),
+            ClipboardContent::Html { html, alt_text } => {
+                Some(alt_text.as_ref().unwrap_or(html).clone())
+            }
+            ClipboardContent::Files(paths) => Some(uri::format_file_paths(paths)),
+            ClipboardContent::Image { .. } => None,
+            ClipboardContent::Bytes { mime_type, data } if mime_type.starts_with("text/") => {
+                str::from_utf8(data).ok().map(Into::into)
+            }
+            ClipboardContent::Bytes { .. } => None,
+        }
+    }
+
+    /// The MIME type of the primary representation.
+    pub fn mime_type(&self) -> &str {
+        match self {
+            ClipboardContent::Text(_) => ContentType::Text.mime_type(),
+            ClipboardContent::Html { .. } => ContentType::Html.mime_type(),
+            ClipboardContent::Files(_) => ContentType::UriList.mime_type(),
+            ClipboardContent::Image { mime_type, .. }
+            | ClipboardContent::Bytes { mime_type, .. } => mime_type,
+        }
+    }
+}
+
+impl From<String> for ClipboardContent {
+    fn from(text: String) -> Self {
+        ClipboardContent::Text(text)
+    }
+}
+
+/// Guess the type of the given clipboard contents.
+///
+/// Images are detected by their magic bytes. Other contents must be valid UTF-8 to be detected
+/// as textual type, and are otherwise reported as [`ContentType::Unknown`]. Textual contents are
+/// checked for RTF, HTML and URI lists, and fall back to plain text.
+///
+/// This is a best effort, and may be unreliable.
+pub fn sniff(bytes: &[u8]) -> ContentType {
+    if let Some(format) = ImageFormat::detect(bytes) {
+        return ContentType::Image(format);
+    }
+
+    let text = match str::from_utf8(bytes) {
+        Ok(text) => text.trim_start_matches('\u{FEFF}').trim(),
+        Err(_) => return ContentType::Unknown,
+    };
+
+    if text.starts_with("{\\rtf") {
+        ContentType::Rtf
+    } else if is_html(text) {
+        ContentType::Html
+    } else if is_uri_list(text) {
+        ContentType::UriList
+    } else {
+        ContentType::Text
+    }
+}
+
+/// Map clipboard format names to MIME types, without duplicates.
+///
+/// Names that are MIME types already are kept as is. Common X11 text target names map to
+/// `text/plain`, other names without a known MIME type are skipped.
+#[allow(dead_code)]
+pub(crate) fn mime_types<'a, I>(names: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut mime_types: Vec<String> = Vec::new();
+    for name in names {
+        let mime_type = match name.trim() {
+            "UTF8_STRING" | "STRING" | "TEXT" | "COMPOUND_TEXT" => ContentType::Text.mime_type(),
+            name if name.contains('/') => name,
+            _ => continue,
+        };
+        if !mime_types.iter().any(|m| m == mime_type) {
+            mime_types.push(mime_type.into());
+        }
+    }
+    mime_types
+}
+
+/// X11 text targets to get contents as, in order of preference.
+#[allow(dead_code)]
+pub(crate) const X11_TEXT_TARGETS: [&str; 3] =
+    ["text/plain;charset=utf-8", "UTF8_STRING", "STRING"];
+
+/// Decode text contents got as the given X11 target.
+///
+/// `STRING` is Latin-1 encoded, and is the only text target of old Motif and Java applications.
+/// Other text targets are UTF-8 encoded.
+#[allow(dead_code)]
+pub(crate) fn decode_x11_text(target: &str, bytes: Vec<u8>) -> Result<String, FromUtf8Error> {
+    match target {
+        "STRING" => Ok(bytes.into_iter().map(char::from).collect()),
+        _ => String::from_utf8(bytes),
+    }
+}
+
+/// Check whether the given trimmed text looks like HTML markup.
+fn is_html(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        return true;
+    }
+
+    // Windows CF_HTML clipboard format
+    if lower.starts_with("version:") && lower.contains("starthtml:") {
+        return true;
+    }
+
+    // Markup enclosed in tags, with at least one closing tag
+    text.starts_with('<') && text.ends_with('>') && (text.contains("</") || text.contains("/>"))
+}
+
+/// Check whether the given trimmed text looks like a `text/uri-list`.
+///
+/// Each line must be a URI with a scheme and authority, or a comment starting with `#`.
+fn is_uri_list(text: &str) -> bool {
+    let mut uris = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .peekable();
+    uris.peek().is_some() && uris.all(is_uri)
+}
+
+/// Check whether the given line looks like a URI with scheme and authority, such as
+/// `file:///tmp/a.txt` or `https://example.com`.
+fn is_uri(line: &str) -> bool {
+    let (scheme, rest) = match line.find("://") {
+        Some(i) => (&line[..i], &line[i + 3..]),
+        None => return false,
+    };
+    let mut chars = scheme.chars();
+    let valid_scheme = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+    valid_scheme && !rest.is_empty() && !line.contains(char::is_whitespace)
+}
+
+/// Represents clipboard content related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The clipboard does not support contents of this MIME type.
+    Unsupported(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unsupported(mime_type) => {
+                write!(f, "Clipboard does not support '{}' contents", mime_type)
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,160 @@
+//! Environment diagnostics for bug reports, see [`diagnostics`](crate::diagnostics).
+
+use std::env;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Environment variables relevant to clipboard selection.
+const ENV_VARS: [&str; 13] = [
+    "XDG_SESSION_TYPE",
+    "XDG_CURRENT_DESKTOP",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "WSL_DISTRO_NAME",
+    "FLATPAK_ID",
+    "SNAP_NAME",
+    "TERM",
+    "TERM_PROGRAM",
+    "INSIDE_EMACS",
+    "TMUX",
+    "STY",
+    "SSH_TTY",
+];
+
+/// Clipboard binaries, along with the argument to print their version.
+const BINARIES: [(&str, Option<&str>); 9] = [
+    ("xclip", Some("-version")),
+    ("xsel", Some("--version")),
+    ("wl-copy", Some("--version")),
+    ("wl-paste", Some("--version")),
+    ("copypasta-ext-x11-helper", None),
+    ("clip.exe", None),
+    ("powershell.exe", None),
+    ("pbcopy", None),
+    ("pbpaste", None),
+];
+
+/// Compiler features of this crate.
+const FEATURES: [(&str, bool); 21] = [
+    ("clipboard", cfg!(feature = "clipboard")),
+    ("x11", cfg!(feature = "x11")),
+    ("wayland", cfg!(feature = "wayland")),
+    ("osc52", cfg!(feature = "osc52")),
+    ("crossterm", cfg!(feature = "crossterm")),
+    ("bracketed-paste", cfg!(feature = "bracketed-paste")),
+    ("notify", cfg!(feature = "notify")),
+    ("signal", cfg!(feature = "signal")),
+    ("lock", cfg!(feature = "lock")),
+    ("gtk", cfg!(feature = "gtk")),
+    ("android", cfg!(feature = "android")),
+    ("x11-bin", cfg!(feature = "x11-bin")),
+    ("x11-fork", cfg!(feature = "x11-fork")),
+    ("x11-native", cfg!(feature = "x11-native")),
+    ("x11-helper", cfg!(feature = "x11-helper")),
+    ("wayland-bin", cfg!(feature = "wayland-bin")),
+    ("wayland-native", cfg!(feature = "wayland-native")),
+    ("wsl-bin", cfg!(feature = "wsl-bin")),
+    ("macos-bin", cfg!(feature = "macos-bin")),
+    ("log", cfg!(feature = "log")),
+    ("serde", cfg!(feature = "serde")),
+];
+
+/// Build the diagnostics report.
+pub(crate) fn report() -> String {
+    // Writing to a string never fails
+    let mut report = String::new();
+    let _ = write_report(&mut report);
+    report
+}
+
+/// Write the diagnostics report to the given string.
+fn write_report(report: &mut String) -> std::fmt::Result {
+    writeln!(report, "copypasta-ext {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(report, "target: {} {}", env::consts::OS, env::consts::ARCH)?;
+    let features: Vec<_> = FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(feature, _)| *feature)
+        .collect();
+    writeln!(report, "features: {}", features.join(", "))?;
+
+    writeln!(
+        report,
+        "display server: {:?}",
+        crate::display::DisplayServer::select()
+    )?;
+    writeln!(report, "wslg: {}", crate::display::is_wslg())?;
+    writeln!(report, "wlroots: {}", crate::display::is_wlroots())?;
+    writeln!(report, "sandboxed: {}", crate::display::is_sandboxed())?;
+    writeln!(report, "ssh: {}", crate::display::is_ssh())?;
+    #[cfg(feature = "osc52")]
+    writeln!(report, "osc52 supported: {}", crate::osc52::is_supported())?;
+
+    writeln!(report, "environment:")?;
+    for var in &ENV_VARS {
+        match env::var(var) {
+            Ok(value) => writeln!(report, "  {}={}", var, redact_home(&value))?,
+            Err(_) => writeln!(report, "  {} not set", var)?,
+        }
+    }
+
+    writeln!(report, "binaries:")?;
+    for (name, version_arg) in &BINARIES {
+        match find_binary(name) {
+            Some(path) => {
+                let path = redact_home(&path.to_string_lossy());
+                match version_arg.and_then(|arg| binary_version(name, arg)) {
+                    Some(version) => writeln!(report, "  {}: {} ({})", name, path, version)?,
+                    None => writeln!(report, "  {}: {}", name, path)?,
+                }
+            }
+            None => writeln!(report, "  {}: not found", name)?,
+        }
+    }
+
+    #[cfg(feature = "clipboard")]
+    {
+        let (_, selection) = crate::try_context_with_report();
+        writeln!(report, "context selection:")?;
+        for line in selection.to_string().lines() {
+            writeln!(report, "  {}", line)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Find a binary in `PATH`.
+fn find_binary(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Get the first line a binary prints when invoked with the given version argument.
+fn binary_version(name: &str, arg: &str) -> Option<String> {
+    let output = Command::new(name)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+
+    // Some binaries print their version to stderr
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(Into::into)
+}
+
+/// Replace the home directory of the user with `~`, as it commonly holds the user name.
+fn redact_home(value: &str) -> String {
+    match env::var("HOME") {
+        Ok(home) if home.len() > 1 => value.replace(&home, "~"),
+        _ => value.into(),
+    }
+}
@@ -1,13 +1,37 @@
 //! Display server management.
 //!
 //! Provides functionality to select used display server based on the runtime environment.
+//!
+//! Display server detection is available without the `clipboard` feature, allowing other crates
+//! to reuse it without pulling in any clipboard dependencies.
 
 use std::env;
+#[cfg(feature = "clipboard")]
+use std::fmt;
+use std::fs;
+use std::path::Path;
 
+#[cfg(feature = "clipboard")]
+use crate::config::ClipboardConfig;
+#[cfg(feature = "clipboard")]
+use crate::filter::FilteredClipboardContext;
+#[cfg(feature = "clipboard")]
 use crate::prelude::ClipboardProviderExt;
+#[cfg(feature = "clipboard")]
+use crate::selection::{SelectedClipboardContext, SelectionClipboardProviderExt};
+#[cfg(feature = "clipboard")]
+use crate::Selection;
 
 /// A display server type.
+///
+/// Serializable with the `serde` feature, using lowercase names such as `"wayland"`, to allow
+/// configuring the display server in configuration files.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 #[non_exhaustive]
 pub enum DisplayServer {
     /// The X11 display server.
@@ -25,6 +49,11 @@ pub enum DisplayServer {
     /// For TTYs.
     /// Not an actual display server, but something with a clipboard context to fall back to.
     Tty,
+
+    /// The Windows Subsystem for Linux, without WSLg.
+    /// Not an actual display server, but the Windows clipboard is reachable through Windows
+    /// binaries.
+    Wsl,
 }
 
 impl DisplayServer {
@@ -34,6 +63,9 @@ impl DisplayServer {
     /// select the current display server. Selects any recognized display server regardless of
     /// compiler feature flag configuration. Defaults to `X11` on Unix if display server could not
     /// be determined.
+    ///
+    /// On WSL with WSLg, `Wayland` is selected, as WSLg synchronizes the Wayland and Windows
+    /// clipboards. On WSL without WSLg, `Wsl` is selected to use the Windows clipboard directly.
     #[allow(unreachable_code)]
     pub fn select() -> DisplayServer {
         #[cfg(target_os = "macos")]
@@ -42,7 +74,11 @@ impl DisplayServer {
         return DisplayServer::Windows;
 
         // Runtime check on Unix
-        if is_wayland() {
+        if is_wslg() {
+            DisplayServer::Wayland
+        } else if is_wsl() {
+            DisplayServer::Wsl
+        } else if is_wayland() {
             DisplayServer::Wayland
         } else if is_x11() {
             DisplayServer::X11
@@ -59,72 +95,687 @@ impl DisplayServer {
     /// This attempts to build a clipboard context for the selected display server based on what
     /// contexts are available.
     ///
+    /// Inside a Flatpak or Snap sandbox, contexts invoking binaries are skipped, as binaries of
+    /// the host aren't visible there. In-process contexts are used instead. Clipboard access
+    /// through the desktop portal is not supported yet. See [`is_sandboxed`].
+    ///
+    /// On wlroots based Wayland compositors, contents are got through the `wlr-data-control`
+    /// protocol with the `wayland-native` feature, as it works without focus. See
+    /// [`is_wlroots`]. Contents are still set through `wl-copy` with the `wayland-bin` feature if
+    /// available, as data-control contents are served by this process and are lost when it exits.
+    /// Without `wl-copy`, such as in a sandbox, data-control is used for setting as well, with a
+    /// [`ContentLifetime::Process`](crate::ContentLifetime::Process) lifetime.
+    ///
+    /// On Windows in an SSH session, the OSC 52 context is preferred with the `osc52` feature, as
+    /// the native clipboard is of the remote machine. Terminals such as Windows Terminal support
+    /// it. See [`is_ssh`].
+    ///
+    /// On macOS, `pbcopy`/`pbpaste` are used as fallback with the `macos-bin` feature, when the
+    /// native pasteboard is not accessible, such as from a daemon.
+    ///
     /// If no compatible context is available or if no compatible context could be initialized,
     /// `None` is returned.
+    #[cfg(feature = "clipboard")]
     pub fn try_context(self) -> Option<Box<dyn ClipboardProviderExt>> {
-        match self {
-            DisplayServer::X11 => {
-                #[cfg(feature = "x11-fork")]
-                {
-                    let context = crate::x11_fork::ClipboardContext::new();
-                    if let Ok(context) = context {
-                        return Some(Box::new(context));
-                    }
+        self.try_context_with_report().0
+    }
+
+    /// Build clipboard context for display server, along with a report of the selection.
+    ///
+    /// Like [`try_context`](DisplayServer::try_context), but also returns a [`SelectionReport`]
+    /// describing what backends were considered, which one was chosen and why others were
+    /// skipped.
+    #[cfg(feature = "clipboard")]
+    pub fn try_context_with_report(
+        self,
+    ) -> (Option<Box<dyn ClipboardProviderExt>>, SelectionReport) {
+        self.try_context_with_config(&ClipboardConfig::default())
+    }
+
+    /// Build clipboard context for display server, configured through the given configuration.
+    ///
+    /// Like [`try_context_with_report`](DisplayServer::try_context_with_report), but only tries
+    /// the configured backends in the configured order, and applies the configuration to the
+    /// chosen context. Backends that can't access the configured selection are skipped. Contents
+    /// larger than the configured maximum size are refused when setting. See
+    /// [`ClipboardConfig`].
+    #[cfg(feature = "clipboard")]
+    pub fn try_context_with_config(
+        self,
+        config: &ClipboardConfig,
+    ) -> (Option<Box<dyn ClipboardProviderExt>>, SelectionReport) {
+        let mut report = SelectionReport::new(self);
+        let context = self.select_context(config, &mut report);
+        (context, report)
+    }
+
+    /// Select clipboard context for display server, recording attempts in the given report.
+    #[cfg(feature = "clipboard")]
+    fn select_context(
+        self,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        let backends: &[Backend] = match self {
+            DisplayServer::X11 => &[
+                ("x11-fork", backend::x11_fork),
+                ("x11-bin", backend::x11_bin),
+                ("x11", backend::x11),
+            ],
+            DisplayServer::Wayland => &[
+                ("wayland-native", backend::wayland_native),
+                ("wayland-bin", backend::wayland_bin),
+                // TODO: this correct?
+                ("copypasta", backend::copypasta),
+            ],
+            DisplayServer::MacOs => &[
+                ("copypasta", backend::copypasta_macos),
+                ("macos-bin", backend::macos_bin),
+            ],
+            DisplayServer::Windows => &[
+                ("osc52", backend::osc52_ssh),
+                ("copypasta", backend::copypasta),
+            ],
+            DisplayServer::Tty => &[("osc52", backend::osc52)],
+            DisplayServer::Wsl => &[("wsl-bin", backend::wsl_bin)],
+        };
+
+        // Try configured backends in configured order
+        let mut backends = backends.to_vec();
+        for (name, _) in &backends {
+            if !config.allows_backend(name) {
+                report.skip(name, "not configured");
+            }
+        }
+        backends.retain(|(name, _)| config.allows_backend(name));
+        backends.sort_by_key(|(name, _)| config.backends.iter().position(|b| b == name));
+
+        let context = backends
+            .iter()
+            .find_map(|(name, backend)| backend(name, config, report))?;
+        Some(match config.max_size {
+            Some(max_size) => Box::new(FilteredClipboardContext::new(context).with_deny(
+                move |contents| contents.len() > max_size,
+                "contents exceed the configured maximum size",
+            )),
+            None => context,
+        })
+    }
+
+    /// Build combined clipboard context for display server.
+    ///
+    /// Like [`try_context`](DisplayServer::try_context), but pairs the fastest available context
+    /// for getting with the most persistent available context for setting clipboard contents,
+    /// using a [`CombinedClipboardContext`](crate::CombinedClipboardContext) where useful:
+    ///
+    /// - X11: native get with forked set (`x11-fork`), or native get with `xclip`/`xsel` set
+    ///   (`x11-bin`)
+    /// - others: same as [`try_context`](DisplayServer::try_context)
+    ///
+    /// If no compatible context is available or if no compatible context could be initialized,
+    /// `None` is returned.
+    #[cfg(feature = "clipboard")]
+    pub fn try_combined_context(self) -> Option<Box<dyn ClipboardProviderExt>> {
+        if self == DisplayServer::X11 {
+            // The fork context already gets natively
+            #[cfg(feature = "x11-fork")]
+            {
+                let context = crate::x11_fork::ClipboardContext::new();
+                if let Ok(context) = context {
+                    return Some(Box::new(context));
                 }
-                #[cfg(feature = "x11-bin")]
-                {
-                    let context = crate::x11_bin::ClipboardContext::new();
-                    if let Ok(context) = context {
-                        return Some(Box::new(context));
-                    }
+            }
+            #[cfg(all(
+                feature = "x11",
+                feature = "x11-bin",
+                unix,
+                not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+            ))]
+            if !is_sandboxed() {
+                let context = crate::x11_bin::X11BinClipboardContext::new_with_x11();
+                if let Ok(context) = context {
+                    return Some(Box::new(context));
                 }
-                #[cfg(all(
-                    unix,
-                    not(any(
-                        target_os = "macos",
-                        target_os = "android",
-                        target_os = "ios",
-                        target_os = "emscripten"
-                    ))
-                ))]
-                {
-                    let context = copypasta::x11_clipboard::X11ClipboardContext::new();
-                    if let Ok(context) = context {
-                        return Some(Box::new(context));
-                    }
+            }
+        }
+
+        self.try_context()
+    }
+}
+
+/// A clipboard backend, its name along with a function to build its context.
+///
+/// The function records its attempt in the given report, and returns the context if chosen.
+#[cfg(feature = "clipboard")]
+type Backend = (
+    &'static str,
+    fn(
+        &'static str,
+        &ClipboardConfig,
+        &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>>,
+);
+
+/// Functions building the context of each backend, see [`Backend`].
+#[cfg(feature = "clipboard")]
+#[allow(unused_imports, unused_variables)]
+mod backend {
+    use super::SelectionReport;
+    use crate::config::ClipboardConfig;
+    use crate::prelude::*;
+    use crate::Selection;
+
+    pub fn x11_fork(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        #[cfg(all(
+            feature = "x11-fork",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        ))]
+        {
+            let context = crate::x11_fork::X11ForkClipboardContext::new()
+                .map(|context| context.with_config(config));
+            report.attempt_selection(name, context, config.selection)
+        }
+        #[cfg(not(all(
+            feature = "x11-fork",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        )))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+
+    pub fn x11_bin(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        #[cfg(all(
+            feature = "x11-bin",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        ))]
+        {
+            if super::is_sandboxed() {
+                report.skip(name, "binaries not visible in sandbox");
+                return None;
+            }
+            let context = crate::x11_bin::X11BinClipboardContext::new()
+                .map(|context| context.with_config(config));
+            report.attempt_selection(name, context, config.selection)
+        }
+        #[cfg(not(all(
+            feature = "x11-bin",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        )))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+
+    pub fn x11(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        #[cfg(all(
+            feature = "x11",
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "ios",
+                target_os = "emscripten"
+            ))
+        ))]
+        {
+            if config.selection != Selection::Clipboard {
+                report.skip(name, "selection not supported");
+                return None;
+            }
+
+            #[cfg(feature = "x11-native")]
+            if let Some(timeout) = config.get_timeout {
+                let context = crate::x11_native::X11TimedClipboardContext::new()
+                    .map(|context| context.with_timeout(timeout));
+                return report.attempt(name, context);
+            }
+
+            report.attempt(name, copypasta::x11_clipboard::X11ClipboardContext::new())
+        }
+        #[cfg(not(all(
+            feature = "x11",
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "ios",
+                target_os = "emscripten"
+            ))
+        )))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+
+    pub fn wayland_native(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        // Data-control works without focus, prefer it where supported
+        #[cfg(all(
+            feature = "wayland-native",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        ))]
+        {
+            if config.selection != Selection::Clipboard {
+                report.skip(name, "selection not supported");
+                return None;
+            }
+            if !super::is_wlroots() && config.backends.is_empty() {
+                report.skip(name, "not a wlroots based compositor");
+                return None;
+            }
+            let context = crate::wayland_native::DataControlClipboardContext::new();
+
+            // Data-control contents are lost when this process exits, keep setting through
+            // wl-copy where available so they outlive it
+            #[cfg(feature = "wayland-bin")]
+            if !super::is_sandboxed() && config.allows_backend("wayland-bin") {
+                let setter = crate::wayland_bin::WaylandBinClipboardContext::new()
+                    .map(|setter| setter.with_config(config));
+                if let (Ok(_), Ok(setter)) = (&context, setter) {
+                    return report.attempt(
+                        name,
+                        context.map(|getter| crate::CombinedClipboardContext(getter, setter)),
+                    );
                 }
+            }
+
+            report.attempt(name, context)
+        }
+        #[cfg(not(all(
+            feature = "wayland-native",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        )))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+
+    pub fn wayland_bin(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        #[cfg(all(
+            feature = "wayland-bin",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        ))]
+        {
+            if super::is_sandboxed() {
+                report.skip(name, "binaries not visible in sandbox");
+                return None;
+            }
+            let context = crate::wayland_bin::WaylandBinClipboardContext::new()
+                .map(|context| context.with_config(config));
+            report.attempt_selection(name, context, config.selection)
+        }
+        #[cfg(not(all(
+            feature = "wayland-bin",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        )))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+
+    pub fn copypasta(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        // Copypasta only has a context on X11 platforms with its X11 support
+        #[cfg(any(
+            feature = "x11",
+            not(all(
+                unix,
+                not(any(
+                    target_os = "macos",
+                    target_os = "android",
+                    target_os = "ios",
+                    target_os = "emscripten"
+                ))
+            ))
+        ))]
+        {
+            if config.selection != Selection::Clipboard {
+                report.skip(name, "selection not supported");
+                return None;
+            }
+            report.attempt(name, copypasta::ClipboardContext::new())
+        }
+        #[cfg(not(any(
+            feature = "x11",
+            not(all(
+                unix,
+                not(any(
+                    target_os = "macos",
+                    target_os = "android",
+                    target_os = "ios",
+                    target_os = "emscripten"
+                ))
+            ))
+        )))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+
+    pub fn copypasta_macos(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        // Fall back to the binaries when the pasteboard turns out to be inaccessible at runtime
+        #[cfg(all(feature = "macos-bin", target_os = "macos"))]
+        {
+            if config.selection != Selection::Clipboard {
+                report.skip(name, "selection not supported");
+                return None;
+            }
+            if config.allows_backend("macos-bin") {
+                let context = copypasta::ClipboardContext::new().and_then(|context| {
+                    let fallback = crate::macos_bin::MacOsBinClipboardContext::new()?;
+                    Ok(crate::FallbackClipboardContext(
+                        context,
+                        fallback.with_config(config),
+                    ))
+                });
+                return report.attempt(name, context);
+            }
+        }
+        copypasta(name, config, report)
+    }
+
+    pub fn macos_bin(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        #[cfg(all(feature = "macos-bin", target_os = "macos"))]
+        {
+            if config.selection != Selection::Clipboard {
+                report.skip(name, "selection not supported");
+                return None;
+            }
+            let context = crate::macos_bin::MacOsBinClipboardContext::new()
+                .map(|context| context.with_config(config));
+            report.attempt(name, context)
+        }
+        #[cfg(not(all(feature = "macos-bin", target_os = "macos")))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+
+    pub fn osc52(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        #[cfg(feature = "osc52")]
+        {
+            if !crate::osc52::is_supported() && config.backends.is_empty() {
+                report.skip(name, "terminal doesn't support OSC 52");
+                return None;
+            }
+            let context = crate::osc52::Osc52ClipboardContext::new();
+            report.attempt_selection(name, context, config.selection)
+        }
+        #[cfg(not(feature = "osc52"))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+
+    pub fn osc52_ssh(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        // The native clipboard is of the remote machine, only useful to a local user
+        if !super::is_ssh() && config.backends.is_empty() {
+            report.skip(name, "not an SSH session");
+            return None;
+        }
+        osc52(name, config, report)
+    }
+
+    pub fn wsl_bin(
+        name: &'static str,
+        config: &ClipboardConfig,
+        report: &mut SelectionReport,
+    ) -> Option<Box<dyn ClipboardProviderExt>> {
+        #[cfg(all(feature = "wsl-bin", target_os = "linux"))]
+        {
+            if config.selection != Selection::Clipboard {
+                report.skip(name, "selection not supported");
+                return None;
+            }
+            let context = crate::wsl_bin::WslBinClipboardContext::new()
+                .map(|context| context.with_config(config));
+            report.attempt(name, context)
+        }
+        #[cfg(not(all(feature = "wsl-bin", target_os = "linux")))]
+        {
+            report.skip(name, "feature not enabled");
+            None
+        }
+    }
+}
+
+/// Report of a clipboard context selection.
+///
+/// Describes the detected display server, the backends that were considered, the backend that
+/// was chosen and why others were skipped. Useful to include in bug reports. Implements
+/// [`Display`](fmt::Display) for a human readable summary.
+///
+/// See [`try_context_with_report`](crate::try_context_with_report).
+///
+/// ```rust,no_run
+/// let (ctx, report) = copypasta_ext::try_context_with_report();
+/// println!("{}", report);
+/// println!("Chosen backend: {:?}", report.chosen());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg(feature = "clipboard")]
+pub struct SelectionReport {
+    display_server: DisplayServer,
+    backends: Vec<BackendReport>,
+}
+
+#[cfg(feature = "clipboard")]
+impl SelectionReport {
+    fn new(display_server: DisplayServer) -> Self {
+        Self {
+            display_server,
+            backends: Vec::new(),
+        }
+    }
+
+    /// The display server a context was selected for.
+    pub fn display_server(&self) -> DisplayServer {
+        self.display_server
+    }
+
+    /// All considered backends, in order.
+    pub fn backends(&self) -> &[BackendReport] {
+        &self.backends
+    }
+
+    /// The chosen backend, if any.
+    pub fn chosen(&self) -> Option<&'static str> {
+        self.backends
+            .iter()
+            .find(|b| b.status == BackendStatus::Chosen)
+            .map(|b| b.backend)
+    }
+
+    /// Backends that were skipped or failed, in order.
+    pub fn skipped(&self) -> impl Iterator<Item = &BackendReport> {
+        self.backends
+            .iter()
+            .filter(|b| b.status != BackendStatus::Chosen)
+    }
+
+    /// Record a backend that was skipped for the given reason.
+    #[allow(dead_code)]
+    fn skip(&mut self, backend: &'static str, reason: &'static str) {
+        debug!("clipboard backend {}: skipped, {}", backend, reason);
+        self.backends.push(BackendReport {
+            backend,
+            status: BackendStatus::Skipped(reason.into()),
+        });
+    }
+
+    /// Record an attempt to initialize a backend for the given selection, returning the boxed
+    /// context on success.
+    ///
+    /// Contexts for other selections than the regular clipboard are wrapped in a
+    /// [`SelectedClipboardContext`](crate::selection::SelectedClipboardContext).
+    #[allow(dead_code)]
+    fn attempt_selection<C, E>(
+        &mut self,
+        backend: &'static str,
+        context: Result<C, E>,
+        selection: Selection,
+    ) -> Option<Box<dyn ClipboardProviderExt>>
+    where
+        C: ClipboardProviderExt + SelectionClipboardProviderExt + 'static,
+        E: fmt::Display,
+    {
+        match context {
+            Ok(context) if selection == Selection::Clipboard => {
+                self.attempt::<_, E>(backend, Ok(context))
+            }
+            Ok(context) if context.supports_selection(selection) => self.attempt::<_, E>(
+                backend,
+                Ok(SelectedClipboardContext::new(context, selection)),
+            ),
+            Ok(_) => {
+                self.skip(backend, "selection not supported");
                 None
             }
-            DisplayServer::Wayland => {
-                #[cfg(feature = "wayland-bin")]
-                {
-                    let context = crate::wayland_bin::ClipboardContext::new();
-                    if let Ok(context) = context {
-                        return Some(Box::new(context));
-                    }
-                }
-                // TODO: this correct?
-                copypasta::ClipboardContext::new()
-                    .ok()
-                    .map(|c| -> Box<dyn ClipboardProviderExt> { Box::new(c) })
-            }
-            DisplayServer::MacOs | DisplayServer::Windows => copypasta::ClipboardContext::new()
-                .ok()
-                .map(|c| -> Box<dyn ClipboardProviderExt> { Box::new(c) }),
-            DisplayServer::Tty => {
-                #[cfg(feature = "osc52")]
-                {
-                    let context = crate::osc52::ClipboardContext::new();
-                    if let Ok(context) = context {
-                        return Some(Box::new(context));
-                    }
-                }
+            Err(err) => self.attempt::<C, E>(backend, Err(err)),
+        }
+    }
+
+    /// Record an attempt to initialize a backend, returning the boxed context on success.
+    fn attempt<C, E>(
+        &mut self,
+        backend: &'static str,
+        context: Result<C, E>,
+    ) -> Option<Box<dyn ClipboardProviderExt>>
+    where
+        C: ClipboardProviderExt + 'static,
+        E: fmt::Display,
+    {
+        match context {
+            Ok(context) => {
+                debug!("clipboard backend {}: chosen", backend);
+                self.backends.push(BackendReport {
+                    backend,
+                    status: BackendStatus::Chosen,
+                });
+                Some(Box::new(context))
+            }
+            Err(err) => {
+                debug!("clipboard backend {}: failed, {}", backend, err);
+                self.backends.push(BackendReport {
+                    backend,
+                    status: BackendStatus::Failed(err.to_string()),
+                });
                 None
             }
         }
     }
 }
 
+#[cfg(feature = "clipboard")]
+impl fmt::Display for SelectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "display server: {:?}", self.display_server)?;
+        for backend in &self.backends {
+            write!(f, "\n{}: {}", backend.backend, backend.status)?;
+        }
+        Ok(())
+    }
+}
+
+/// Report of a single backend considered in a [`SelectionReport`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg(feature = "clipboard")]
+pub struct BackendReport {
+    /// Backend name, such as `x11-fork`.
+    pub backend: &'static str,
+
+    /// What happened with the backend.
+    pub status: BackendStatus,
+}
+
+/// Status of a backend considered in a [`SelectionReport`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+#[cfg(feature = "clipboard")]
+pub enum BackendStatus {
+    /// The backend was chosen.
+    Chosen,
+
+    /// The backend was skipped, holds the reason.
+    Skipped(String),
+
+    /// Initializing the backend failed, holds the error.
+    Failed(String),
+}
+
+#[cfg(feature = "clipboard")]
+impl fmt::Display for BackendStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BackendStatus::Chosen => write!(f, "chosen"),
+            BackendStatus::Skipped(reason) => write!(f, "skipped, {}", reason),
+            BackendStatus::Failed(err) => write!(f, "failed, {}", err),
+        }
+    }
+}
+
 /// Check whether we're in an X11 environment.
 ///
 /// This is a best effort, may be unreliable.
@@ -163,6 +814,80 @@ pub fn is_wayland() -> bool {
     }
 }
 
+/// Check whether we're in a Wayland environment with XWayland available.
+///
+/// This is a best effort, may be unreliable.
+/// Checks whether we're in a Wayland environment, and whether the `DISPLAY` environment variable
+/// is set for the XWayland server.
+/// Always returns false on Windows/macOS.
+pub fn is_xwayland() -> bool {
+    is_wayland() && has_non_empty_env("DISPLAY")
+}
+
+/// Check whether we're running on a wlroots based Wayland compositor.
+///
+/// This is a best effort, may be unreliable.
+/// Checks whether we're in a Wayland environment, and whether the `XDG_CURRENT_DESKTOP`
+/// environment variable or compositor specific variables such as `SWAYSOCK` identify a
+/// compositor known to support the `wlr-data-control` protocol, such as Sway, Hyprland or river.
+/// Always returns false on Windows/macOS.
+pub fn is_wlroots() -> bool {
+    const DESKTOPS: [&str; 6] = ["sway", "hyprland", "river", "wayfire", "labwc", "dwl"];
+
+    if !is_wayland() {
+        return false;
+    }
+
+    let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    desktop
+        .split(':')
+        .any(|d| DESKTOPS.iter().any(|known| d.eq_ignore_ascii_case(known)))
+        || has_non_empty_env("SWAYSOCK")
+        || has_non_empty_env("HYPRLAND_INSTANCE_SIGNATURE")
+}
+
+/// Check whether we're running inside a Flatpak sandbox.
+///
+/// Checks for the `/.flatpak-info` file, and the `FLATPAK_ID` environment variable.
+/// Always returns false on platforms other than Linux.
+pub fn is_flatpak() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+
+    has_non_empty_env("FLATPAK_ID") || Path::new("/.flatpak-info").exists()
+}
+
+/// Check whether we're running inside a Snap sandbox.
+///
+/// Checks the `SNAP` and `SNAP_NAME` environment variables.
+/// Always returns false on platforms other than Linux.
+pub fn is_snap() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+
+    has_non_empty_env("SNAP") && has_non_empty_env("SNAP_NAME")
+}
+
+/// Check whether we're running inside a Flatpak or Snap sandbox.
+///
+/// Binaries of the host, such as `xclip` and `wl-copy`, are not visible inside the sandbox.
+pub fn is_sandboxed() -> bool {
+    is_flatpak() || is_snap()
+}
+
+/// Check whether we're running in an SSH session.
+///
+/// This is a best effort, may be unreliable.
+/// Checks the `SSH_CONNECTION`, `SSH_CLIENT` and `SSH_TTY` environment variables, which the
+/// OpenSSH server also sets on Windows.
+pub fn is_ssh() -> bool {
+    has_non_empty_env("SSH_CONNECTION")
+        || has_non_empty_env("SSH_CLIENT")
+        || has_non_empty_env("SSH_TTY")
+}
+
 /// Check whether we're in a TTY environment.
 ///
 /// This is a basic check and only returns true if `XDG_SESSION_TYPE` is set to `tty` explicitly.
@@ -170,6 +895,33 @@ pub fn is_tty() -> bool {
     env::var("XDG_SESSION_TYPE").as_deref() == Ok("tty")
 }
 
+/// Check whether we're running in the Windows Subsystem for Linux.
+///
+/// This is a best effort, may be unreliable.
+/// Checks the `WSL_DISTRO_NAME` environment variable, and whether the kernel release mentions
+/// Microsoft.
+/// Always returns false on platforms other than Linux.
+pub fn is_wsl() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+
+    has_non_empty_env("WSL_DISTRO_NAME")
+        || fs::read_to_string("/proc/sys/kernel/osrelease")
+            .map(|release| release.to_lowercase().contains("microsoft"))
+            .unwrap_or(false)
+}
+
+/// Check whether we're running in the Windows Subsystem for Linux with WSLg.
+///
+/// This is a best effort, may be unreliable.
+/// Checks whether we're in WSL, and whether the `WAYLAND_DISPLAY` environment variable is set
+/// for the WSLg Wayland compositor. WSLg synchronizes its clipboard with the Windows clipboard.
+/// Always returns false on platforms other than Linux.
+pub fn is_wslg() -> bool {
+    is_wsl() && has_non_empty_env("WAYLAND_DISPLAY")
+}
+
 /// Check if an environment variable is set and is not empty.
 #[inline]
 fn has_non_empty_env(env: &str) -> bool {
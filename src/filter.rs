@@ -0,0 +1,288 @@
+//! Filter clipboard contents before setting.
+//!
+//! [`FilteredClipboardContext`] wraps any clipboard context, and passes contents through a list of
+//! filters before they reach the clipboard. Each filter may allow, scrub or reject the contents.
+//! Rejected contents are never set, and [`Error::Rejected`] is returned instead.
+//!
+//! # Examples
+//!
+//! Block anything that looks like a credit card number:
+//!
+//! ```rust
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::filter::{self, FilteredClipboardContext};
+//! use copypasta_ext::testing::DryRunClipboardContext;
+//!
+//! let mut ctx = FilteredClipboardContext::new(DryRunClipboardContext::new())
+//!     .with_deny(filter::contains_card_number, "contains credit card number");
+//! assert!(ctx.set_contents("4111 1111 1111 1111".into()).is_err());
+//! assert!(ctx.set_contents("some string".into()).is_ok());
+//! ```
+//!
+//! Scrub contents instead:
+//!
+//! ```rust
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::filter::{FilterAction, FilteredClipboardContext};
+//! use copypasta_ext::testing::DryRunClipboardContext;
+//!
+//! let mut ctx = FilteredClipboardContext::new(DryRunClipboardContext::new())
+//!     .with_filter(|contents: &str| FilterAction::Replace(contents.replace("hunter2", "*******")));
+//! ctx.set_contents("password: hunter2".into()).unwrap();
+//! assert_eq!(ctx.get_contents().unwrap(), "password: *******");
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::Write;
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ClipboardProviderNew, ContentLifetime, Selection, ThreadAffinity};
+
+/// What to do with contents passed through a filter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FilterAction {
+    /// Allow the contents unchanged.
+    Allow,
+
+    /// Replace the contents, such as with a scrubbed version.
+    Replace(String),
+
+    /// Reject the contents, holds the reason.
+    Reject(String),
+}
+
+/// A filter applied to contents before setting.
+type Filter = Box<dyn Fn(&str) -> FilterAction + Send>;
+
+/// Filter contents before setting them on the wrapped context.
+///
+/// Without any filters, contents are passed through unchanged. Getting contents is not filtered.
+///
+/// See module documentation for more information.
+pub struct FilteredClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    context: C,
+    filters: Vec<Filter>,
+}
+
+impl<C> FilteredClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context.
+    pub fn new(context: C) -> Self {
+        Self {
+            context,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Add a filter, deciding what to do with the contents.
+    ///
+    /// Filters are applied in the order they are added. Replaced contents are passed to the next
+    /// filter. The first rejection stops filtering.
+    pub fn with_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&str) -> FilterAction + Send + 'static,
+    {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Add a filter rejecting contents matching the given predicate, for the given reason.
+    pub fn with_deny<F>(self, predicate: F, reason: &str) -> Self
+    where
+        F: Fn(&str) -> bool + Send + 'static,
+    {
+        let reason = reason.to_string();
+        self.with_filter(move |contents| {
+            if predicate(contents) {
+                FilterAction::Reject(reason.clone())
+            } else {
+                FilterAction::Allow
+            }
+        })
+    }
+
+    /// Pass the given contents through all filters.
+    ///
+    /// Returns the contents to set, or [`Error::Rejected`] if any filter rejects them.
+    pub fn apply(&self, contents: String) -> Result<String, Error> {
+        self.filters
+            .iter()
+            .try_fold(contents, |contents, filter| match filter(&contents) {
+                FilterAction::Allow => Ok(contents),
+                FilterAction::Replace(contents) => Ok(contents),
+                FilterAction::Reject(reason) => Err(Error::Rejected(reason)),
+            })
+    }
+
+    /// Get a reference to the inner clipboard context.
+    pub fn inner(&self) -> &C {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+}
+
+impl<C> ClipboardProvider for FilteredClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.context.get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        let contents = self.apply(contents)?;
+        self.context.set_contents(contents)
+    }
+}
+
+impl<C> ClipboardProviderNew for FilteredClipboardContext<C>
+where
+    C: ClipboardProviderNew,
+{
+    fn new() -> crate::ClipResult<Self> {
+        Ok(FilteredClipboardContext::new(C::new()?))
+    }
+}
+
+/// Setting from a stream is not forwarded to the inner context, as contents must be filtered as
+/// a whole.
+impl<C> ClipboardProviderExt for FilteredClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.context.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.context.change_token()
+    }
+
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        self.context.content_hash()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.context.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.context.available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.context.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.context.clear_contents()
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> crate::ClipResult<()> {
+        self.context.get_contents_to(writer)
+    }
+}
+
+/// Check whether the contents contain something that looks like a payment card number.
+///
+/// Finds sequences of 13 to 19 digits, optionally grouped by single spaces or dashes, that pass
+/// the Luhn checksum. Use with
+/// [`with_deny`](FilteredClipboardContext::with_deny).
+///
+/// ```rust
+/// use copypasta_ext::filter::contains_card_number;
+///
+/// assert!(contains_card_number("card: 4111-1111-1111-1111"));
+/// assert!(!contains_card_number("order 1234 5678 9012 3456"));
+/// ```
+pub fn contains_card_number(contents: &str) -> bool {
+    let mut digits: Vec<u32> = Vec::with_capacity(19);
+    let mut separator = false;
+    for c in contents.chars().chain(Some('\0')) {
+        match c.to_digit(10) {
+            Some(digit) => {
+                digits.push(digit);
+                separator = false;
+            }
+            None if (c == ' ' || c == '-') && !separator && !digits.is_empty() => separator = true,
+            None => {
+                if (13..=19).contains(&digits.len()) && luhn(&digits) {
+                    return true;
+                }
+                digits.clear();
+                separator = false;
+            }
+        }
+    }
+    false
+}
+
+/// Check whether the given digits pass the Luhn checksum.
+fn luhn(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| match (i % 2, digit * 2) {
+            (1, double) if double > 9 => double - 9,
+            (1, double) => double,
+            _ => *digit,
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Represents content filter related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The contents were rejected by a filter, holds the reason.
+    Rejected(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Rejected(reason) => write!(f, "Clipboard contents rejected: {}", reason),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
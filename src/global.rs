@@ -0,0 +1,217 @@
+//! Thread-safe global clipboard handle.
+//!
+//! See [`GlobalClipboard`] for details.
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use once_cell::sync::OnceCell;
+
+use crate::content::ClipboardContent;
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ContentLifetime, Selection, ThreadAffinity};
+
+/// The process wide clipboard handle, initialized on first use.
+static GLOBAL: OnceCell<GlobalClipboard> = OnceCell::new();
+
+/// Thread-safe global clipboard handle.
+///
+/// A process wide clipboard context that may be shared between threads, so you don't have to
+/// manage context lifetimes and locking yourself. Access to the underlying context is serialized
+/// through an internal lock. Cloning is cheap, all clones refer to the same context.
+///
+/// The context is lazily selected using [`try_context`](crate::try_context) on first use, unless
+/// a specific context is set up front using [`GlobalClipboard::init`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use copypasta_ext::GlobalClipboard;
+///
+/// let clipboard = GlobalClipboard::get().unwrap();
+/// clipboard.copy("some string").unwrap();
+///
+/// std::thread::spawn(|| {
+///     let clipboard = GlobalClipboard::get().unwrap();
+///     println!("{:?}", clipboard.paste());
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct GlobalClipboard(Arc<Mutex<Box<dyn ClipboardProviderExt>>>);
+
+impl GlobalClipboard {
+    /// Get the global clipboard handle.
+    ///
+    /// Selects a clipboard context using [`try_context`](crate::try_context) if the global
+    /// clipboard wasn't initialized yet. Errors if no clipboard context is available, in which
+    /// case initialization is attempted again on the next call.
+    pub fn get() -> crate::ClipResult<Self> {
+        GLOBAL
+            .get_or_try_init(|| {
+                crate::try_context()
+                    .map(Self::from_boxed)
+                    .ok_or(Error::NoContext)
+            })
+            .cloned()
+            .map_err(Into::into)
+    }
+
+    /// Initialize the global clipboard with the given context.
+    ///
+    /// Use this to select a specific context rather than letting [`GlobalClipboard::get`] pick
+    /// one. Errors if the global clipboard was already initialized.
+    pub fn init<C>(context: C) -> crate::ClipResult<Self>
+    where
+        C: ClipboardProviderExt + 'static,
+    {
+        let clipboard = Self::from_boxed(Box::new(context));
+        GLOBAL
+            .set(clipboard.clone())
+            .map_err(|_| Error::AlreadyInitialized)?;
+        Ok(clipboard)
+    }
+
+    fn from_boxed(context: Box<dyn ClipboardProviderExt>) -> Self {
+        Self(Arc::new(Mutex::new(context)))
+    }
+
+    /// Set clipboard contents.
+    pub fn copy(&self, contents: &str) -> crate::ClipResult<()> {
+        self.lock().set_contents(contents.into())
+    }
+
+    /// Get clipboard contents.
+    pub fn paste(&self) -> crate::ClipResult<String> {
+        self.lock().get_contents()
+    }
+
+    /// Set clipboard contents, returning the previous contents.
+    ///
+    /// See [`ClipboardProviderExt::swap_contents`].
+    pub fn swap(&self, contents: &str) -> crate::ClipResult<String> {
+        self.lock().swap_contents(contents.into())
+    }
+
+    /// Run the given closure with exclusive access to the underlying clipboard context.
+    pub fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut dyn ClipboardProviderExt) -> R,
+    {
+        f(self.lock().as_mut())
+    }
+
+    /// Lock the underlying context.
+    ///
+    /// A panic while holding the lock doesn't leave the context in an inconsistent state, so
+    /// poisoning is ignored.
+    fn lock(&self) -> MutexGuard<'_, Box<dyn ClipboardProviderExt>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl ClipboardProvider for GlobalClipboard {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.paste()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.lock().set_contents(contents)
+    }
+}
+
+impl ClipboardProviderExt for GlobalClipboard {
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.lock().display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.lock().content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.lock().requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.lock().thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.lock().selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.lock().change_token()
+    }
+
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        self.lock().content_hash()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.lock().is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.lock().available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.lock().persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.lock().clear_contents()
+    }
+
+    fn swap_contents(&mut self, contents: String) -> crate::ClipResult<String> {
+        self.lock().swap_contents(contents)
+    }
+
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> crate::ClipResult<()> {
+        self.lock().set_contents_from(reader)
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> crate::ClipResult<()> {
+        self.lock().get_contents_to(writer)
+    }
+
+    fn get_content(&mut self) -> crate::ClipResult<ClipboardContent> {
+        self.lock().get_content()
+    }
+
+    fn set_content(&mut self, content: ClipboardContent) -> crate::ClipResult<()> {
+        self.lock().set_content(content)
+    }
+}
+
+/// Represents global clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No clipboard context is available for the current environment.
+    NoContext,
+
+    /// The global clipboard was already initialized.
+    AlreadyInitialized,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoContext => write!(f, "No clipboard context available for global clipboard"),
+            Error::AlreadyInitialized => write!(f, "Global clipboard is already initialized"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
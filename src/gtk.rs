@@ -0,0 +1,194 @@
+//! Use the GTK clipboard, for applications running a GTK main loop.
+//!
+//! [`GtkClipboardContext`] gets and sets contents through the clipboard of GTK 3, on whatever
+//! display the application is connected to. Contents are owned by the application like any
+//! contents copied through GTK widgets, and are served by its main loop.
+//!
+//! GTK must be initialized, and the context must be used on the thread running the main loop.
+//! Getting contents runs a nested main loop until the owner responds.
+//!
+//! Set contents are lost when the application exits, unless stored in a clipboard manager. GTK
+//! does this automatically when its main loop quits, or call
+//! [`persist`](ClipboardProviderExt::persist) to do so earlier.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::gtk::GtkClipboardContext;
+//!
+//! gtk::init().unwrap();
+//!
+//! let mut ctx = GtkClipboardContext::new().unwrap();
+//! println!("{:?}", ctx.get_contents());
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use gtk::gdk;
+use gtk::glib::prelude::*;
+use gtk::Clipboard;
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::selection::{Selection, SelectionClipboardProviderExt};
+use crate::{ClipboardProviderNew, ContentLifetime, ThreadAffinity};
+
+/// Clipboard context using the GTK clipboard.
+///
+/// The GTK clipboard is looked up on each operation, which fails with [`Error::NotInitialized`]
+/// if not on the main thread. So the context may be moved between threads, but only be used on
+/// the main thread.
+///
+/// See module documentation for more information.
+pub struct GtkClipboardContext;
+
+impl GtkClipboardContext {
+    /// Construct a new context for the regular clipboard.
+    ///
+    /// Fails if GTK isn't initialized, or if not called on the main thread.
+    pub fn new() -> crate::ClipResult<Self> {
+        clipboard(Selection::Clipboard)?;
+        Ok(GtkClipboardContext)
+    }
+}
+
+impl ClipboardProvider for GtkClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.get_selection(Selection::Clipboard)
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.set_selection(Selection::Clipboard, contents)
+    }
+}
+
+impl ClipboardProviderNew for GtkClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        GtkClipboardContext::new()
+    }
+}
+
+impl SelectionClipboardProviderExt for GtkClipboardContext {
+    fn supports_selection(&self, _selection: Selection) -> bool {
+        true
+    }
+
+    fn get_selection(&mut self, selection: Selection) -> crate::ClipResult<String> {
+        get_text(&clipboard(selection)?)
+    }
+
+    fn set_selection(&mut self, selection: Selection, contents: String) -> crate::ClipResult<()> {
+        clipboard(selection)?.set_text(&contents);
+        Ok(())
+    }
+}
+
+/// Contents are served by the GTK main loop of this application.
+impl ClipboardProviderExt for GtkClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        let display = clipboard(Selection::Clipboard).ok()?.display()?;
+        match display.type_().name() {
+            "GdkX11Display" => Some(DisplayServer::X11),
+            "GdkWaylandDisplay" => Some(DisplayServer::Wayland),
+            "GdkQuartzDisplay" => Some(DisplayServer::MacOs),
+            "GdkWin32Display" => Some(DisplayServer::Windows),
+            _ => None,
+        }
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Process
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        true
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        ThreadAffinity::MainThread
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        Ok(targets(&clipboard(Selection::Clipboard)?).is_empty())
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        let targets = targets(&clipboard(Selection::Clipboard)?);
+        Ok(crate::content::mime_types(
+            targets.iter().map(|target| target.as_str()),
+        ))
+    }
+
+    /// Store the contents in a clipboard manager.
+    ///
+    /// Blocks until the clipboard manager took the contents, or for at most a few seconds if
+    /// there is none.
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        clipboard(Selection::Clipboard)?.store();
+        Ok(())
+    }
+}
+
+/// Get the GTK clipboard for the given selection on the default display.
+fn clipboard(selection: Selection) -> Result<Clipboard, Error> {
+    if !gtk::is_initialized_main_thread() {
+        return Err(Error::NotInitialized);
+    }
+    let atom = match selection {
+        Selection::Clipboard => gdk::SELECTION_CLIPBOARD,
+        Selection::Primary => gdk::SELECTION_PRIMARY,
+        Selection::Secondary => gdk::SELECTION_SECONDARY,
+    };
+    Ok(Clipboard::get(&atom))
+}
+
+/// Get text contents of the given clipboard, empty if it has no contents.
+fn get_text(clipboard: &Clipboard) -> crate::ClipResult<String> {
+    match clipboard.wait_for_text() {
+        Some(text) => Ok(text.into()),
+        None if targets(clipboard).is_empty() => Ok(String::new()),
+        None => Err(Error::NoText.into()),
+    }
+}
+
+/// Names of the targets the given clipboard currently offers.
+fn targets(clipboard: &Clipboard) -> Vec<String> {
+    clipboard
+        .wait_for_targets()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|target| target.name().into())
+        .collect()
+}
+
+/// Represents GTK clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// GTK isn't initialized, or not used on the main thread.
+    NotInitialized,
+
+    /// The clipboard has contents, but none can be converted to text.
+    NoText,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotInitialized => write!(
+                f,
+                "GTK is not initialized, or clipboard not used on the main thread"
+            ),
+            Error::NoText => write!(f, "Clipboard contents are not available as text"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,305 @@
+//! Temporarily set clipboard contents.
+//!
+//! A [`ClipboardGuard`] saves the current clipboard contents, sets new contents, and restores the
+//! original contents when dropped. This is useful to temporarily copy a one-time password for
+//! example, and put the previous clipboard contents of the user back afterwards.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use copypasta_ext::guard::ClipboardGuard;
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! let mut ctx = ClipboardContext::new().unwrap();
+//! let guard = ClipboardGuard::new(&mut ctx, "123456".into()).unwrap();
+//!
+//! // Restore previous contents after 30 seconds
+//! guard.restore_after(Duration::from_secs(30)).unwrap();
+//! ```
+//!
+//! Use [`set_contents_temporarily`] to restore in the background instead, only if the contents
+//! weren't changed in the meantime. This matches what password managers do:
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use copypasta_ext::guard;
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! let ctx = ClipboardContext::new().unwrap();
+//! let handle = guard::set_contents_temporarily(ctx, "123456".into(), Duration::from_secs(30))
+//!     .unwrap();
+//!
+//! // Keep the process alive until restored
+//! handle.join().unwrap().unwrap();
+//! ```
+//!
+//! Use [`ClearOnExit`] to clear copied secrets when the process exits, on exit paths you don't
+//! control:
+//!
+//! ```rust,no_run
+//! use copypasta_ext::guard::ClearOnExit;
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! let ctx = ClipboardContext::new().unwrap();
+//! let _clear = ClearOnExit::set(ctx, "123456".into()).unwrap();
+//!
+//! // Cleared even if the process exits here
+//! std::process::exit(0);
+//! ```
+
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, Once, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+
+use crate::prelude::*;
+
+/// Contents to clear, shared between a [`ClearOnExit`] guard and the exit registry.
+type SharedClear = Arc<Mutex<Option<PendingClear>>>;
+
+/// Clears pending when the process exits, registered by [`ClearOnExit`].
+static CLEAR_ON_EXIT: Lazy<Mutex<Vec<SharedClear>>> = Lazy::new(Default::default);
+
+/// Registers [`clear_all_on_exit`] with the C runtime once.
+static REGISTER_AT_EXIT: Once = Once::new();
+
+/// The process that registered [`clear_all_on_exit`], forks inherit the registration.
+static AT_EXIT_PID: AtomicU32 = AtomicU32::new(0);
+
+extern "C" {
+    fn atexit(callback: extern "C" fn()) -> c_int;
+}
+
+/// Temporarily set clipboard contents, restoring the previous contents on drop.
+///
+/// Errors while restoring on drop are ignored, use [`restore`](ClipboardGuard::restore) to
+/// handle them. If the clipboard was empty or couldn't be read, it is restored by setting empty
+/// contents.
+///
+/// See module documentation for more information.
+///
+/// ```rust
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::guard::ClipboardGuard;
+/// use copypasta_ext::testing::DryRunClipboardContext;
+///
+/// let mut ctx = DryRunClipboardContext::with_contents("previous");
+/// {
+///     let _guard = ClipboardGuard::new(&mut ctx, "secret".into()).unwrap();
+/// }
+/// assert_eq!(ctx.get_contents().unwrap(), "previous");
+/// ```
+pub struct ClipboardGuard<'a, C>
+where
+    C: ClipboardProvider + ?Sized,
+{
+    context: &'a mut C,
+    previous: Option<String>,
+    active: bool,
+}
+
+impl<'a, C> ClipboardGuard<'a, C>
+where
+    C: ClipboardProvider + ?Sized,
+{
+    /// Save the current clipboard contents, and set the given contents.
+    pub fn new(context: &'a mut C, contents: String) -> crate::ClipResult<Self> {
+        let previous = context.get_contents().ok();
+        context.set_contents(contents)?;
+        Ok(Self {
+            context,
+            previous,
+            active: true,
+        })
+    }
+
+    /// The saved previous clipboard contents, if any.
+    pub fn previous(&self) -> Option<&str> {
+        self.previous.as_deref()
+    }
+
+    /// Restore the previous clipboard contents now.
+    pub fn restore(mut self) -> crate::ClipResult<()> {
+        self.restore_inner()
+    }
+
+    /// Wait for the given duration, then restore the previous clipboard contents.
+    ///
+    /// Blocks the current thread.
+    pub fn restore_after(self, duration: Duration) -> crate::ClipResult<()> {
+        thread::sleep(duration);
+        self.restore()
+    }
+
+    /// Keep the new clipboard contents, don't restore the previous contents.
+    pub fn keep(mut self) {
+        self.active = false;
+    }
+
+    fn restore_inner(&mut self) -> crate::ClipResult<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.active = false;
+        let previous = self.previous.take().unwrap_or_default();
+        self.context.set_contents(previous)
+    }
+}
+
+impl<'a, C> Drop for ClipboardGuard<'a, C>
+where
+    C: ClipboardProvider + ?Sized,
+{
+    fn drop(&mut self) {
+        let _ = self.restore_inner();
+    }
+}
+
+/// Set clipboard contents for the given time to live, then restore the previous contents.
+///
+/// Saves the current clipboard contents and sets the given contents. A background thread waits
+/// for the time to live to elapse. If the clipboard still holds the given contents by then, the
+/// previous contents are restored. If the clipboard was changed in the meantime it is left
+/// untouched, so contents copied by the user aren't overwritten.
+///
+/// Errors setting the contents are returned immediately. The returned handle yields the result
+/// of restoring. The background thread is stopped if the process exits, keep the process alive
+/// until the handle is joined to make sure the contents are restored.
+pub fn set_contents_temporarily<C>(
+    mut context: C,
+    contents: String,
+    ttl: Duration,
+) -> crate::ClipResult<JoinHandle<crate::ClipResult<()>>>
+where
+    C: ClipboardProvider + 'static,
+{
+    let previous = context.get_contents().ok();
+    context.set_contents(contents.clone())?;
+
+    Ok(thread::spawn(move || {
+        thread::sleep(ttl);
+        match context.get_contents() {
+            Ok(current) if current == contents => {
+                context.set_contents(previous.unwrap_or_default())
+            }
+            _ => Ok(()),
+        }
+    }))
+}
+
+/// Clear the clipboard when dropped or when the process exits, if it still holds our contents.
+///
+/// Sets the given contents, and clears them again when this guard is dropped. Also clears them
+/// when the process exits normally without dropping this guard, such as through
+/// [`std::process::exit`] or returning from `main` while the guard is held in a static. If the
+/// clipboard was changed in the meantime it is left untouched, so contents copied by the user
+/// aren't cleared.
+///
+/// Clearing is not possible if the process is killed, or if it aborts.
+///
+/// ```rust
+/// use copypasta_ext::GlobalClipboard;
+/// use copypasta_ext::guard::ClearOnExit;
+/// use copypasta_ext::testing::DryRunClipboardContext;
+///
+/// let clipboard = GlobalClipboard::init(DryRunClipboardContext::new()).unwrap();
+/// let clear = ClearOnExit::set(clipboard.clone(), "secret".into()).unwrap();
+/// assert_eq!(clipboard.paste().unwrap(), "secret");
+/// drop(clear);
+/// assert_eq!(clipboard.paste().unwrap(), "");
+/// ```
+pub struct ClearOnExit {
+    pending: SharedClear,
+}
+
+impl ClearOnExit {
+    /// Set the given contents on the clipboard, and clear them on drop or exit.
+    pub fn set<C>(mut context: C, contents: String) -> crate::ClipResult<Self>
+    where
+        C: ClipboardProvider + 'static,
+    {
+        context.set_contents(contents.clone())?;
+
+        let pending = Arc::new(Mutex::new(Some(PendingClear {
+            context: Box::new(context),
+            contents,
+        })));
+        lock(&CLEAR_ON_EXIT).push(pending.clone());
+        REGISTER_AT_EXIT.call_once(|| unsafe {
+            AT_EXIT_PID.store(std::process::id(), Ordering::SeqCst);
+            atexit(clear_all_on_exit);
+        });
+
+        Ok(Self { pending })
+    }
+
+    /// Clear the clipboard now, if it still holds our contents.
+    pub fn clear(self) -> crate::ClipResult<()> {
+        self.clear_inner()
+    }
+
+    /// Keep the contents, don't clear them on drop or exit.
+    pub fn keep(self) {
+        lock(&self.pending).take();
+    }
+
+    fn clear_inner(&self) -> crate::ClipResult<()> {
+        lock(&CLEAR_ON_EXIT).retain(|pending| !Arc::ptr_eq(pending, &self.pending));
+        let pending = lock(&self.pending).take();
+        match pending {
+            Some(pending) => pending.clear(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for ClearOnExit {
+    fn drop(&mut self) {
+        let _ = self.clear_inner();
+    }
+}
+
+/// Contents to clear, along with the context they were set on.
+struct PendingClear {
+    context: Box<dyn ClipboardProvider>,
+    contents: String,
+}
+
+impl PendingClear {
+    /// Clear the clipboard, if it still holds our contents.
+    fn clear(mut self) -> crate::ClipResult<()> {
+        match self.context.get_contents() {
+            Ok(current) if current == self.contents => self.context.set_contents(String::new()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Clear all pending contents, called by the C runtime when the process exits.
+///
+/// Does nothing in forks, such as those serving contents for `x11_fork`, which would otherwise
+/// clear contents of the parent on their exit.
+extern "C" fn clear_all_on_exit() {
+    if std::process::id() == AT_EXIT_PID.load(Ordering::SeqCst) {
+        clear_all();
+    }
+}
+
+/// Clear all contents pending to be cleared on exit, if the clipboard still holds them.
+pub(crate) fn clear_all() {
+    let pending: Vec<_> = lock(&CLEAR_ON_EXIT).drain(..).collect();
+    for pending in pending {
+        if let Some(pending) = lock(&pending).take() {
+            let _ = pending.clear();
+        }
+    }
+}
+
+/// Lock the given mutex, ignoring poisoning.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
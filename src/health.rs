@@ -0,0 +1,146 @@
+//! Check whether a clipboard context works.
+//!
+//! [`health_check`](crate::ClipboardProviderExt::health_check) probes a context at startup, so
+//! applications can fall back to another context or warn the user early instead of failing on
+//! the first copy. The check avoids clobbering the user's clipboard: contents are only set back to
+//! what they already were, and only when that loses nothing.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//!
+//! let mut ctx = copypasta_ext::try_context().expect("failed to get clipboard context");
+//! let report = ctx.health_check();
+//! if !report.is_healthy() {
+//!     eprintln!("Clipboard doesn't work:\n{}", report);
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::prelude::*;
+use crate::ContentLifetime;
+
+/// Report of a clipboard health check.
+///
+/// Implements [`Display`](fmt::Display) for a human readable summary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct HealthReport {
+    /// Getting the clipboard contents.
+    pub get: CheckStatus,
+
+    /// Setting the clipboard contents, and getting them back unchanged.
+    pub set: CheckStatus,
+}
+
+impl HealthReport {
+    /// Whether no check failed.
+    ///
+    /// Skipped checks don't count as failure.
+    pub fn is_healthy(&self) -> bool {
+        !self.get.is_failed() && !self.set.is_failed()
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "get: {}\nset: {}", self.get, self.set)
+    }
+}
+
+/// Status of a single check in a [`HealthReport`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+pub enum CheckStatus {
+    /// The check passed.
+    Passed,
+
+    /// The check was skipped, holds the reason.
+    Skipped(String),
+
+    /// The check failed, holds the error.
+    Failed(String),
+}
+
+impl CheckStatus {
+    /// Whether the check failed.
+    pub fn is_failed(&self) -> bool {
+        matches!(self, CheckStatus::Failed(_))
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckStatus::Passed => write!(f, "passed"),
+            CheckStatus::Skipped(reason) => write!(f, "skipped, {}", reason),
+            CheckStatus::Failed(err) => write!(f, "failed, {}", err),
+        }
+    }
+}
+
+/// Run a health check on the given context, see
+/// [`health_check`](crate::ClipboardProviderExt::health_check).
+pub(crate) fn check<C>(context: &mut C) -> HealthReport
+where
+    C: ClipboardProviderExt + ?Sized,
+{
+    let contents = match context.get_contents() {
+        Ok(contents) => contents,
+        Err(err) => {
+            return HealthReport {
+                get: CheckStatus::Failed(err.to_string()),
+                set: CheckStatus::Skipped("would replace current contents".into()),
+            }
+        }
+    };
+
+    let set = match round_trip(context, contents) {
+        Ok(()) => CheckStatus::Passed,
+        Err(status) => status,
+    };
+    HealthReport {
+        get: CheckStatus::Passed,
+        set,
+    }
+}
+
+/// Set the given current contents again, and check they are returned unchanged.
+fn round_trip<C>(context: &mut C, contents: String) -> Result<(), CheckStatus>
+where
+    C: ClipboardProviderExt + ?Sized,
+{
+    // Contents would be lost on exit, or rich representations would be replaced by plain text
+    if context.content_lifetime() == ContentLifetime::Process {
+        return Err(CheckStatus::Skipped(
+            "contents would be lost on exit".into(),
+        ));
+    }
+    let mime_types = context
+        .available_mime_types()
+        .map_err(|err| CheckStatus::Failed(err.to_string()))?;
+    if mime_types.iter().any(|m| !m.starts_with("text/plain")) {
+        return Err(CheckStatus::Skipped(
+            "would replace current rich contents".into(),
+        ));
+    }
+
+    context
+        .set_contents(contents.clone())
+        .map_err(|err| CheckStatus::Failed(err.to_string()))?;
+    let result = context
+        .get_contents()
+        .map_err(|err| CheckStatus::Failed(err.to_string()))?;
+    if result != contents {
+        return Err(CheckStatus::Failed("got different contents back".into()));
+    }
+    Ok(())
+}
@@ -6,13 +6,79 @@
 //! - [`X11ForkClipboardProvider`](https://docs.rs/copypasta-ext/*/copypasta_ext/x11_fork/index.html):
 //!   forks process and sets clipboard on X11, keeps contents after exit
 //! - [`X11BinClipboardProvider`](https://docs.rs/copypasta-ext/*/copypasta_ext/x11_bin/index.html):
-//!   invokes `xclip`/`xsel` to set clipboard on X11, keeps contents after exit
+//!   invokes `xclip`/`xsel` to set clipboard on X11, keeps contents after exit, or the bundled
+//!   helper binary with the `x11-helper` feature
 //! - [`WaylandBinClipboardProvider`](https://docs.rs/copypasta-ext/*/copypasta_ext/wayland_bin/index.html):
 //!   invokes `wl-copy`/`wl-paste` to set clipboard on Wayland
+//! - [`DataControlClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/wayland_native/struct.DataControlClipboardContext.html):
+//!   get and set clipboard on wlroots based Wayland compositors directly, without focus
+//! - [`WslBinClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/wsl_bin/index.html):
+//!   invokes `clip.exe`/`powershell.exe` to use the Windows clipboard from WSL
+//! - [`MacOsBinClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/macos_bin/index.html):
+//!   invokes `pbcopy`/`pbpaste` on macOS, when the native pasteboard is not accessible
 //! - [`Osc52ClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/osc52/index.html):
 //!   use OSC 52 escape sequence to set clipboard contents
+//! - [`CopyToClipboard`](https://docs.rs/copypasta-ext/*/copypasta_ext/osc52/struct.CopyToClipboard.html):
+//!   crossterm command to queue OSC 52 clipboard writes, requires the `crossterm` feature
+//! - [`BracketedPasteClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/bracketed_paste/index.html):
+//!   get clipboard contents through a user initiated terminal paste
+//! - [`GtkClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/gtk/index.html):
+//!   use the GTK clipboard in applications running a GTK main loop, requires the `gtk` feature
+//! - [`AndroidClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/android/index.html):
+//!   use the Android clipboard through JNI in apps embedding Rust code, requires the `android`
+//!   feature
+//! - [`X11ClipboardProviderExt`](https://docs.rs/copypasta-ext/*/copypasta_ext/x11_native/trait.X11ClipboardProviderExt.html):
+//!   query X11 clipboard details such as the selection owner directly
+//! - [`X11TimedClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/x11_native/struct.X11TimedClipboardContext.html):
+//!   get X11 clipboard contents with a timeout, instead of freezing on unresponsive applications
+//! - [`WindowsClipboardProviderExt`](https://docs.rs/copypasta-ext/*/copypasta_ext/windows_native/trait.WindowsClipboardProviderExt.html):
+//!   set rich contents such as HTML and RTF on Windows
+//! - [`MacOsClipboardProviderExt`](https://docs.rs/copypasta-ext/*/copypasta_ext/macos_native/trait.MacOsClipboardProviderExt.html):
+//!   set rich contents such as RTF, and multiple items, on macOS
+//! - [`SelectionClipboardProviderExt`](https://docs.rs/copypasta-ext/*/copypasta_ext/selection/trait.SelectionClipboardProviderExt.html):
+//!   get and set the primary selection through X11, Wayland and OSC 52 contexts alike
+//! - [`ClipboardTarget`](https://docs.rs/copypasta-ext/*/copypasta_ext/seat/enum.ClipboardTarget.html):
+//!   enumerate X11 displays and Wayland seats, and get a context bound to each
 //! - [`CombinedClipboardProvider`](https://docs.rs/copypasta-ext/*/copypasta_ext/struct.CombinedClipboardContext.html):
 //!   combine two providers, use different for getting/setting clipboard
+//! - [`ChainClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/combined/struct.ChainClipboardContext.html):
+//!   try a sequence of providers until one succeeds
+//! - [`MultiSetClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/combined/struct.MultiSetClipboardContext.html):
+//!   set contents on multiple providers at once
+//! - [`GlobalClipboard`](https://docs.rs/copypasta-ext/*/copypasta_ext/global/struct.GlobalClipboard.html):
+//!   thread-safe process wide clipboard handle
+//! - [`SharedClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/shared/struct.SharedClipboardContext.html):
+//!   cloneable handle sharing one context between threads
+//! - [`NonBlockingClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/nonblocking/struct.NonBlockingClipboardContext.html):
+//!   get and set contents without blocking, for single-threaded applications
+//! - [`Watcher`](https://docs.rs/copypasta-ext/*/copypasta_ext/watch/struct.Watcher.html):
+//!   watch clipboard contents for changes
+//! - [`ClipboardMirror`](https://docs.rs/copypasta-ext/*/copypasta_ext/mirror/struct.ClipboardMirror.html):
+//!   mirror contents between clipboards, such as X11 `PRIMARY` and `CLIPBOARD`
+//! - [`NormalizedClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/normalize/struct.NormalizedClipboardContext.html):
+//!   normalize contents such as line endings when getting or setting
+//! - [`ClipboardGuard`](https://docs.rs/copypasta-ext/*/copypasta_ext/guard/struct.ClipboardGuard.html):
+//!   temporarily set contents, restoring the previous contents afterwards
+//! - [`NotifyClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/notify/struct.NotifyClipboardContext.html):
+//!   show a desktop notification when setting contents
+//! - [`TimestampClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/timestamp/struct.TimestampClipboardContext.html):
+//!   track when contents were set, such as to show "copied 30 seconds ago"
+//! - [`AuditClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/audit/struct.AuditClipboardContext.html):
+//!   keep an audit trail of clipboard operations, without recording contents
+//! - [`FilteredClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/filter/struct.FilteredClipboardContext.html):
+//!   scrub or reject contents, such as credit card numbers, before setting
+//! - [`PermissionClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/permission/struct.PermissionClipboardContext.html):
+//!   ask a callback for permission before each clipboard access
+//! - [`health_check`](https://docs.rs/copypasta-ext/*/copypasta_ext/health/index.html):
+//!   check whether a clipboard context works at startup, without clobbering contents
+//! - [`ClipboardConfig`](https://docs.rs/copypasta-ext/*/copypasta_ext/config/struct.ClipboardConfig.html):
+//!   configure backends, timeouts and binary paths at runtime, such as from environment variables
+//! - [`diagnostics`](https://docs.rs/copypasta-ext/*/copypasta_ext/fn.diagnostics.html):
+//!   describe the clipboard environment, for bug reports
+//! - [`signal`](https://docs.rs/copypasta-ext/*/copypasta_ext/signal/index.html):
+//!   clear secrets or persist contents when interrupted by `Ctrl+C`, requires the `signal` feature
+//! - [`LockedClipboardContext`](https://docs.rs/copypasta-ext/*/copypasta_ext/lock/struct.LockedClipboardContext.html):
+//!   coordinate sets between processes through a lock file, requires the `lock` feature
 //!
 //! # Example
 //!
@@ -26,6 +92,16 @@
 //! ctx.set_contents("some string".into()).unwrap();
 //! ```
 //!
+//! Or use the global clipboard through
+//! [`copy`](https://docs.rs/copypasta-ext/*/copypasta_ext/fn.copy.html) and
+//! [`paste`](https://docs.rs/copypasta-ext/*/copypasta_ext/fn.paste.html), without managing a
+//! context:
+//!
+//! ```rust,no_run
+//! copypasta_ext::copy("some string").unwrap();
+//! println!("{}", copypasta_ext::paste().unwrap());
+//! ```
+//!
 //! Get and set clipboard contents. Keeps contents in X11 clipboard after exit by forking the
 //! process (which normally doesn't work with copypasta's X11ClipboardContext). Falls back to
 //! standard clipboard provider on non X11 platforms. See
@@ -57,22 +133,127 @@
 //!
 //! # Requirements
 //!
-//! - Rust 1.47 or above
+//! - Rust 1.60 or above
 //! - Same requirements as [`copypasta`][copypasta]
 //! - Requirements noted in specific clipboard context modules
 //!
 //! [copypasta]: https://github.com/alacritty/copypasta
 
-mod combined;
+/// Log a debug message, if the `log` feature is enabled.
+///
+/// Clipboard contents must only be logged through [`redact::Redacted`].
+#[allow(unused_macros)]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+    };
+}
+
+#[cfg(all(feature = "android", target_os = "android"))]
+pub mod android;
+#[cfg(feature = "clipboard")]
+pub mod audit;
+#[cfg(all(feature = "bracketed-paste", unix))]
+pub mod bracketed_paste;
+#[cfg(feature = "clipboard")]
+pub mod combined;
+#[cfg(any(
+    all(
+        any(feature = "x11-bin", feature = "wayland-bin", feature = "wsl-bin"),
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ),
+    all(feature = "macos-bin", target_os = "macos")
+))]
+pub mod command;
+#[cfg(feature = "clipboard")]
+pub mod config;
+pub mod content;
+mod diagnostics;
 pub mod display;
+#[cfg(feature = "clipboard")]
+pub mod filter;
+#[cfg(feature = "clipboard")]
+pub mod global;
+#[cfg(feature = "gtk")]
+pub mod gtk;
+#[cfg(feature = "clipboard")]
+pub mod guard;
+#[cfg(feature = "clipboard")]
+pub mod health;
+#[cfg(all(feature = "lock", unix))]
+pub mod lock;
+#[cfg(all(feature = "macos-bin", target_os = "macos"))]
+pub mod macos_bin;
+#[cfg(all(feature = "clipboard", target_os = "macos"))]
+pub mod macos_native;
+#[cfg(feature = "clipboard")]
+pub mod mirror;
+#[cfg(feature = "clipboard")]
+pub mod nonblocking;
+#[cfg(feature = "clipboard")]
+pub mod normalize;
+#[cfg(feature = "notify")]
+pub mod notify;
 #[cfg(feature = "osc52")]
 pub mod osc52;
+#[cfg(feature = "clipboard")]
+pub mod permission;
+#[cfg(feature = "clipboard")]
+pub mod persist;
+#[cfg(any(
+    all(
+        any(
+            feature = "x11-bin",
+            feature = "wayland-bin",
+            feature = "wsl-bin",
+            feature = "x11-fork"
+        ),
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ),
+    all(feature = "macos-bin", target_os = "macos")
+))]
+mod process;
+pub mod redact;
+#[cfg(all(
+    feature = "clipboard",
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+pub mod seat;
+#[cfg(feature = "clipboard")]
+pub mod selection;
+#[cfg(feature = "clipboard")]
+pub mod shared;
+#[cfg(all(feature = "signal", unix))]
+pub mod signal;
+#[cfg(feature = "clipboard")]
+pub mod stream;
+#[cfg(feature = "clipboard")]
+pub mod testing;
+#[cfg(feature = "clipboard")]
+pub mod timestamp;
+pub mod uri;
+#[cfg(feature = "clipboard")]
+pub mod watch;
 #[cfg(all(
     feature = "wayland-bin",
     unix,
     not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
 ))]
 pub mod wayland_bin;
+#[cfg(all(
+    feature = "wayland-native",
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+pub mod wayland_native;
+#[cfg(all(feature = "clipboard", windows))]
+pub mod windows_native;
+#[cfg(all(feature = "wsl-bin", target_os = "linux"))]
+pub mod wsl_bin;
 #[cfg(all(
     feature = "x11-bin",
     unix,
@@ -85,44 +266,147 @@ pub mod x11_bin;
     not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
 ))]
 pub mod x11_fork;
-
-// Expose platform specific contexts
-#[cfg(not(all(
-    feature = "wayland-bin",
+#[cfg(all(
+    feature = "x11-native",
     unix,
     not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
-)))]
+))]
+pub mod x11_native;
+
+// Expose platform specific contexts, copypasta has no context on X11 platforms without its X11
+// support
+#[cfg(all(
+    feature = "clipboard",
+    any(
+        feature = "x11",
+        not(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "ios",
+                target_os = "emscripten"
+            ))
+        ))
+    ),
+    not(all(feature = "macos-bin", target_os = "macos"))
+))]
+pub mod macos_bin {
+    /// No macOS binary (`macos-bin`) support. Fallback to `copypasta::ClipboardContext`.
+    pub type ClipboardContext = copypasta::ClipboardContext;
+}
+#[cfg(all(
+    feature = "clipboard",
+    any(
+        feature = "x11",
+        not(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "ios",
+                target_os = "emscripten"
+            ))
+        ))
+    ),
+    not(all(
+        feature = "wayland-bin",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))
+))]
 pub mod wayland_bin {
     /// No Wayland binary (`wayland-bin`) support. Fallback to `copypasta::ClipboardContext`.
     pub type ClipboardContext = copypasta::ClipboardContext;
 }
-#[cfg(not(all(
-    feature = "x11-bin",
-    unix,
-    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
-)))]
+#[cfg(all(
+    feature = "clipboard",
+    any(
+        feature = "x11",
+        not(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "ios",
+                target_os = "emscripten"
+            ))
+        ))
+    ),
+    not(all(feature = "wsl-bin", target_os = "linux"))
+))]
+pub mod wsl_bin {
+    /// No WSL binary (`wsl-bin`) support. Fallback to `copypasta::ClipboardContext`.
+    pub type ClipboardContext = copypasta::ClipboardContext;
+}
+#[cfg(all(
+    feature = "clipboard",
+    any(
+        feature = "x11",
+        not(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "ios",
+                target_os = "emscripten"
+            ))
+        ))
+    ),
+    not(all(
+        feature = "x11-bin",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))
+))]
 pub mod x11_bin {
     /// No X11 binary (`x11-bin`) support. Fallback to `copypasta::ClipboardContext`.
     pub type ClipboardContext = copypasta::ClipboardContext;
 }
-#[cfg(not(all(
-    feature = "x11-fork",
-    unix,
-    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
-)))]
+#[cfg(all(
+    feature = "clipboard",
+    any(
+        feature = "x11",
+        not(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "ios",
+                target_os = "emscripten"
+            ))
+        ))
+    ),
+    not(all(
+        feature = "x11-fork",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))
+))]
 pub mod x11_fork {
     /// No X11 fork (`x11-fork`) support. Fallback to `copypasta::ClipboardContext`.
     pub type ClipboardContext = copypasta::ClipboardContext;
 }
 
 use std::error::Error;
+#[cfg(feature = "clipboard")]
+use std::io::{Read, Write};
 
 /// Copypasta result type, for your convenience.
 pub type ClipResult<T> = Result<T, Box<dyn Error + Send + Sync + 'static>>;
 
 // Re-export
-pub use combined::CombinedClipboardContext;
+#[cfg(feature = "clipboard")]
+pub use combined::{
+    ChainClipboardContext, CombinedClipboardContext, FallbackClipboardContext,
+    MultiSetClipboardContext,
+};
+#[cfg(feature = "clipboard")]
 pub use copypasta;
+#[cfg(feature = "clipboard")]
+pub use global::GlobalClipboard;
+#[cfg(feature = "clipboard")]
+pub use selection::Selection;
 
 /// Try to get clipboard context.
 ///
@@ -132,44 +416,956 @@ pub use copypasta;
 ///
 /// Note: this function may be used to automatically select an X11 or Wayland clipboard on Unix
 /// systems based on the runtime environment.
+///
+/// The returned context implements [`ClipboardProviderExt`], so backend properties such as the
+/// display server and content lifetime remain queryable on it.
+///
+/// The returned context is [`Send`], and may be moved to other threads. Use
+/// [`try_context_sync`] if you need it to be [`Sync`] as well.
+///
+/// ```rust,no_run
+/// let mut ctx = copypasta_ext::try_context().expect("failed to get clipboard context");
+/// println!("Using display server: {:?}", ctx.display_server());
+/// if ctx.content_lifetime() == copypasta_ext::ContentLifetime::Process {
+///     println!("Clipboard contents are lost when this application exits");
+/// }
+/// ctx.set_contents("some string".into()).unwrap();
+/// ```
+#[cfg(feature = "clipboard")]
 pub fn try_context() -> Option<Box<dyn ClipboardProviderExt>> {
     display::DisplayServer::select().try_context()
 }
 
+/// Try to get clipboard context for the given display server.
+///
+/// Like [`try_context`], but skips display server detection. Useful if your application has its
+/// own detection logic, or if the user configured what display server is used.
+///
+/// ```rust,no_run
+/// use copypasta_ext::display::DisplayServer;
+///
+/// let mut ctx = copypasta_ext::try_context_for(DisplayServer::Wayland)
+///     .expect("failed to get clipboard context");
+/// ctx.set_contents("some string".into()).unwrap();
+/// ```
+#[cfg(feature = "clipboard")]
+pub fn try_context_for(
+    display_server: display::DisplayServer,
+) -> Option<Box<dyn ClipboardProviderExt>> {
+    display_server.try_context()
+}
+
+/// Try to get clipboard context, along with a report of the selection.
+///
+/// Like [`try_context`], but also returns a [`SelectionReport`](display::SelectionReport)
+/// describing the detected display server, the considered backends, the chosen backend and why
+/// others were skipped. Useful to include in bug reports.
+///
+/// ```rust,no_run
+/// let (ctx, report) = copypasta_ext::try_context_with_report();
+/// if ctx.is_none() {
+///     eprintln!("No clipboard available:\n{}", report);
+/// }
+/// ```
+#[cfg(feature = "clipboard")]
+pub fn try_context_with_report() -> (
+    Option<Box<dyn ClipboardProviderExt>>,
+    display::SelectionReport,
+) {
+    display::DisplayServer::select().try_context_with_report()
+}
+
+/// Try to get clipboard context, configured through the given configuration.
+///
+/// Like [`try_context`], but selects a context for the configured display server, only tries the
+/// configured backends, and applies the configuration to the chosen context. See
+/// [`ClipboardConfig`](config::ClipboardConfig).
+///
+/// ```rust,no_run
+/// use copypasta_ext::config::ClipboardConfig;
+///
+/// let config = ClipboardConfig::from_env().expect("invalid clipboard configuration");
+/// let mut ctx = copypasta_ext::try_context_with_config(&config)
+///     .expect("failed to get clipboard context");
+/// ```
+#[cfg(feature = "clipboard")]
+pub fn try_context_with_config(
+    config: &config::ClipboardConfig,
+) -> Option<Box<dyn ClipboardProviderExt>> {
+    config
+        .display_server
+        .unwrap_or_else(display::DisplayServer::select)
+        .try_context_with_config(config)
+        .0
+}
+
+/// Describe the clipboard environment, for bug reports.
+///
+/// Includes the detected display server, relevant environment variables, clipboard binaries
+/// found in `PATH` along with their versions, enabled compiler features and the context
+/// [`try_context`] would select. Clipboard contents are never included, and the home directory
+/// is replaced by `~`. Users may paste this into issues.
+///
+/// Invokes the found clipboard binaries to get their versions.
+///
+/// ```rust,no_run
+/// eprintln!("{}", copypasta_ext::diagnostics());
+/// ```
+pub fn diagnostics() -> String {
+    diagnostics::report()
+}
+
+/// Try to get combined clipboard context.
+///
+/// Like [`try_context`], but automatically pairs the fastest available context for getting
+/// with the most persistent available context for setting clipboard contents. This saves you
+/// from combining contexts yourself, such as through
+/// [`X11BinClipboardContext::new_with_x11`](x11_bin/struct.X11BinClipboardContext.html#method.new_with_x11).
+///
+/// See [`DisplayServer::try_combined_context`](display::DisplayServer::try_combined_context)
+/// for the selected combinations.
+///
+/// ```rust,no_run
+/// let mut ctx = copypasta_ext::try_combined_context().expect("failed to get clipboard context");
+/// println!("{:?}", ctx.get_contents());
+/// ctx.set_contents("some string".into()).unwrap();
+/// ```
+#[cfg(feature = "clipboard")]
+pub fn try_combined_context() -> Option<Box<dyn ClipboardProviderExt>> {
+    display::DisplayServer::select().try_combined_context()
+}
+
+/// Try to get clipboard context that is [`Send`] and [`Sync`].
+///
+/// Like [`try_context`], but wraps the context in a
+/// [`SyncClipboardContext`](shared::SyncClipboardContext) so it can be stored in shared
+/// application state, for example behind an `Arc`.
+///
+/// ```rust,no_run
+/// use std::sync::{Arc, Mutex};
+///
+/// let ctx = copypasta_ext::try_context_sync().expect("failed to get clipboard context");
+/// let state = Arc::new(Mutex::new(ctx));
+/// std::thread::spawn(move || {
+///     state.lock().unwrap().set_contents("some string".into()).unwrap();
+/// });
+/// ```
+#[cfg(feature = "clipboard")]
+pub fn try_context_sync() -> Option<Box<dyn ClipboardProviderExt + Sync>> {
+    try_context().map(|context| -> Box<dyn ClipboardProviderExt + Sync> {
+        Box::new(shared::SyncClipboardContext::new(context))
+    })
+}
+
+/// Set clipboard contents through the global clipboard.
+///
+/// Uses the process wide [`GlobalClipboard`], selecting a context on first use. Useful for
+/// simple tools that don't want to manage a context. Contents set through a context with a
+/// [`ContentLifetime::Process`] lifetime are lost when the process exits, see
+/// [`persist`](ClipboardProviderExt::persist).
+///
+/// ```rust,no_run
+/// copypasta_ext::copy("some string").unwrap();
+/// ```
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> ClipResult<()> {
+    GlobalClipboard::get()?.copy(text)
+}
+
+/// Get clipboard contents through the global clipboard.
+///
+/// Uses the process wide [`GlobalClipboard`], selecting a context on first use. See [`copy`].
+///
+/// ```rust,no_run
+/// println!("{}", copypasta_ext::paste().unwrap());
+/// ```
+#[cfg(feature = "clipboard")]
+pub fn paste() -> ClipResult<String> {
+    GlobalClipboard::get()?.paste()
+}
+
 /// Trait prelude.
 ///
 /// ```rust
 /// use copypasta_ext::prelude::*;
 /// ```
+#[cfg(feature = "clipboard")]
 pub mod prelude {
     pub use super::copypasta::ClipboardProvider;
     pub use super::ClipboardProviderExt;
 }
 
+/// Lifetime of clipboard contents set through a clipboard provider.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+pub enum ContentLifetime {
+    /// Contents are lost when the current process exits.
+    Process,
+
+    /// Contents survive the current process, served by a forked process until the clipboard
+    /// changes.
+    Fork,
+
+    /// Contents survive the current process, served by an external binary until the clipboard
+    /// changes.
+    Binary,
+
+    /// Contents are persisted by the system, a clipboard manager or the terminal, and survive
+    /// the current process.
+    Persistent,
+}
+
+/// Threads a clipboard provider may be used from.
+///
+/// Variants are ordered from least to most restrictive.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+#[non_exhaustive]
+pub enum ThreadAffinity {
+    /// Any thread.
+    Any,
+
+    /// Only the thread the provider was created on.
+    CreatingThread,
+
+    /// Only the main thread of the process.
+    MainThread,
+}
+
+/// Opaque token identifying a clipboard state.
+///
+/// Obtained through [`ClipboardProviderExt::change_token`]. Compare tokens from the same context
+/// to detect whether the clipboard changed. Tokens from different contexts are not comparable.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ChangeToken(u64);
+
+impl ChangeToken {
+    /// Construct token from a clipboard sequence number provided by the system.
+    #[allow(dead_code)]
+    fn from_sequence(sequence: u64) -> Self {
+        Self(sequence)
+    }
+
+    /// Construct token from a clipboard contents hash.
+    #[allow(dead_code)]
+    fn from_hash(hash: u64) -> Self {
+        Self(hash)
+    }
+}
+
+/// Writer feeding all written bytes into a hasher.
+#[cfg(feature = "clipboard")]
+struct HashWriter(std::collections::hash_map::DefaultHasher);
+
+#[cfg(feature = "clipboard")]
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        std::hash::Hasher::write(&mut self.0, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Extension trait for clipboard access
+#[cfg(feature = "clipboard")]
 pub trait ClipboardProviderExt: prelude::ClipboardProvider {
     /// Get related display server.
     fn display_server(&self) -> Option<display::DisplayServer>;
 
+    /// How long clipboard contents set through this provider remain available.
+    ///
+    /// Applications may use this to warn users, for example that copied contents are lost when
+    /// the application exits.
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
+    }
+
+    /// Whether the application must keep running an event loop for this provider to work.
+    ///
+    /// Providers built on the connection of the application, such as the native Wayland
+    /// clipboard, only work while the application dispatches its events and has input focus.
+    /// Frameworks may use this to decide where to host the context. Others work anywhere, even
+    /// in applications without any windows.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// assert!(!DryRunClipboardContext::new().requires_event_loop());
+    /// ```
+    fn requires_event_loop(&self) -> bool {
+        false
+    }
+
+    /// Which threads this provider may be used from.
+    ///
+    /// Frameworks may use this to decide where to host the context, such as on the main thread.
+    fn thread_affinity(&self) -> ThreadAffinity {
+        ThreadAffinity::Any
+    }
+
+    /// The selection this provider gets and sets, such as the primary selection on X11.
+    ///
+    /// Most providers use the regular clipboard. Watchers use this to watch the same selection,
+    /// see [`Watcher::new_auto`](watch::Watcher::new_auto).
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::Selection;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// assert_eq!(DryRunClipboardContext::new().selection(), Selection::Clipboard);
+    /// ```
+    fn selection(&self) -> Selection {
+        Selection::Clipboard
+    }
+
+    /// Get a token identifying the current clipboard state.
+    ///
+    /// The token changes when the clipboard contents change. Compare it to an earlier token to
+    /// cheaply detect whether the clipboard changed since. Uses a sequence number provided by the
+    /// system where available, such as on Windows and macOS. Falls back to hashing the clipboard contents
+    /// elsewhere, which requires fetching them.
+    ///
+    /// ```rust,no_run
+    /// let mut ctx = copypasta_ext::try_context().expect("failed to get clipboard context");
+    /// let token = ctx.change_token().unwrap();
+    /// // ...
+    /// if ctx.change_token().unwrap() != token {
+    ///     println!("Clipboard changed");
+    /// }
+    /// ```
+    fn change_token(&mut self) -> ClipResult<ChangeToken> {
+        Ok(ChangeToken::from_hash(self.content_hash()?))
+    }
+
+    /// Hash the current clipboard contents.
+    ///
+    /// The contents are streamed through the hasher using
+    /// [`get_contents_to`](ClipboardProviderExt::get_contents_to), so they aren't materialized
+    /// as string on contexts supporting streaming. Useful for deduplication of large contents,
+    /// such as in clipboard history managers.
+    ///
+    /// Hashes are only comparable within the same process.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::with_contents("some string");
+    /// let hash = ctx.content_hash().unwrap();
+    /// ctx.set_contents("other string".into()).unwrap();
+    /// assert_ne!(ctx.content_hash().unwrap(), hash);
+    /// ```
+    fn content_hash(&mut self) -> ClipResult<u64> {
+        let mut writer = HashWriter(Default::default());
+        self.get_contents_to(&mut writer)?;
+        Ok(std::hash::Hasher::finish(&writer.0))
+    }
+
+    /// Check whether the clipboard is empty.
+    ///
+    /// Clipboard contexts that can query the available formats do so, without fetching the
+    /// contents. Others fall back to getting the contents. Useful to grey out a paste button in
+    /// user interfaces.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::new();
+    /// assert!(ctx.is_empty().unwrap());
+    /// ctx.set_contents("some string".into()).unwrap();
+    /// assert!(!ctx.is_empty().unwrap());
+    /// ```
+    fn is_empty(&mut self) -> ClipResult<bool> {
+        Ok(self.get_contents()?.is_empty())
+    }
+
+    /// List the MIME types of the formats currently offered on the clipboard.
+    ///
+    /// Clipboard contexts that can query the available formats return the MIME type of all
+    /// formats that have one, such as `text/plain`, `text/html` and `image/png`. Others only
+    /// report `text/plain` if the clipboard isn't empty.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::with_contents("some string");
+    /// assert_eq!(ctx.available_mime_types().unwrap(), vec!["text/plain"]);
+    /// ```
+    fn available_mime_types(&mut self) -> ClipResult<Vec<String>> {
+        Ok(if self.is_empty()? {
+            vec![]
+        } else {
+            vec![content::ContentType::Text.mime_type().into()]
+        })
+    }
+
+    /// Get clipboard contents, only if changed since the given token.
+    ///
+    /// Returns `None` if the clipboard didn't change since `last` was obtained. Otherwise returns
+    /// the contents along with the current change token, to pass on the next call. On contexts
+    /// with a system provided sequence number, contents aren't transferred when unchanged. See
+    /// [`change_token`](ClipboardProviderExt::change_token).
+    ///
+    /// ```rust,no_run
+    /// use std::{thread, time::Duration};
+    ///
+    /// let mut ctx = copypasta_ext::try_context().expect("failed to get clipboard context");
+    /// let mut token = ctx.change_token().unwrap();
+    /// loop {
+    ///     if let Some((contents, new_token)) = ctx.get_contents_if_changed(token).unwrap() {
+    ///         println!("Clipboard changed: {}", contents);
+    ///         token = new_token;
+    ///     }
+    ///     thread::sleep(Duration::from_millis(100));
+    /// }
+    /// ```
+    fn get_contents_if_changed(
+        &mut self,
+        last: ChangeToken,
+    ) -> ClipResult<Option<(String, ChangeToken)>> {
+        let token = self.change_token()?;
+        if token == last {
+            return Ok(None);
+        }
+        Ok(Some((self.get_contents()?, token)))
+    }
+
+    /// Make the current clipboard contents outlive the current process.
+    ///
+    /// Contexts with a [`ContentLifetime::Process`] lifetime hand off the current contents to the
+    /// best available mechanism that outlives the process, see the [`persist`] module. Call this
+    /// once before exiting. Does nothing for contexts whose contents already outlive the process.
+    ///
+    /// Errors with [`persist::Error::Unsupported`] if contents can't be persisted.
+    fn persist(&mut self) -> ClipResult<()> {
+        match self.content_lifetime() {
+            ContentLifetime::Process => Err(persist::Error::Unsupported.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Clear the clipboard contents.
+    ///
+    /// On Windows and macOS the clipboard is emptied natively, so clipboard managers don't record
+    /// an empty entry. Other contexts set empty contents.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::with_contents("secret");
+    /// ctx.clear_contents().unwrap();
+    /// assert!(ctx.is_empty().unwrap());
+    /// ```
+    fn clear_contents(&mut self) -> ClipResult<()> {
+        self.set_contents(String::new())
+    }
+
+    /// Set the clipboard contents, returning the previous contents.
+    ///
+    /// Simplifies saving and replacing contents. Fails without setting if the previous contents
+    /// can't be read, so they aren't lost. Contexts shared through a lock, such as
+    /// [`SharedClipboardContext`](shared::SharedClipboardContext), hold it for both operations.
+    /// Other applications may still change the contents in between.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::with_contents("previous");
+    /// assert_eq!(ctx.swap_contents("new".into()).unwrap(), "previous");
+    /// assert_eq!(ctx.get_contents().unwrap(), "new");
+    /// ```
+    fn swap_contents(&mut self, contents: String) -> ClipResult<String> {
+        let previous = self.get_contents()?;
+        self.set_contents(contents)?;
+        Ok(previous)
+    }
+
+    /// Check whether this context works, without clobbering the clipboard.
+    ///
+    /// Gets the current contents, and sets them again to check they are returned unchanged.
+    /// Setting is skipped if it would lose anything, such as rich contents replaced by plain text
+    /// or contents only kept while this process runs. Useful to fall back to another context at
+    /// startup. See the [`health`] module.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::health::CheckStatus;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::with_contents("some string");
+    /// let report = ctx.health_check();
+    /// assert!(report.is_healthy());
+    /// assert_eq!(report.set, CheckStatus::Passed);
+    /// ```
+    fn health_check(&mut self) -> health::HealthReport {
+        health::check(self)
+    }
+
     /// If this clipboard provider only has a clipboard lifetime of the current binary, rather than
     /// forever.
+    #[deprecated(since = "0.4.5", note = "use content_lifetime instead")]
     fn has_bin_lifetime(&self) -> bool {
-        false
+        self.content_lifetime() == ContentLifetime::Process
+    }
+
+    /// Get clipboard contents, replacing invalid UTF-8.
+    ///
+    /// Like [`get_contents`](prelude::ClipboardProvider::get_contents), but if the clipboard
+    /// contents aren't valid UTF-8 any invalid sequences are replaced with
+    /// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD] instead of returning an error. Useful for user
+    /// facing paste operations.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::ClipboardContext;
+    ///
+    /// let mut ctx = ClipboardContext::new().unwrap();
+    /// println!("{}", ctx.get_contents_lossy().unwrap());
+    /// ```
+    ///
+    /// [U+FFFD]: std::char::REPLACEMENT_CHARACTER
+    fn get_contents_lossy(&mut self) -> ClipResult<String> {
+        self.get_contents().or_else(|err| {
+            invalid_utf8_bytes(err).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        })
+    }
+
+    /// Get clipboard contents as a single URL.
+    ///
+    /// Errors with [`uri::Error::NoUrl`] if the clipboard holds anything else. See
+    /// [`uri::parse_url`] for the accepted forms.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::with_contents("https://example.com/\n");
+    /// assert_eq!(ctx.get_url().unwrap(), "https://example.com/");
+    /// ```
+    fn get_url(&mut self) -> ClipResult<String> {
+        let contents = self.get_contents()?;
+        match uri::parse_url(&contents) {
+            Some(url) => Ok(url.into()),
+            None => Err(uri::Error::NoUrl.into()),
+        }
+    }
+
+    /// Get clipboard contents as a list of file paths, such as copied in a file manager.
+    ///
+    /// Handles `text/uri-list` contents with `file://` URIs and plain absolute paths. Errors with
+    /// [`uri::Error::NoFilePaths`] if the clipboard holds anything else. See
+    /// [`uri::parse_file_paths`] for the accepted forms.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::ClipboardContext;
+    ///
+    /// let mut ctx = ClipboardContext::new().unwrap();
+    /// for path in ctx.get_file_paths().unwrap() {
+    ///     println!("{}", path.display());
+    /// }
+    /// ```
+    fn get_file_paths(&mut self) -> ClipResult<Vec<std::path::PathBuf>> {
+        let contents = self.get_contents()?;
+        uri::parse_file_paths(&contents).ok_or_else(|| uri::Error::NoFilePaths.into())
+    }
+
+    /// Get clipboard contents of any supported kind.
+    ///
+    /// Platforms holding multiple representations, such as the native macOS and Windows
+    /// contexts, return the richest supported kind. Other contexts detect the kind of the textual
+    /// contents, see [`ClipboardContent::from_text`](content::ClipboardContent::from_text).
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::content::ClipboardContent;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::with_contents("some string");
+    /// match ctx.get_content().unwrap() {
+    ///     ClipboardContent::Text(text) => assert_eq!(text, "some string"),
+    ///     ClipboardContent::Files(paths) => println!("{} files", paths.len()),
+    ///     content => println!("{} contents", content.mime_type()),
+    /// }
+    /// ```
+    fn get_content(&mut self) -> ClipResult<content::ClipboardContent> {
+        Ok(content::ClipboardContent::from_text(self.get_contents()?))
+    }
+
+    /// Set clipboard contents of any supported kind.
+    ///
+    /// Contexts that only hold text set the textual representation, see
+    /// [`ClipboardContent::to_text`](content::ClipboardContent::to_text), and error with
+    /// [`content::Error::Unsupported`] for contents without one, such as images.
+    ///
+    /// ```rust
+    /// use std::path::PathBuf;
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::content::ClipboardContent;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let mut ctx = DryRunClipboardContext::new();
+    /// let files = ClipboardContent::Files(vec![PathBuf::from("/tmp/a.txt")]);
+    /// ctx.set_content(files.clone()).unwrap();
+    /// assert_eq!(ctx.get_content().unwrap(), files);
+    /// ```
+    fn set_content(&mut self, content: content::ClipboardContent) -> ClipResult<()> {
+        match content.to_text() {
+            Some(text) => self.set_contents(text),
+            None => Err(content::Error::Unsupported(content.mime_type().into()).into()),
+        }
+    }
+
+    /// Set clipboard contents, reading them from the given reader.
+    ///
+    /// Clipboard contexts invoking a binary stream the contents directly into the binary, without
+    /// holding all contents in memory. Other contexts read all contents into memory first, and
+    /// error if they aren't valid UTF-8.
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::ClipboardContext;
+    ///
+    /// let mut ctx = ClipboardContext::new().unwrap();
+    /// ctx.set_contents_from(&mut File::open("large.txt").unwrap()).unwrap();
+    /// ```
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> ClipResult<()> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        self.set_contents(contents)
+    }
+
+    /// Get clipboard contents, writing them into the given writer.
+    ///
+    /// Clipboard contexts invoking a binary stream the contents directly from the binary, without
+    /// holding all contents in memory. The contents are written as-is and are not validated to be
+    /// UTF-8 in that case. Other contexts get all contents into memory first.
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::ClipboardContext;
+    ///
+    /// let mut ctx = ClipboardContext::new().unwrap();
+    /// ctx.get_contents_to(&mut io::stdout()).unwrap();
+    /// ```
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> ClipResult<()> {
+        writer.write_all(self.get_contents()?.as_bytes())?;
+        Ok(())
     }
 }
 
+/// Extract the raw bytes from an invalid UTF-8 error returned by a clipboard context.
+///
+/// Returns the original error if it isn't an invalid UTF-8 error.
+#[cfg(feature = "clipboard")]
+fn invalid_utf8_bytes(err: Box<dyn Error + Send + Sync + 'static>) -> ClipResult<Vec<u8>> {
+    let err = match err.downcast::<std::string::FromUtf8Error>() {
+        Ok(err) => return Ok(err.into_bytes()),
+        Err(err) => err,
+    };
+    #[cfg(all(
+        feature = "x11-bin",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    let err = match err.downcast::<x11_bin::Error>() {
+        Ok(err) => match *err {
+            x11_bin::Error::NoUtf8(err) => return Ok(err.into_bytes()),
+            err => Box::new(err),
+        },
+        Err(err) => err,
+    };
+    #[cfg(all(
+        feature = "wayland-bin",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    let err = match err.downcast::<wayland_bin::Error>() {
+        Ok(err) => match *err {
+            wayland_bin::Error::NoUtf8(err) => return Ok(err.into_bytes()),
+            err => Box::new(err),
+        },
+        Err(err) => err,
+    };
+    #[cfg(all(feature = "wsl-bin", target_os = "linux"))]
+    let err = match err.downcast::<wsl_bin::Error>() {
+        Ok(err) => match *err {
+            wsl_bin::Error::NoUtf8(err) => return Ok(err.into_bytes()),
+            err => Box::new(err),
+        },
+        Err(err) => err,
+    };
+    #[cfg(all(feature = "bracketed-paste", unix))]
+    let err = match err.downcast::<bracketed_paste::Error>() {
+        Ok(err) => match *err {
+            bracketed_paste::Error::NoUtf8(err) => return Ok(err.into_bytes()),
+            err => Box::new(err),
+        },
+        Err(err) => err,
+    };
+    Err(err)
+}
+
+/// Clipboard context that can be constructed without arguments.
+///
+/// Implemented by all clipboard contexts in this crate, and by the `copypasta` contexts. This
+/// allows constructing contexts generically, such as in
+/// [`CombinedClipboardContext::new`](CombinedClipboardContext::new).
+#[cfg(feature = "clipboard")]
+pub trait ClipboardProviderNew: prelude::ClipboardProvider + Sized {
+    /// Construct a new clipboard context.
+    fn new() -> ClipResult<Self>;
+}
+
+/// Boxed contexts are contexts themselves, so they can be wrapped and passed on.
+///
+/// Box any context to pass it where a [`ClipboardProvider`](prelude::ClipboardProvider) is
+/// taken, or to select a context at runtime. A blanket implementation for any `Box<C>` is not
+/// possible, as `ClipboardProvider` is defined by `copypasta`.
+///
+/// ```rust
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::normalize::NormalizedClipboardContext;
+/// use copypasta_ext::testing::DryRunClipboardContext;
+///
+/// let context: Box<dyn ClipboardProviderExt> = Box::new(DryRunClipboardContext::new());
+/// let mut ctx = NormalizedClipboardContext::new(context);
+/// ctx.set_contents("some string".into()).unwrap();
+/// ```
+#[cfg(feature = "clipboard")]
+impl prelude::ClipboardProvider for Box<dyn ClipboardProviderExt + '_> {
+    fn get_contents(&mut self) -> ClipResult<String> {
+        self.as_mut().get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> ClipResult<()> {
+        self.as_mut().set_contents(contents)
+    }
+}
+
+#[cfg(feature = "clipboard")]
+impl ClipboardProviderExt for Box<dyn ClipboardProviderExt + '_> {
+    fn display_server(&self) -> Option<display::DisplayServer> {
+        self.as_ref().display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.as_ref().content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.as_ref().requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.as_ref().thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.as_ref().selection()
+    }
+
+    fn change_token(&mut self) -> ClipResult<ChangeToken> {
+        self.as_mut().change_token()
+    }
+
+    fn content_hash(&mut self) -> ClipResult<u64> {
+        self.as_mut().content_hash()
+    }
+
+    fn is_empty(&mut self) -> ClipResult<bool> {
+        self.as_mut().is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> ClipResult<Vec<String>> {
+        self.as_mut().available_mime_types()
+    }
+
+    fn persist(&mut self) -> ClipResult<()> {
+        self.as_mut().persist()
+    }
+
+    fn clear_contents(&mut self) -> ClipResult<()> {
+        self.as_mut().clear_contents()
+    }
+
+    fn swap_contents(&mut self, contents: String) -> ClipResult<String> {
+        self.as_mut().swap_contents(contents)
+    }
+
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> ClipResult<()> {
+        self.as_mut().set_contents_from(reader)
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> ClipResult<()> {
+        self.as_mut().get_contents_to(writer)
+    }
+
+    fn get_content(&mut self) -> ClipResult<content::ClipboardContent> {
+        self.as_mut().get_content()
+    }
+
+    fn set_content(&mut self, content: content::ClipboardContent) -> ClipResult<()> {
+        self.as_mut().set_content(content)
+    }
+}
+
+/// Borrowed contexts are contexts themselves, so a context can be passed on and used again after.
+///
+/// Useful to pass a context to functions or wrappers taking a context by value. A blanket
+/// implementation for any `&mut C` is not possible, as `ClipboardProvider` is defined by
+/// `copypasta`.
+///
+/// ```rust
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::testing::DryRunClipboardContext;
+///
+/// fn copy<C: ClipboardProvider>(mut ctx: C) {
+///     ctx.set_contents("some string".into()).unwrap();
+/// }
+///
+/// let mut ctx = DryRunClipboardContext::new();
+/// copy(&mut ctx as &mut dyn ClipboardProviderExt);
+/// assert_eq!(ctx.get_contents().unwrap(), "some string");
+/// ```
+#[cfg(feature = "clipboard")]
+impl prelude::ClipboardProvider for &mut dyn ClipboardProviderExt {
+    fn get_contents(&mut self) -> ClipResult<String> {
+        (**self).get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> ClipResult<()> {
+        (**self).set_contents(contents)
+    }
+}
+
+#[cfg(feature = "clipboard")]
+impl ClipboardProviderExt for &mut dyn ClipboardProviderExt {
+    fn display_server(&self) -> Option<display::DisplayServer> {
+        (**self).display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        (**self).content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        (**self).requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        (**self).thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        (**self).selection()
+    }
+
+    fn change_token(&mut self) -> ClipResult<ChangeToken> {
+        (**self).change_token()
+    }
+
+    fn content_hash(&mut self) -> ClipResult<u64> {
+        (**self).content_hash()
+    }
+
+    fn is_empty(&mut self) -> ClipResult<bool> {
+        (**self).is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> ClipResult<Vec<String>> {
+        (**self).available_mime_types()
+    }
+
+    fn persist(&mut self) -> ClipResult<()> {
+        (**self).persist()
+    }
+
+    fn clear_contents(&mut self) -> ClipResult<()> {
+        (**self).clear_contents()
+    }
+
+    fn swap_contents(&mut self, contents: String) -> ClipResult<String> {
+        (**self).swap_contents(contents)
+    }
+
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> ClipResult<()> {
+        (**self).set_contents_from(reader)
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> ClipResult<()> {
+        (**self).get_contents_to(writer)
+    }
+
+    fn get_content(&mut self) -> ClipResult<content::ClipboardContent> {
+        (**self).get_content()
+    }
+
+    fn set_content(&mut self, content: content::ClipboardContent) -> ClipResult<()> {
+        (**self).set_content(content)
+    }
+}
+
+#[cfg(feature = "clipboard")]
+impl ClipboardProviderNew for copypasta::nop_clipboard::NopClipboardContext {
+    fn new() -> ClipResult<Self> {
+        copypasta::nop_clipboard::NopClipboardContext::new()
+    }
+}
+
+#[cfg(feature = "clipboard")]
 impl ClipboardProviderExt for copypasta::nop_clipboard::NopClipboardContext {
     fn display_server(&self) -> Option<display::DisplayServer> {
         None
     }
 
-    fn has_bin_lifetime(&self) -> bool {
-        false
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
+    }
+}
+
+#[cfg(all(
+    feature = "x11",
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "ios",
+        target_os = "emscripten"
+    ))
+))]
+impl<S> ClipboardProviderNew for copypasta::x11_clipboard::X11ClipboardContext<S>
+where
+    S: copypasta::x11_clipboard::Selection,
+{
+    fn new() -> ClipResult<Self> {
+        copypasta::x11_clipboard::X11ClipboardContext::new()
     }
 }
 
-/// X11 clipboards have binary lifetime, not infinite.
+/// X11 clipboards have process lifetime, not infinite.
 #[cfg(all(
+    feature = "x11",
     unix,
     not(any(
         target_os = "macos",
@@ -183,13 +1379,29 @@ impl ClipboardProviderExt for copypasta::x11_clipboard::X11ClipboardContext {
         Some(display::DisplayServer::X11)
     }
 
-    fn has_bin_lifetime(&self) -> bool {
-        true
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Process
+    }
+
+    fn persist(&mut self) -> ClipResult<()> {
+        persist::x11(prelude::ClipboardProvider::get_contents(self)?)
+    }
+
+    #[cfg(feature = "x11-native")]
+    fn is_empty(&mut self) -> ClipResult<bool> {
+        Ok(x11_native::targets::<copypasta::x11_clipboard::Clipboard>()?.is_empty())
+    }
+
+    #[cfg(feature = "x11-native")]
+    fn available_mime_types(&mut self) -> ClipResult<Vec<String>> {
+        let targets = x11_native::targets::<copypasta::x11_clipboard::Clipboard>()?;
+        Ok(content::mime_types(targets.iter().map(|t| t.as_str())))
     }
 }
 
-/// Wayland clipboards have binary lifetime, not infinite.
+/// Wayland clipboards have process lifetime, not infinite.
 #[cfg(all(
+    feature = "wayland",
     unix,
     not(any(
         target_os = "macos",
@@ -203,29 +1415,105 @@ impl ClipboardProviderExt for copypasta::wayland_clipboard::Clipboard {
         Some(display::DisplayServer::Wayland)
     }
 
-    fn has_bin_lifetime(&self) -> bool {
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Process
+    }
+
+    /// Uses the Wayland connection of the application, and needs its input focus.
+    fn requires_event_loop(&self) -> bool {
         true
     }
+
+    fn persist(&mut self) -> ClipResult<()> {
+        persist::wayland(prelude::ClipboardProvider::get_contents(self)?)
+    }
+}
+
+#[cfg(all(feature = "clipboard", windows))]
+#[link(name = "user32")]
+extern "system" {
+    fn GetClipboardSequenceNumber() -> u32;
+    fn CountClipboardFormats() -> i32;
+}
+
+#[cfg(all(feature = "clipboard", windows))]
+impl ClipboardProviderNew for copypasta::windows_clipboard::WindowsClipboardContext {
+    fn new() -> ClipResult<Self> {
+        copypasta::windows_clipboard::WindowsClipboardContext::new()
+    }
 }
 
-#[cfg(windows)]
+#[cfg(all(feature = "clipboard", windows))]
 impl ClipboardProviderExt for copypasta::windows_clipboard::WindowsClipboardContext {
     fn display_server(&self) -> Option<display::DisplayServer> {
         Some(display::DisplayServer::Windows)
     }
 
-    fn has_bin_lifetime(&self) -> bool {
-        false
+    fn change_token(&mut self) -> ClipResult<ChangeToken> {
+        Ok(ChangeToken::from_sequence(
+            unsafe { GetClipboardSequenceNumber() }.into(),
+        ))
+    }
+
+    fn is_empty(&mut self) -> ClipResult<bool> {
+        Ok(unsafe { CountClipboardFormats() } == 0)
+    }
+
+    fn clear_contents(&mut self) -> ClipResult<()> {
+        windows_native::clear()
+    }
+
+    fn get_content(&mut self) -> ClipResult<content::ClipboardContent> {
+        windows_native::get_content(self)
+    }
+
+    fn set_content(&mut self, content: content::ClipboardContent) -> ClipResult<()> {
+        windows_native::WindowsClipboardProviderExt::set_items(self, &[content.to_item()])
+    }
+
+    fn available_mime_types(&mut self) -> ClipResult<Vec<String>> {
+        let formats = windows_native::formats()?;
+        Ok(content::mime_types(
+            formats.iter().filter_map(|format| format.mime_type()),
+        ))
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
     }
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(all(feature = "clipboard", target_os = "macos"))]
+impl ClipboardProviderNew for copypasta::osx_clipboard::OSXClipboardContext {
+    fn new() -> ClipResult<Self> {
+        copypasta::osx_clipboard::OSXClipboardContext::new()
+    }
+}
+
+#[cfg(all(feature = "clipboard", target_os = "macos"))]
 impl ClipboardProviderExt for copypasta::osx_clipboard::OSXClipboardContext {
     fn display_server(&self) -> Option<display::DisplayServer> {
         Some(display::DisplayServer::MacOs)
     }
 
-    fn has_bin_lifetime(&self) -> bool {
-        false
+    fn change_token(&mut self) -> ClipResult<ChangeToken> {
+        let change_count = macos_native::change_count()?;
+        Ok(ChangeToken::from_sequence(change_count as u64))
+    }
+
+    fn get_content(&mut self) -> ClipResult<content::ClipboardContent> {
+        macos_native::get_content(self)
+    }
+
+    fn set_content(&mut self, content: content::ClipboardContent) -> ClipResult<()> {
+        macos_native::MacOsClipboardProviderExt::set_items(self, &content.to_items())
+    }
+
+    fn clear_contents(&mut self) -> ClipResult<()> {
+        macos_native::clear()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
     }
 }
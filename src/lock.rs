@@ -0,0 +1,302 @@
+//! Coordinate setting clipboard contents between processes.
+//!
+//! When multiple instances of an application, or cooperating tools, copy in quick succession,
+//! their sets interleave. Contexts that spawn a binary or fork per set can then cause a burst of
+//! processes fighting over clipboard ownership, with unpredictable contents as result.
+//!
+//! [`LockedClipboardContext`] takes an advisory file lock around each set, so sets through any
+//! process using the same lock file run one at a time. Only processes using this lock are
+//! coordinated, others are not affected by it.
+//!
+//! The lock file is `copypasta-ext.lock` in `XDG_RUNTIME_DIR`, or a file for the current user in
+//! the temporary directory if not set. See [`default_path`].
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::lock::LockedClipboardContext;
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! let mut ctx = LockedClipboardContext::new(ClipboardContext::new().unwrap());
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+
+use std::env;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::content::ClipboardContent;
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ClipboardProviderNew, ContentLifetime, Selection, ThreadAffinity};
+
+/// Name of the lock file.
+const LOCK_FILE: &str = "copypasta-ext.lock";
+
+/// How long to wait for the lock by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Interval to retry taking the lock at while it's held by another process.
+const RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Take an inter-process lock around setting contents on the wrapped context.
+///
+/// Getting contents is not locked. If the lock isn't acquired within the timeout, setting fails
+/// with [`Error::Timeout`].
+///
+/// See module documentation for more information.
+pub struct LockedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    context: C,
+    path: PathBuf,
+    timeout: Duration,
+}
+
+impl<C> LockedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context, using the default lock file.
+    pub fn new(context: C) -> Self {
+        Self {
+            context,
+            path: default_path(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Use the given lock file.
+    ///
+    /// Only processes using the same lock file are coordinated. Use a file specific to your
+    /// application to not wait on unrelated tools.
+    pub fn with_path<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.path = path.into();
+        self
+    }
+
+    /// Wait at most the given duration for the lock, defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The used lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get a reference to the inner clipboard context.
+    pub fn inner(&self) -> &C {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+
+    /// Run the given operation on the inner context while holding the lock.
+    fn locked<T, F>(&mut self, op: F) -> crate::ClipResult<T>
+    where
+        F: FnOnce(&mut C) -> crate::ClipResult<T>,
+    {
+        let _lock = ClipboardLock::acquire(&self.path, self.timeout)?;
+        op(&mut self.context)
+    }
+}
+
+impl<C> ClipboardProvider for LockedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.context.get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.locked(|context| context.set_contents(contents))
+    }
+}
+
+impl<C> ClipboardProviderNew for LockedClipboardContext<C>
+where
+    C: ClipboardProviderNew,
+{
+    fn new() -> crate::ClipResult<Self> {
+        Ok(LockedClipboardContext::new(C::new()?))
+    }
+}
+
+impl<C> ClipboardProviderExt for LockedClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.context.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.context.change_token()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.context.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.context.available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.context.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.locked(|context| context.clear_contents())
+    }
+
+    fn swap_contents(&mut self, contents: String) -> crate::ClipResult<String> {
+        self.locked(|context| context.swap_contents(contents))
+    }
+
+    fn get_content(&mut self) -> crate::ClipResult<ClipboardContent> {
+        self.context.get_content()
+    }
+
+    fn set_content(&mut self, content: ClipboardContent) -> crate::ClipResult<()> {
+        self.locked(|context| context.set_content(content))
+    }
+}
+
+/// An acquired inter-process clipboard lock, released when dropped.
+///
+/// Hold it across multiple operations that must not be interleaved with sets from other
+/// processes.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use copypasta_ext::lock::{self, ClipboardLock};
+///
+/// let _lock = ClipboardLock::acquire(lock::default_path(), Duration::from_secs(5)).unwrap();
+/// // Get, transform and set contents
+/// ```
+#[derive(Debug)]
+pub struct ClipboardLock {
+    /// The locked file, closing it releases the lock.
+    _file: File,
+}
+
+impl ClipboardLock {
+    /// Acquire the lock on the given file, creating it if it doesn't exist.
+    ///
+    /// Waits at most the given duration while another process holds the lock.
+    pub fn acquire<P>(path: P, timeout: Duration) -> crate::ClipResult<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .open(path)
+            .map_err(Error::Open)?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+                return Ok(Self { _file: file });
+            }
+            let err = IoError::last_os_error();
+            match err.kind() {
+                IoErrorKind::WouldBlock | IoErrorKind::Interrupted => {}
+                _ => return Err(Error::Lock(err).into()),
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout.into());
+            }
+            thread::sleep(RETRY_INTERVAL);
+        }
+    }
+}
+
+/// The default lock file.
+///
+/// `copypasta-ext.lock` in `XDG_RUNTIME_DIR` if set, which is private to the user. Otherwise a
+/// file named after the user ID in the temporary directory.
+pub fn default_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join(LOCK_FILE),
+        _ => {
+            let uid = unsafe { libc::getuid() };
+            env::temp_dir().join(format!("copypasta-ext-{}.lock", uid))
+        }
+    }
+}
+
+/// Represents inter-process lock related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to open or create the lock file.
+    Open(IoError),
+
+    /// Failed to lock the lock file.
+    Lock(IoError),
+
+    /// Another process held the lock for longer than the timeout.
+    Timeout,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Open(err) => write!(f, "Failed to open clipboard lock file: {}", err),
+            Error::Lock(err) => write!(f, "Failed to take clipboard lock: {}", err),
+            Error::Timeout => write!(f, "Timed out waiting for clipboard lock"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Open(err) | Error::Lock(err) => Some(err),
+            _ => None,
+        }
+    }
+}
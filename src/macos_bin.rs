@@ -0,0 +1,297 @@
+//! Invokes [`pbcopy`][pbcopy]/[`pbpaste`][pbpaste] to access clipboard on macOS.
+//!
+//! This provider is a fallback for when the native pasteboard can't be accessed, such as from a
+//! daemon outside of the user's login session, or when sandboxing or privacy restrictions deny
+//! access. [`DisplayServer::try_context`](crate::display::DisplayServer::try_context) falls back
+//! to it automatically.
+//!
+//! Both binaries ship with macOS in `/usr/bin`, and are found through `PATH` or at that location.
+//!
+//! Use the provided `ClipboardContext` type alias to use this clipboard context on supported
+//! platforms, but fall back to the standard clipboard on others.
+//!
+//! ## Benefits
+//!
+//! - Works where the native pasteboard isn't accessible to the current process.
+//! - Keeps contents in clipboard even after your application exists.
+//!
+//! ## Drawbacks
+//!
+//! - Requires a login session of the user to reach its pasteboard.
+//! - Slow due to binary invocation.
+//! - Only supports plain text contents.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::macos_bin::MacOsBinClipboardContext;
+//!
+//! let mut ctx = MacOsBinClipboardContext::new().unwrap();
+//! println!("{:?}", ctx.get_contents());
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+//!
+//! [pbcopy]: https://ss64.com/mac/pbcopy.html
+//! [pbpaste]: https://ss64.com/mac/pbpaste.html
+
+use std::error::Error as StdError;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::string::FromUtf8Error;
+use std::sync::Arc;
+
+use crate::command::{BinaryLookup, CommandOptions, CommandRunner, SearchPath};
+use crate::config::ClipboardConfig;
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Default location of `pbcopy`.
+const PBCOPY_PATH: &str = "/usr/bin/pbcopy";
+
+/// Default location of `pbpaste`.
+const PBPASTE_PATH: &str = "/usr/bin/pbpaste";
+
+/// Locale to run the binaries with, they mangle non-ASCII text without a UTF-8 locale.
+const LOCALE: &str = "en_US.UTF-8";
+
+/// Platform specific context.
+///
+/// Alias for `MacOsBinClipboardContext` on supported platforms, aliases to standard
+/// `ClipboardContext` provided by `rust-clipboard` on other platforms.
+pub type ClipboardContext = MacOsBinClipboardContext;
+
+/// Invokes [`pbcopy`][pbcopy]/[`pbpaste`][pbpaste] to access clipboard on macOS.
+///
+/// See module documentation for more information.
+///
+/// [pbcopy]: https://ss64.com/mac/pbcopy.html
+/// [pbpaste]: https://ss64.com/mac/pbpaste.html
+pub struct MacOsBinClipboardContext {
+    /// Options for spawning clipboard binaries.
+    command: CommandOptions,
+}
+
+impl MacOsBinClipboardContext {
+    pub fn new() -> crate::ClipResult<Self> {
+        Ok(Self {
+            command: CommandOptions::default(),
+        })
+    }
+
+    /// Apply the options of the given configuration.
+    ///
+    /// Uses the configured search path and environment sanitizing. See [`ClipboardConfig`].
+    pub fn with_config(mut self, config: &ClipboardConfig) -> Self {
+        self.command = self.command.with_config(config);
+        self
+    }
+
+    /// Sanitize the environment of spawned clipboard binaries.
+    ///
+    /// See [`command::sanitize_env`](crate::command::sanitize_env).
+    pub fn with_sanitized_env(mut self) -> Self {
+        self.command.sanitize_env = true;
+        self
+    }
+
+    /// Spawn clipboard binaries through the given runner.
+    ///
+    /// See [`CommandRunner`].
+    pub fn with_command_runner<R>(mut self, runner: R) -> Self
+    where
+        R: CommandRunner + 'static,
+    {
+        self.command.runner = Arc::new(runner);
+        self
+    }
+
+    /// Append the given arguments to `pbpaste` invocations for getting contents.
+    ///
+    /// Gives access to options this crate doesn't model, such as `-pboard find`.
+    pub fn with_get_args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        self.command.get_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Append the given arguments to `pbcopy` invocations for setting contents.
+    ///
+    /// Gives access to options this crate doesn't model, such as `-pboard find`.
+    pub fn with_set_args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        self.command.set_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Find clipboard binaries in the given directories, instead of `PATH`.
+    ///
+    /// The default location in `/usr/bin` is still used as fallback. See [`SearchPath`].
+    pub fn with_search_path<I, P>(self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.with_binary_lookup(SearchPath::new(dirs))
+    }
+
+    /// Find clipboard binaries through the given lookup, instead of `PATH`.
+    ///
+    /// See [`BinaryLookup`].
+    pub fn with_binary_lookup<L>(mut self, lookup: L) -> Self
+    where
+        L: BinaryLookup + 'static,
+    {
+        self.command.lookup = Arc::new(lookup);
+        self
+    }
+
+    /// The `pbcopy` and `pbpaste` binaries, along with their resolved paths.
+    ///
+    /// A path is `None` if the binary could not be found. Useful to show in diagnostics.
+    pub fn binaries(&self) -> [(&'static str, Option<PathBuf>); 2] {
+        [
+            ("pbcopy", self.resolve_bin("pbcopy", PBCOPY_PATH)),
+            ("pbpaste", self.resolve_bin("pbpaste", PBPASTE_PATH)),
+        ]
+    }
+
+    /// Resolve the path of a binary through the lookup, falling back to the given default location.
+    fn resolve_bin(&self, bin: &str, default: &str) -> Option<PathBuf> {
+        self.command
+            .find(bin)
+            .or_else(|| Some(PathBuf::from(default)).filter(|path| path.exists()))
+    }
+
+    /// Build the command for the given binary, using its default location if not in `PATH`.
+    fn command(&self, bin: &'static str, default: &str) -> Command {
+        let path = self.resolve_bin(bin, default).unwrap_or_else(|| bin.into());
+        let mut cmd = Command::new(path);
+        self.command.prepare(&mut cmd);
+        cmd.env("LANG", LOCALE);
+        cmd
+    }
+}
+
+impl ClipboardProvider for MacOsBinClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        let mut cmd = self.command("pbpaste", PBPASTE_PATH);
+        cmd.args(&self.command.get_args).stderr(Stdio::null());
+
+        let mut output = Vec::new();
+        let status = self
+            .command
+            .runner
+            .run(&mut cmd, None, Some(&mut output))
+            .map_err(|err| map_io_err("pbpaste", err))?;
+        if !status.success() {
+            return Err(Error::BinaryStatus("pbpaste", status.code().unwrap_or(0)).into());
+        }
+
+        let contents = String::from_utf8(output).map_err(Error::NoUtf8)?;
+        debug!("macos-bin: got {}", crate::redact::Redacted(&contents));
+        Ok(contents)
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        debug!("macos-bin: setting {}", crate::redact::Redacted(&contents));
+        let mut cmd = self.command("pbcopy", PBCOPY_PATH);
+        cmd.args(&self.command.set_args);
+        let status = self
+            .command
+            .runner
+            .run(&mut cmd, Some(&mut contents.as_bytes()), None)
+            .map_err(|err| map_io_err("pbcopy", err))?;
+        if !status.success() {
+            return Err(Error::BinaryStatus("pbcopy", status.code().unwrap_or(0)).into());
+        }
+        Ok(())
+    }
+}
+
+impl ClipboardProviderNew for MacOsBinClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        MacOsBinClipboardContext::new()
+    }
+}
+
+impl ClipboardProviderExt for MacOsBinClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        Some(DisplayServer::MacOs)
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
+    }
+}
+
+/// Map an error spawning or communicating with a binary.
+fn map_io_err(bin: &'static str, err: IoError) -> Error {
+    match err.kind() {
+        IoErrorKind::NotFound => Error::NoBinary,
+        _ => Error::BinaryIo(bin, err),
+    }
+}
+
+/// Represents macOS binary related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The `pbcopy` or `pbpaste` binary could not be found, required for clipboard support.
+    NoBinary,
+
+    /// An error occurred while using `pbcopy` or `pbpaste` to manage the clipboard contents.
+    /// This problem probably occurred when starting, or while piping the clipboard contents
+    /// from/to the process.
+    BinaryIo(&'static str, IoError),
+
+    /// `pbcopy` or `pbpaste` unexpectetly exited with a non-successful status code.
+    BinaryStatus(&'static str, i32),
+
+    /// The clipboard contents could not be parsed as valid UTF-8.
+    NoUtf8(FromUtf8Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoBinary => write!(
+                f,
+                "Could not find pbcopy or pbpaste binary for clipboard support"
+            ),
+            Error::BinaryIo(cmd, err) => {
+                write!(f, "Failed to access clipboard using {}: {}", cmd, err)
+            }
+            Error::BinaryStatus(cmd, code) => write!(
+                f,
+                "Failed to use clipboard, {} exited with status code {}",
+                cmd, code
+            ),
+            Error::NoUtf8(err) => write!(
+                f,
+                "Failed to parse clipboard contents as valid UTF-8: {}",
+                err
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::BinaryIo(_, err) => Some(err),
+            Error::NoUtf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! macOS specific clipboard operations, through `NSPasteboard` directly.
+//!
+//! Provides operations not available through the clipboard provider interface, such as setting
+//! rich RTF contents and multiple items. Use [`MacOsClipboardProviderExt`] on the native macOS clipboard context.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::copypasta::osx_clipboard::OSXClipboardContext;
+//! use copypasta_ext::macos_native::MacOsClipboardProviderExt;
+//!
+//! let mut ctx = OSXClipboardContext::new().unwrap();
+//! ctx.set_rtf(r"{\rtf1\ansi {\b some string}}", "some string").unwrap();
+//! ```
+
+use std::error::Error as StdError;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_void};
+
+use copypasta::osx_clipboard::OSXClipboardContext;
+use objc::runtime::{Class, Object, Sel, BOOL, NO};
+use objc::Message;
+
+use crate::content::{ClipboardContent, ClipboardItem};
+
+/// Pasteboard type for RTF contents.
+const TYPE_RTF: &str = "public.rtf";
+
+/// Pasteboard type for plain text contents.
+const TYPE_TEXT: &str = "public.utf8-plain-text";
+
+/// `NSUTF8StringEncoding`.
+const UTF8_ENCODING: usize = 4;
+
+/// macOS specific clipboard operations.
+///
+/// Implemented for the native macOS clipboard context.
+pub trait MacOsClipboardProviderExt {
+    /// Set RTF contents, along with a plain text alternative.
+    ///
+    /// The RTF document is put on the pasteboard as `public.rtf`, accepted by word processors
+    /// such as Pages and TextEdit. The plain text alternative is set for applications not
+    /// accepting RTF.
+    fn set_rtf(&mut self, rtf: &str, alt_text: &str) -> crate::ClipResult<()>;
+
+    /// Get RTF contents.
+    ///
+    /// Returns `None` if the pasteboard doesn't hold RTF contents.
+    fn get_rtf(&mut self) -> crate::ClipResult<Option<String>>;
+
+    /// Set the given items, each in all of its representations.
+    ///
+    /// Each item is put on the pasteboard as separate `NSPasteboardItem`, such as when copying
+    /// multiple files in Finder. Representations are mapped to pasteboard types by MIME type,
+    /// such as `text/plain` as `public.utf8-plain-text` and `image/png` as `public.png`. Other
+    /// MIME types are used as pasteboard type as-is.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::content::ClipboardItem;
+    /// use copypasta_ext::copypasta::osx_clipboard::OSXClipboardContext;
+    /// use copypasta_ext::macos_native::MacOsClipboardProviderExt;
+    ///
+    /// let items = vec![
+    ///     ClipboardItem::new().with_text("first"),
+    ///     ClipboardItem::new().with_text("second"),
+    /// ];
+    /// let mut ctx = OSXClipboardContext::new().unwrap();
+    /// ctx.set_items(&items).unwrap();
+    /// ```
+    fn set_items(&mut self, items: &[ClipboardItem]) -> crate::ClipResult<()>;
+
+    /// Get all pasteboard items.
+    ///
+    /// Each item holds the representations with a known MIME type, pasteboard types that don't
+    /// map to a MIME type are skipped.
+    fn get_items(&mut self) -> crate::ClipResult<Vec<ClipboardItem>>;
+}
+
+impl MacOsClipboardProviderExt for OSXClipboardContext {
+    fn set_rtf(&mut self, rtf: &str, alt_text: &str) -> crate::ClipResult<()> {
+        let pasteboard = general_pasteboard()?;
+        unsafe {
+            let _: isize = (*pasteboard).send_message(Sel::register("clearContents"), ())?;
+            set_string(pasteboard, rtf, TYPE_RTF)?;
+            set_string(pasteboard, alt_text, TYPE_TEXT)?;
+        }
+        Ok(())
+    }
+
+    fn get_rtf(&mut self) -> crate::ClipResult<Option<String>> {
+        let pasteboard = general_pasteboard()?;
+        unsafe { get_string(pasteboard, TYPE_RTF) }
+    }
+
+    fn set_items(&mut self, items: &[ClipboardItem]) -> crate::ClipResult<()> {
+        let pasteboard = general_pasteboard()?;
+        unsafe {
+            let objects = new_object("NSMutableArray")?;
+            let result = add_items(objects, items).and_then(|_| {
+                let _: isize = (*pasteboard).send_message(Sel::register("clearContents"), ())?;
+                let written: BOOL =
+                    (*pasteboard).send_message(Sel::register("writeObjects:"), (objects,))?;
+                if written == NO {
+                    return Err(Error::Set.into());
+                }
+                Ok(())
+            });
+            release(objects);
+            result
+        }
+    }
+
+    fn get_items(&mut self) -> crate::ClipResult<Vec<ClipboardItem>> {
+        let pasteboard = general_pasteboard()?;
+        unsafe {
+            let objects: *mut Object =
+                (*pasteboard).send_message(Sel::register("pasteboardItems"), ())?;
+            if objects.is_null() {
+                return Ok(vec![]);
+            }
+            let mut items = Vec::new();
+            for object in ns_array(objects)? {
+                items.push(get_item(object)?);
+            }
+            Ok(items)
+        }
+    }
+}
+
+/// Create `NSPasteboardItem` objects for the given items, and add them to the given array.
+unsafe fn add_items(objects: *mut Object, items: &[ClipboardItem]) -> crate::ClipResult<()> {
+    for item in items {
+        let object = new_object("NSPasteboardItem")?;
+        let result = set_item(object, item).and_then(|_| {
+            let _: () = (*objects).send_message(Sel::register("addObject:"), (object,))?;
+            Ok(())
+        });
+        release(object);
+        result?;
+    }
+    Ok(())
+}
+
+/// Set all representations of the given item on a `NSPasteboardItem`.
+unsafe fn set_item(object: *mut Object, item: &ClipboardItem) -> crate::ClipResult<()> {
+    for (mime_type, data) in item.representations() {
+        let kind = ns_string(mime_type_to_kind(mime_type))?;
+        let data = match ns_data(data) {
+            Ok(data) => data,
+            Err(err) => {
+                release(kind);
+                return Err(err);
+            }
+        };
+        let result: Result<BOOL, _> =
+            (*object).send_message(Sel::register("setData:forType:"), (data, kind));
+        release(data);
+        release(kind);
+        if result? == NO {
+            return Err(Error::Set.into());
+        }
+    }
+    Ok(())
+}
+
+/// Get all representations with a known MIME type from a `NSPasteboardItem`.
+unsafe fn get_item(object: *mut Object) -> crate::ClipResult<ClipboardItem> {
+    let mut item = ClipboardItem::new();
+    let kinds: *mut Object = (*object).send_message(Sel::register("types"), ())?;
+    if kinds.is_null() {
+        return Ok(item);
+    }
+    for kind in ns_array(kinds)? {
+        let mime_type = match from_ns_string(kind)?.as_deref().and_then(kind_to_mime_type) {
+            Some(mime_type) => mime_type,
+            None => continue,
+        };
+        if item.get(&mime_type).is_some() {
+            continue;
+        }
+        let data: *mut Object = (*object).send_message(Sel::register("dataForType:"), (kind,))?;
+        if data.is_null() {
+            continue;
+        }
+        let len: usize = (*data).send_message(Sel::register("length"), ())?;
+        let bytes: *const c_void = (*data).send_message(Sel::register("bytes"), ())?;
+        let data = if bytes.is_null() || len == 0 {
+            vec![]
+        } else {
+            std::slice::from_raw_parts(bytes as *const u8, len).to_vec()
+        };
+        item = item.with_representation(&mime_type, data);
+    }
+    Ok(item)
+}
+
+/// Pasteboard types for MIME types, with a distinct name.
+const KINDS: &[(&str, &str)] = &[
+    ("text/plain", TYPE_TEXT),
+    ("text/html", "public.html"),
+    ("text/rtf", TYPE_RTF),
+    ("text/uri-list", "public.file-url"),
+    ("image/png", "public.png"),
+    ("image/jpeg", "public.jpeg"),
+    ("image/tiff", "public.tiff"),
+    ("application/pdf", "com.adobe.pdf"),
+];
+
+/// Get the pasteboard type for the given MIME type.
+fn mime_type_to_kind(mime_type: &str) -> &str {
+    KINDS
+        .iter()
+        .find(|(m, _)| *m == mime_type)
+        .map_or(mime_type, |(_, kind)| kind)
+}
+
+/// Get the MIME type for the given pasteboard type, `None` if unknown.
+fn kind_to_mime_type(kind: &str) -> Option<String> {
+    match KINDS.iter().find(|(_, k)| *k == kind) {
+        Some((mime_type, _)) => Some((*mime_type).into()),
+        None if kind.contains('/') => Some(kind.into()),
+        None => None,
+    }
+}
+
+/// Get clipboard contents of any supported kind, from all items on the pasteboard.
+pub(crate) fn get_content<C>(context: &mut C) -> crate::ClipResult<ClipboardContent>
+where
+    C: MacOsClipboardProviderExt,
+{
+    let items = context.get_items()?;
+    Ok(ClipboardContent::from_items(&items).unwrap_or_else(|| String::new().into()))
+}
+
+/// Clear the pasteboard, see
+/// [`ClipboardProviderExt::clear_contents`](crate::ClipboardProviderExt::clear_contents).
+pub(crate) fn clear() -> crate::ClipResult<()> {
+    let pasteboard = general_pasteboard()?;
+    let _: isize = unsafe { (*pasteboard).send_message(Sel::register("clearContents"), ())? };
+    Ok(())
+}
+
+/// Get the pasteboard change count, which increases each time the pasteboard contents change.
+pub(crate) fn change_count() -> crate::ClipResult<isize> {
+    let pasteboard = general_pasteboard()?;
+    Ok(unsafe { (*pasteboard).send_message(Sel::register("changeCount"), ())? })
+}
+
+/// Get the general pasteboard.
+fn general_pasteboard() -> crate::ClipResult<*mut Object> {
+    let class = Class::get("NSPasteboard").ok_or(Error::Class("NSPasteboard"))?;
+    let pasteboard: *mut Object =
+        unsafe { class.send_message(Sel::register("generalPasteboard"), ())? };
+    if pasteboard.is_null() {
+        return Err(Error::Pasteboard.into());
+    }
+    Ok(pasteboard)
+}
+
+/// Set a string on the pasteboard for the given type.
+unsafe fn set_string(pasteboard: *mut Object, value: &str, kind: &str) -> crate::ClipResult<()> {
+    let value = ns_string(value)?;
+    let kind = ns_string(kind)?;
+    let result: Result<BOOL, _> =
+        (*pasteboard).send_message(Sel::register("setString:forType:"), (value, kind));
+    release(value);
+    release(kind);
+    if result? == NO {
+        return Err(Error::Set.into());
+    }
+    Ok(())
+}
+
+/// Get a string from the pasteboard for the given type, `None` if not available.
+unsafe fn get_string(pasteboard: *mut Object, kind: &str) -> crate::ClipResult<Option<String>> {
+    let kind = ns_string(kind)?;
+    let result: Result<*mut Object, _> =
+        (*pasteboard).send_message(Sel::register("stringForType:"), (kind,));
+    release(kind);
+    from_ns_string(result?)
+}
+
+/// Copy the given `NSString` into a string, `None` if null.
+unsafe fn from_ns_string(value: *mut Object) -> crate::ClipResult<Option<String>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let chars: *const c_char = (*value).send_message(Sel::register("UTF8String"), ())?;
+    if chars.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(CStr::from_ptr(chars).to_string_lossy().into_owned()))
+}
+
+/// Create an owned `NSData` holding a copy of the given bytes, release it with [`release`].
+unsafe fn ns_data(bytes: &[u8]) -> crate::ClipResult<*mut Object> {
+    let class = Class::get("NSData").ok_or(Error::Class("NSData"))?;
+    let data: *mut Object = class.send_message(Sel::register("alloc"), ())?;
+    let data: *mut Object = (*data).send_message(
+        Sel::register("initWithBytes:length:"),
+        (bytes.as_ptr() as *const c_void, bytes.len()),
+    )?;
+    if data.is_null() {
+        return Err(Error::Data.into());
+    }
+    Ok(data)
+}
+
+/// Create an owned instance of the given class, release it with [`release`].
+unsafe fn new_object(class: &'static str) -> crate::ClipResult<*mut Object> {
+    let object: *mut Object = Class::get(class)
+        .ok_or(Error::Class(class))?
+        .send_message(Sel::register("new"), ())?;
+    if object.is_null() {
+        return Err(Error::Class(class).into());
+    }
+    Ok(object)
+}
+
+/// Collect the objects in the given `NSArray`.
+unsafe fn ns_array(array: *mut Object) -> crate::ClipResult<Vec<*mut Object>> {
+    let count: usize = (*array).send_message(Sel::register("count"), ())?;
+    let mut objects = Vec::with_capacity(count);
+    for i in 0..count {
+        objects.push((*array).send_message(Sel::register("objectAtIndex:"), (i,))?);
+    }
+    Ok(objects)
+}
+
+/// Create an owned `NSString` from the given string, release it with [`release`].
+unsafe fn ns_string(value: &str) -> crate::ClipResult<*mut Object> {
+    let class = Class::get("NSString").ok_or(Error::Class("NSString"))?;
+    let string: *mut Object = class.send_message(Sel::register("alloc"), ())?;
+    let string: *mut Object = (*string).send_message(
+        Sel::register("initWithBytes:length:encoding:"),
+        (value.as_ptr() as *const c_void, value.len(), UTF8_ENCODING),
+    )?;
+    if string.is_null() {
+        return Err(Error::String.into());
+    }
+    Ok(string)
+}
+
+/// Release an owned object.
+unsafe fn release(object: *mut Object) {
+    let _: Result<(), _> = (*object).send_message(Sel::register("release"), ());
+}
+
+/// Represents macOS native clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An Objective-C class could not be found.
+    Class(&'static str),
+
+    /// Failed to get the general pasteboard.
+    Pasteboard,
+
+    /// Failed to create a string to pass to the pasteboard.
+    String,
+
+    /// Failed to create data to pass to the pasteboard.
+    Data,
+
+    /// The pasteboard refused to set contents.
+    Set,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Class(class) => write!(f, "Objective-C class '{}' not found", class),
+            Error::Pasteboard => write!(f, "Failed to get general pasteboard"),
+            Error::String => write!(f, "Failed to create string for pasteboard"),
+            Error::Data => write!(f, "Failed to create data for pasteboard"),
+            Error::Set => write!(f, "Failed to set pasteboard contents"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
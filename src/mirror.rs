@@ -0,0 +1,200 @@
+//! Mirror clipboard contents between two clipboard contexts.
+//!
+//! A [`ClipboardMirror`] watches two clipboard contexts, and copies changed contents from one to
+//! the other. This can be used to keep the X11 `PRIMARY` selection and `CLIPBOARD` in sync, like
+//! [`autocutsel`][autocutsel] does.
+//!
+//! Contents set by the mirror itself are not mirrored back, to prevent loops.
+//!
+//! # Examples
+//!
+//! Mirror `PRIMARY` into `CLIPBOARD` on X11:
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use copypasta_ext::copypasta::x11_clipboard::{Clipboard, Primary, X11ClipboardContext};
+//! use copypasta_ext::mirror::{ClipboardMirror, Direction};
+//!
+//! let primary: X11ClipboardContext<Primary> = X11ClipboardContext::new().unwrap();
+//! let clipboard: X11ClipboardContext<Clipboard> = X11ClipboardContext::new().unwrap();
+//! let mirror = ClipboardMirror::new(
+//!     primary,
+//!     clipboard,
+//!     Direction::LeftToRight,
+//!     Duration::from_millis(250),
+//! );
+//!
+//! // Mirror until dropped
+//! std::thread::park();
+//! # drop(mirror);
+//! ```
+//!
+//! Bridge the X11 and Wayland clipboards in mixed sessions running X11 applications through
+//! XWayland, so contents copied in either can be pasted in the other:
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use copypasta_ext::mirror::ClipboardMirror;
+//!
+//! let bridge = ClipboardMirror::xwayland(Duration::from_millis(500)).unwrap();
+//! # drop(bridge);
+//! ```
+//!
+//! [autocutsel]: https://github.com/sigmike/autocutsel
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::prelude::*;
+use crate::watch::ChangeDetector;
+
+/// Direction to mirror clipboard contents in.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Direction {
+    /// Mirror changes in the left context into the right context.
+    LeftToRight,
+
+    /// Mirror changes in the right context into the left context.
+    RightToLeft,
+
+    /// Mirror changes in either context into the other.
+    ///
+    /// If both change at the same time, the left context wins.
+    Both,
+}
+
+impl Direction {
+    fn left_to_right(self) -> bool {
+        matches!(self, Direction::LeftToRight | Direction::Both)
+    }
+
+    fn right_to_left(self) -> bool {
+        matches!(self, Direction::RightToLeft | Direction::Both)
+    }
+}
+
+/// Mirror clipboard contents between two clipboard contexts.
+///
+/// Both contexts are polled for changes on a background thread. The thread is stopped when the
+/// mirror is dropped.
+///
+/// See module documentation for more information.
+pub struct ClipboardMirror {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ClipboardMirror {
+    /// Start mirroring between the given clipboard contexts, polling at the given interval.
+    pub fn new<L, R>(left: L, right: R, direction: Direction, interval: Duration) -> Self
+    where
+        L: ClipboardProvider + 'static,
+        R: ClipboardProvider + 'static,
+    {
+        let (stop, stop_rx) = mpsc::channel();
+
+        let thread = thread::spawn(move || {
+            let (mut left, mut right) = (left, right);
+            let mut left_detector = ChangeDetector::new(&mut left);
+            let mut right_detector = ChangeDetector::new(&mut right);
+            // Poll each interval, until stopped or disconnected
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                if direction.left_to_right() {
+                    if let Some(contents) = left_detector.poll(&mut left) {
+                        mirror(&mut right, &mut right_detector, contents);
+                        continue;
+                    }
+                }
+                if direction.right_to_left() {
+                    if let Some(contents) = right_detector.poll(&mut right) {
+                        mirror(&mut left, &mut left_detector, contents);
+                    }
+                }
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Start bridging the X11 and Wayland clipboard, polling at the given interval.
+    ///
+    /// Useful in Wayland sessions running X11 applications through XWayland, where the
+    /// compositor doesn't synchronize both clipboards itself. Changes in either clipboard are
+    /// mirrored into the other.
+    ///
+    /// The X11 clipboard is accessed natively through the XWayland server, the Wayland clipboard
+    /// through [`WaylandBinClipboardContext`](crate::wayland_bin::WaylandBinClipboardContext).
+    ///
+    /// Errors if not running in a Wayland session with XWayland available, see
+    /// [`display::is_xwayland`](crate::display::is_xwayland).
+    #[cfg(all(
+        feature = "x11",
+        feature = "wayland-bin",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    pub fn xwayland(interval: Duration) -> crate::ClipResult<Self> {
+        use copypasta::x11_clipboard::X11ClipboardContext;
+
+        if !crate::display::is_xwayland() {
+            return Err(Error::NoXWayland.into());
+        }
+
+        let x11: X11ClipboardContext = X11ClipboardContext::new()?;
+        let wayland = crate::wayland_bin::WaylandBinClipboardContext::new()?;
+        Ok(Self::new(x11, wayland, Direction::Both, interval))
+    }
+}
+
+impl Drop for ClipboardMirror {
+    fn drop(&mut self) {
+        // Dropping the stop sender disconnects, making the thread exit
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Set contents on the target context, marking them as seen to prevent mirroring them back.
+fn mirror(target: &mut dyn ClipboardProvider, detector: &mut ChangeDetector, contents: String) {
+    if target.set_contents(contents.clone()).is_ok() {
+        detector.assume(contents);
+    }
+}
+
+/// Represents clipboard mirror related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Not running in a Wayland session with XWayland available.
+    NoXWayland,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoXWayland => write!(
+                f,
+                "Cannot bridge clipboards, not in Wayland session with XWayland available"
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
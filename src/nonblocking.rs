@@ -0,0 +1,213 @@
+//! Get and set clipboard contents without blocking the calling thread.
+//!
+//! Clipboard operations may block for a long time, such as when an X11 selection owner is slow
+//! to respond or when a clipboard binary hangs. [`NonBlockingClipboardContext`] runs the wrapped
+//! context on a worker thread, so single-threaded applications such as terminal user interfaces
+//! can keep rendering while an operation is in progress.
+//!
+//! Operations either return a [`Pending`] handle to poll, or are polled through
+//! [`try_get_contents`](NonBlockingClipboardContext::try_get_contents) and
+//! [`try_set_contents`](NonBlockingClipboardContext::try_set_contents), which fail with
+//! [`Error::WouldBlock`] while in progress.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use copypasta_ext::nonblocking::{self, NonBlockingClipboardContext};
+//! use copypasta_ext::testing::DryRunClipboardContext;
+//!
+//! let context = DryRunClipboardContext::with_contents("some string");
+//! let mut ctx = NonBlockingClipboardContext::new(context).unwrap();
+//!
+//! // Poll once per frame, render in between
+//! let contents = loop {
+//!     match ctx.try_get_contents() {
+//!         Err(err) if nonblocking::is_would_block(err.as_ref()) => { /* render */ }
+//!         result => break result.unwrap(),
+//!     }
+//! };
+//! assert_eq!(contents, "some string");
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use crate::prelude::*;
+
+/// Result of an operation on the worker thread.
+type OperationResult<T> = crate::ClipResult<T>;
+
+/// An operation to run on the worker thread, along with where to send its result.
+enum Request {
+    Get(Sender<OperationResult<String>>),
+    Set(String, Sender<OperationResult<()>>),
+}
+
+/// Run a clipboard context on a worker thread, to get and set contents without blocking.
+///
+/// Operations are run in order on the worker thread. The worker thread stops when this context
+/// is dropped, after finishing queued operations.
+///
+/// Also implements [`ClipboardProvider`], blocking until the operation finishes.
+///
+/// See module documentation for more information.
+pub struct NonBlockingClipboardContext {
+    requests: Sender<Request>,
+    get: Option<Pending<String>>,
+    set: Option<(String, Pending<()>)>,
+}
+
+impl NonBlockingClipboardContext {
+    /// Wrap the given clipboard context, moving it to a new worker thread.
+    pub fn new<C>(context: C) -> crate::ClipResult<Self>
+    where
+        C: ClipboardProvider + 'static,
+    {
+        let (requests, request_rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("copypasta-ext-nonblocking".into())
+            .spawn(move || serve(context, request_rx))?;
+        Ok(Self {
+            requests,
+            get: None,
+            set: None,
+        })
+    }
+
+    /// Start getting the contents, returning a handle to poll for the result.
+    pub fn get_contents_pending(&self) -> Pending<String> {
+        let (result_tx, result) = mpsc::channel();
+        let _ = self.requests.send(Request::Get(result_tx));
+        Pending(result)
+    }
+
+    /// Start setting the given contents, returning a handle to poll for the result.
+    pub fn set_contents_pending(&self, contents: String) -> Pending<()> {
+        let (result_tx, result) = mpsc::channel();
+        let _ = self.requests.send(Request::Set(contents, result_tx));
+        Pending(result)
+    }
+
+    /// Get the contents, without blocking.
+    ///
+    /// Starts getting the contents on the first call, and fails with [`Error::WouldBlock`] until
+    /// they are available. Call again, such as on the next frame, to poll.
+    pub fn try_get_contents(&mut self) -> crate::ClipResult<String> {
+        if self.get.is_none() {
+            self.get = Some(self.get_contents_pending());
+        }
+        let result = self
+            .get
+            .as_mut()
+            .and_then(Pending::try_result)
+            .ok_or(Error::WouldBlock)?;
+        self.get = None;
+        result
+    }
+
+    /// Set the given contents, without blocking.
+    ///
+    /// Starts setting the contents on the first call, and fails with [`Error::WouldBlock`] until
+    /// they are set. Call again with the same contents to poll. If other contents are still being
+    /// set, waits for that to finish before setting the given contents.
+    pub fn try_set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        if let Some((pending_contents, pending)) = &mut self.set {
+            let result = pending.try_result().ok_or(Error::WouldBlock)?;
+            let same = *pending_contents == contents;
+            self.set = None;
+            if same {
+                return result;
+            }
+        }
+
+        let pending = self.set_contents_pending(contents.clone());
+        self.set = Some((contents, pending));
+        Err(Error::WouldBlock.into())
+    }
+}
+
+impl ClipboardProvider for NonBlockingClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.get_contents_pending().wait()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.set_contents_pending(contents).wait()
+    }
+}
+
+/// Serve requests on the given context, until the sender is dropped.
+fn serve<C>(mut context: C, requests: Receiver<Request>)
+where
+    C: ClipboardProvider,
+{
+    for request in requests {
+        // The requester may have stopped waiting for the result
+        match request {
+            Request::Get(result) => {
+                let _ = result.send(context.get_contents());
+            }
+            Request::Set(contents, result) => {
+                let _ = result.send(context.set_contents(contents));
+            }
+        }
+    }
+}
+
+/// Handle to the result of an operation in progress.
+///
+/// Dropping the handle doesn't cancel the operation.
+pub struct Pending<T>(Receiver<OperationResult<T>>);
+
+impl<T> Pending<T> {
+    /// Get the result if the operation finished, without blocking.
+    ///
+    /// Returns `None` while the operation is in progress. The result is only returned once,
+    /// later calls fail with [`Error::WorkerGone`].
+    pub fn try_result(&mut self) -> Option<crate::ClipResult<T>> {
+        match self.0.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(Error::WorkerGone.into())),
+        }
+    }
+
+    /// Wait for the operation to finish, and get its result.
+    pub fn wait(self) -> crate::ClipResult<T> {
+        self.0.recv().map_err(|_| Error::WorkerGone)?
+    }
+}
+
+/// Check whether the given error is [`Error::WouldBlock`].
+pub fn is_would_block(err: &(dyn StdError + 'static)) -> bool {
+    matches!(err.downcast_ref::<Error>(), Some(Error::WouldBlock))
+}
+
+/// Represents non-blocking clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The operation is in progress, poll again later.
+    WouldBlock,
+
+    /// The worker thread is gone, such as after a panic in the clipboard context, or the result
+    /// was already taken.
+    WorkerGone,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::WouldBlock => write!(f, "Clipboard operation is in progress"),
+            Error::WorkerGone => write!(f, "Clipboard worker thread is gone"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,233 @@
+//! Normalize clipboard contents when getting or setting.
+//!
+//! Clipboard contents may not be in the form your application expects. Text copied on Windows
+//! uses CRLF line endings for example, leaving stray carriage returns when pasted in Unix tools.
+//! Content copied from some Windows applications starts with a byte order mark, or contains
+//! invisible zero-width characters. [`NormalizedClipboardContext`] wraps any clipboard context
+//! and normalizes contents passing through it.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::normalize::{LineEnding, NormalizedClipboardContext};
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! let mut ctx = NormalizedClipboardContext::new(ClipboardContext::new().unwrap())
+//!     .with_get_line_ending(LineEnding::Lf)
+//!     .with_strip_bom();
+//! println!("{:?}", ctx.get_contents());
+//! ```
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ClipboardProviderNew, ContentLifetime, Selection, ThreadAffinity};
+
+/// Line ending style.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum LineEnding {
+    /// Unix style line feed, `\n`.
+    Lf,
+
+    /// Windows style carriage return and line feed, `\r\n`.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Convert all line endings in the given text to this style.
+    ///
+    /// ```rust
+    /// use copypasta_ext::normalize::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::Lf.apply("a\r\nb\nc"), "a\nb\nc");
+    /// assert_eq!(LineEnding::CrLf.apply("a\r\nb\nc"), "a\r\nb\r\nc");
+    /// ```
+    pub fn apply(self, text: &str) -> String {
+        let text = text.replace("\r\n", "\n");
+        match self {
+            LineEnding::Lf => text,
+            LineEnding::CrLf => text.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// Zero-width characters stripped from contents, see
+/// [`with_strip_invisible`](NormalizedClipboardContext::with_strip_invisible).
+const INVISIBLE: &[char] = &[
+    '\u{200B}', // zero width space
+    '\u{200C}', // zero width non-joiner
+    '\u{200D}', // zero width joiner
+    '\u{2060}', // word joiner
+    '\u{FEFF}', // zero width no-break space, byte order mark
+];
+
+/// Normalize clipboard contents of the wrapped context.
+///
+/// Without any options set, contents are passed through unchanged.
+///
+/// See module documentation for more information.
+pub struct NormalizedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    context: C,
+    get_line_ending: Option<LineEnding>,
+    set_line_ending: Option<LineEnding>,
+    strip_bom: bool,
+    strip_invisible: bool,
+}
+
+impl<C> NormalizedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context.
+    pub fn new(context: C) -> Self {
+        Self {
+            context,
+            get_line_ending: None,
+            set_line_ending: None,
+            strip_bom: false,
+            strip_invisible: false,
+        }
+    }
+
+    /// Convert line endings of contents to the given style when getting.
+    pub fn with_get_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.get_line_ending = Some(line_ending);
+        self
+    }
+
+    /// Convert line endings of contents to the given style when setting.
+    pub fn with_set_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.set_line_ending = Some(line_ending);
+        self
+    }
+
+    /// Strip a leading byte order mark from contents when getting.
+    ///
+    /// ```rust
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::normalize::NormalizedClipboardContext;
+    /// use copypasta_ext::testing::DryRunClipboardContext;
+    ///
+    /// let ctx = DryRunClipboardContext::with_contents("\u{FEFF}some string");
+    /// let mut ctx = NormalizedClipboardContext::new(ctx).with_strip_bom();
+    /// assert_eq!(ctx.get_contents().unwrap(), "some string");
+    /// ```
+    pub fn with_strip_bom(mut self) -> Self {
+        self.strip_bom = true;
+        self
+    }
+
+    /// Strip invisible zero-width characters from contents when getting.
+    ///
+    /// Strips zero width spaces, (non-)joiners, word joiners and byte order marks anywhere in the
+    /// contents. Note that this breaks emoji sequences relying on the zero width joiner.
+    pub fn with_strip_invisible(mut self) -> Self {
+        self.strip_invisible = true;
+        self
+    }
+
+    /// Get a reference to the inner clipboard context.
+    pub fn inner(&self) -> &C {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+}
+
+impl<C> ClipboardProvider for NormalizedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        let mut contents = self.context.get_contents()?;
+        if self.strip_bom && contents.starts_with('\u{FEFF}') {
+            contents.remove(0);
+        }
+        if self.strip_invisible {
+            contents.retain(|c| !INVISIBLE.contains(&c));
+        }
+        Ok(match self.get_line_ending {
+            Some(line_ending) => line_ending.apply(&contents),
+            None => contents,
+        })
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        let contents = match self.set_line_ending {
+            Some(line_ending) => line_ending.apply(&contents),
+            None => contents,
+        };
+        self.context.set_contents(contents)
+    }
+}
+
+impl<C> ClipboardProviderNew for NormalizedClipboardContext<C>
+where
+    C: ClipboardProviderNew,
+{
+    fn new() -> crate::ClipResult<Self> {
+        Ok(NormalizedClipboardContext::new(C::new()?))
+    }
+}
+
+/// Streaming is not forwarded to the inner context, as contents must be normalized as a whole.
+impl<C> ClipboardProviderExt for NormalizedClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.context.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.context.change_token()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.context.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.context.available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.context.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.context.clear_contents()
+    }
+}
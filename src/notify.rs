@@ -0,0 +1,318 @@
+//! Show a desktop notification when setting clipboard contents.
+//!
+//! [`NotifyClipboardContext`] wraps any clipboard context, and shows a notification such as
+//! "Copied 42 characters" each time contents are set successfully. This is useful for clipboard
+//! centric command line tools, that don't have a window to show feedback in.
+//!
+//! Notifications are shown through the tools the system provides:
+//!
+//! - Linux/BSD: `notify-send`, talking to the notification daemon over D-Bus
+//! - macOS: `osascript`, using the notification center
+//! - Windows: `powershell.exe`, using a notification area balloon
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::notify::NotifyClipboardContext;
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! let mut ctx = NotifyClipboardContext::new(ClipboardContext::new().unwrap())
+//!     .with_app_name("my-tool");
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::{Error as IoError, Write};
+use std::process::{Command, Stdio};
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ClipboardProviderNew, ContentLifetime, Selection, ThreadAffinity};
+
+/// Notification summary when no app name is set.
+const DEFAULT_SUMMARY: &str = "Clipboard";
+
+/// Builds the notification message for the set contents.
+type Message = Box<dyn Fn(&str) -> String + Send>;
+
+/// Show a desktop notification after setting contents on the wrapped context.
+///
+/// Failing to show a notification does not fail setting the clipboard contents.
+///
+/// See module documentation for more information.
+pub struct NotifyClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    context: C,
+    app_name: Option<String>,
+    message: Message,
+}
+
+impl<C> NotifyClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context.
+    pub fn new(context: C) -> Self {
+        Self {
+            context,
+            app_name: None,
+            message: Box::new(default_message),
+        }
+    }
+
+    /// Set the application name, shown as notification summary.
+    pub fn with_app_name(mut self, app_name: &str) -> Self {
+        self.app_name = Some(app_name.into());
+        self
+    }
+
+    /// Build the notification message from the set contents with the given function.
+    ///
+    /// By default the message shows the number of copied characters. Avoid including the
+    /// contents themselves if they may be sensitive, notifications are often logged.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::notify::NotifyClipboardContext;
+    /// use copypasta_ext::x11_bin::ClipboardContext;
+    ///
+    /// let ctx = NotifyClipboardContext::new(ClipboardContext::new().unwrap())
+    ///     .with_message(|_| "Copied password, clears in 30 seconds".into());
+    /// ```
+    pub fn with_message<F>(mut self, message: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + 'static,
+    {
+        self.message = Box::new(message);
+        self
+    }
+
+    /// Get a reference to the inner clipboard context.
+    pub fn inner(&self) -> &C {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+}
+
+impl<C> ClipboardProvider for NotifyClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.context.get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        let message = (self.message)(&contents);
+        self.context.set_contents(contents)?;
+        let summary = self.app_name.as_deref().unwrap_or(DEFAULT_SUMMARY);
+        let _ = show_notification(summary, &message);
+        Ok(())
+    }
+}
+
+impl<C> ClipboardProviderNew for NotifyClipboardContext<C>
+where
+    C: ClipboardProviderNew,
+{
+    fn new() -> crate::ClipResult<Self> {
+        Ok(NotifyClipboardContext::new(C::new()?))
+    }
+}
+
+/// Setting from a stream is not forwarded to the inner context, so the contents are known to
+/// build the notification message.
+impl<C> ClipboardProviderExt for NotifyClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.context.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.context.change_token()
+    }
+
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        self.context.content_hash()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.context.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.context.available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.context.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.context.clear_contents()
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> crate::ClipResult<()> {
+        self.context.get_contents_to(writer)
+    }
+}
+
+/// The default notification message, such as "Copied 42 characters".
+fn default_message(contents: &str) -> String {
+    match contents.chars().count() {
+        1 => "Copied 1 character".into(),
+        count => format!("Copied {} characters", count),
+    }
+}
+
+/// Show a desktop notification with the given summary and body.
+///
+/// Uses the notification tool provided by the system, see module documentation. Fails if the
+/// tool is not available, or if it fails to show the notification.
+///
+/// ```rust,no_run
+/// copypasta_ext::notify::show_notification("my-tool", "Copied 42 characters").unwrap();
+/// ```
+pub fn show_notification(summary: &str, body: &str) -> crate::ClipResult<()> {
+    let mut command = notification_command(summary, body)?;
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // The balloon is shown as long as the process lives, don't wait for it on Windows
+    if cfg!(windows) {
+        command.spawn().map_err(Error::Spawn)?;
+        return Ok(());
+    }
+    let status = command.status().map_err(Error::Spawn)?;
+    if !status.success() {
+        return Err(Error::Status(status.code().unwrap_or(0)).into());
+    }
+    Ok(())
+}
+
+/// Build the command to show a notification on this platform.
+///
+/// On all platforms text is passed as arguments or through the environment, never as part of a
+/// script, so it can't be interpreted.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn notification_command(summary: &str, body: &str) -> Result<Command, Error> {
+    let mut command = Command::new("notify-send");
+    command.arg("--").arg(summary).arg(body);
+    Ok(command)
+}
+
+/// Build the command to show a notification on this platform.
+#[cfg(target_os = "macos")]
+fn notification_command(summary: &str, body: &str) -> Result<Command, Error> {
+    let mut command = Command::new("osascript");
+    command
+        .arg("-e")
+        .arg("on run argv")
+        .arg("-e")
+        .arg("display notification (item 2 of argv) with title (item 1 of argv)")
+        .arg("-e")
+        .arg("end run")
+        .arg(summary)
+        .arg(body);
+    Ok(command)
+}
+
+/// Build the command to show a notification on this platform.
+#[cfg(windows)]
+fn notification_command(summary: &str, body: &str) -> Result<Command, Error> {
+    const SCRIPT: &str = "Add-Type -AssemblyName System.Windows.Forms; \
+        $n = New-Object System.Windows.Forms.NotifyIcon; \
+        $n.Icon = [System.Drawing.SystemIcons]::Information; \
+        $n.Visible = $true; \
+        $n.ShowBalloonTip(5000, $env:COPYPASTA_NOTIFY_SUMMARY, $env:COPYPASTA_NOTIFY_BODY, 'None'); \
+        Start-Sleep -Seconds 6; \
+        $n.Dispose()";
+    let mut command = Command::new("powershell.exe");
+    command
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-WindowStyle",
+            "Hidden",
+            "-Command",
+            SCRIPT,
+        ])
+        .env("COPYPASTA_NOTIFY_SUMMARY", summary)
+        .env("COPYPASTA_NOTIFY_BODY", body);
+    Ok(command)
+}
+
+/// Build the command to show a notification on this platform.
+#[cfg(not(any(unix, windows)))]
+fn notification_command(_summary: &str, _body: &str) -> Result<Command, Error> {
+    Err(Error::Unsupported)
+}
+
+/// Represents desktop notification related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Desktop notifications are not supported on this platform.
+    Unsupported,
+
+    /// Failed to start the notification tool, it is probably not installed.
+    Spawn(IoError),
+
+    /// The notification tool exited with a non-successful status code.
+    Status(i32),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unsupported => write!(f, "Desktop notifications not supported on this platform"),
+            Error::Spawn(err) => write!(f, "Failed to start notification tool: {}", err),
+            Error::Status(code) => write!(
+                f,
+                "Failed to show notification, tool exited with status code {}",
+                code
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Spawn(err) => Some(err),
+            _ => None,
+        }
+    }
+}
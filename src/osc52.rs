@@ -14,6 +14,11 @@
 //! - Requires terminal that supports these escape codes.
 //! - Doesn't catch errors while setting clipboard contents.
 //! - Cannot get clipboard contents.
+//! - Some terminals and multiplexers silently drop the sequence, see [`is_supported`].
+//!
+//! To embed the sequence in your own terminal output instead, use [`encode`] or one of its
+//! multiplexer passthrough variants. With the `crossterm` feature, [`CopyToClipboard`] queues it
+//! as a crossterm command.
 //!
 //! # Examples
 //!
@@ -39,14 +44,18 @@
 //!
 //! [X11ClipboardContext]: https://docs.rs/copypasta/*/copypasta/x11_clipboard/struct.X11ClipboardContext.html
 
+use std::env;
 use std::error::Error as StdError;
 use std::fmt;
+use std::fs;
 
 use base64::engine::Engine;
 
 use crate::combined::CombinedClipboardContext;
 use crate::display::DisplayServer;
 use crate::prelude::*;
+use crate::selection::{Selection, SelectionClipboardProviderExt};
+use crate::{ClipboardProviderNew, ContentLifetime};
 
 /// Platform specific context.
 ///
@@ -54,6 +63,221 @@ use crate::prelude::*;
 /// `ClipboardContext` provided by `rust-clipboard` on other platforms.
 pub type ClipboardContext = Osc52ClipboardContext;
 
+/// Terminal types known not to support OSC 52, such as the Linux console.
+const UNSUPPORTED_TERMS: [&str; 2] = ["dumb", "linux"];
+
+/// Terminal programs known not to support OSC 52, as set in `TERM_PROGRAM`.
+const UNSUPPORTED_TERM_PROGRAMS: [&str; 1] = ["Apple_Terminal"];
+
+/// Maximum number of ancestor processes to inspect when detecting mosh.
+const MAX_ANCESTORS: usize = 16;
+
+/// Check whether the terminal likely supports OSC 52.
+///
+/// This is a best effort, may be unreliable. Returns false for terminals and multiplexers known
+/// to silently drop the sequence, so contents would be copied into the void:
+///
+/// - no terminal type except on Windows, or a terminal type such as `dumb` or the Linux console
+///   (`TERM`)
+/// - Terminal.app on macOS (`TERM_PROGRAM`)
+/// - terminal emulation inside Emacs (`INSIDE_EMACS`)
+/// - mosh, detected through a `mosh-server` ancestor process on Linux, as versions before 1.4
+///   drop the sequence and the version can't be detected
+///
+/// Used when selecting a context through [`try_context`](crate::try_context), unless the `osc52`
+/// backend is explicitly configured.
+///
+/// ```rust
+/// use copypasta_ext::osc52;
+///
+/// if !osc52::is_supported() {
+///     eprintln!("Terminal doesn't support copying through OSC 52");
+/// }
+/// ```
+pub fn is_supported() -> bool {
+    match env::var("TERM") {
+        Ok(term) if term.is_empty() || UNSUPPORTED_TERMS.contains(&term.as_str()) => return false,
+        Ok(_) => {}
+        // Terminals on Windows don't set a terminal type
+        Err(_) if !cfg!(windows) => return false,
+        Err(_) => {}
+    }
+    if let Ok(program) = env::var("TERM_PROGRAM") {
+        if UNSUPPORTED_TERM_PROGRAMS.contains(&program.as_str()) {
+            return false;
+        }
+    }
+    env::var_os("INSIDE_EMACS").is_none() && !is_mosh()
+}
+
+/// Check whether we're running in a mosh session, through a `mosh-server` ancestor process.
+///
+/// Only supported on Linux, always returns false on other platforms.
+fn is_mosh() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+
+    let mut pid = std::process::id();
+    for _ in 0..MAX_ANCESTORS {
+        // Format: pid (comm) state ppid ..., comm may contain spaces and parentheses
+        let stat = match fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Ok(stat) => stat,
+            Err(_) => return false,
+        };
+        let (start, end) = match (stat.find('('), stat.rfind(')')) {
+            (Some(start), Some(end)) if start < end => (start, end),
+            _ => return false,
+        };
+        if &stat[start + 1..end] == "mosh-server" {
+            return true;
+        }
+        pid = match stat[end + 1..].split_whitespace().nth(1).map(str::parse) {
+            Some(Ok(ppid)) if ppid > 1 => ppid,
+            _ => return false,
+        };
+    }
+    false
+}
+
+/// Maximum length of a single screen passthrough sequence, longer sequences are chunked.
+const SCREEN_CHUNK_LEN: usize = 76;
+
+/// String terminator to end an OSC 52 sequence with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Terminator {
+    /// The bell character (`BEL`), the most widely supported. The default.
+    Bel,
+
+    /// The string terminator (`ESC \`), as specified by ECMA-48.
+    St,
+}
+
+impl Terminator {
+    /// The terminator characters.
+    fn as_str(self) -> &'static str {
+        match self {
+            Terminator::Bel => "\x07",
+            Terminator::St => "\x1B\\",
+        }
+    }
+}
+
+impl Default for Terminator {
+    fn default() -> Self {
+        Terminator::Bel
+    }
+}
+
+/// Encode an OSC 52 sequence to set the given selection to the given contents.
+///
+/// Doesn't output anything, so it can be embedded in the output of applications owning the
+/// terminal writer, such as terminal user interface frameworks.
+///
+/// ```rust
+/// use copypasta_ext::osc52::{self, Terminator};
+/// use copypasta_ext::Selection;
+///
+/// let sequence = osc52::encode("some string", Selection::Clipboard, Terminator::Bel);
+/// assert_eq!(sequence, "\x1B]52;c;c29tZSBzdHJpbmc=\x07");
+/// ```
+pub fn encode(contents: &str, selection: Selection, terminator: Terminator) -> String {
+    format!(
+        "\x1B]52;{};{}{}",
+        target(selection),
+        base64::engine::general_purpose::STANDARD.encode(contents),
+        terminator.as_str(),
+    )
+}
+
+/// Encode an OSC 52 sequence wrapped for passthrough by tmux.
+///
+/// tmux only forwards the sequence to the outer terminal if `allow-passthrough` is enabled.
+///
+/// ```rust
+/// use copypasta_ext::osc52::{self, Terminator};
+/// use copypasta_ext::Selection;
+///
+/// let sequence = osc52::encode_tmux("some string", Selection::Clipboard, Terminator::Bel);
+/// assert_eq!(sequence, "\x1BPtmux;\x1B\x1B]52;c;c29tZSBzdHJpbmc=\x07\x1B\\");
+/// ```
+pub fn encode_tmux(contents: &str, selection: Selection, terminator: Terminator) -> String {
+    // Escape characters in the wrapped sequence must be doubled
+    let sequence = encode(contents, selection, terminator).replace('\x1B', "\x1B\x1B");
+    format!("\x1BPtmux;{}\x1B\\", sequence)
+}
+
+/// Encode an OSC 52 sequence wrapped for passthrough by GNU screen.
+///
+/// Screen limits the length of passthrough sequences, so the sequence is split into chunks that
+/// are each wrapped separately. The sequence is always terminated by `BEL`, as screen ends the
+/// passthrough at the first string terminator.
+///
+/// ```rust
+/// use copypasta_ext::osc52;
+/// use copypasta_ext::Selection;
+///
+/// let sequence = osc52::encode_screen("some string", Selection::Clipboard);
+/// assert_eq!(sequence, "\x1BP\x1B]52;c;c29tZSBzdHJpbmc=\x07\x1B\\");
+/// ```
+pub fn encode_screen(contents: &str, selection: Selection) -> String {
+    // The sequence is ASCII, so chunking bytes keeps valid UTF-8
+    let sequence = encode(contents, selection, Terminator::Bel);
+    sequence
+        .as_bytes()
+        .chunks(SCREEN_CHUNK_LEN)
+        .map(|chunk| format!("\x1BP{}\x1B\\", String::from_utf8_lossy(chunk)))
+        .collect()
+}
+
+/// A crossterm command to set the clipboard contents through OSC 52.
+///
+/// Queue it along with other output, such as in ratatui applications, so it isn't interleaved
+/// with rendering. Requires the `crossterm` feature.
+///
+/// ```rust,no_run
+/// use std::io::{self, Write};
+/// use copypasta_ext::osc52::CopyToClipboard;
+///
+/// let mut stdout = io::stdout();
+/// crossterm::queue!(stdout, CopyToClipboard("some string")).unwrap();
+/// stdout.flush().unwrap();
+/// ```
+///
+/// On Windows, the sequence is only written if the console supports ANSI escape sequences.
+#[cfg(feature = "crossterm")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CopyToClipboard<T: AsRef<str>>(pub T);
+
+#[cfg(feature = "crossterm")]
+impl<T: AsRef<str>> crossterm::Command for CopyToClipboard<T> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(&encode(
+            self.0.as_ref(),
+            Selection::Clipboard,
+            Terminator::Bel,
+        ))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "OSC 52 is not supported by this console",
+        ))
+    }
+}
+
+/// The OSC 52 selection parameter for the given selection.
+fn target(selection: Selection) -> char {
+    match selection {
+        Selection::Clipboard => 'c',
+        Selection::Primary => 'p',
+        Selection::Secondary => 's',
+    }
+}
+
 /// OSC 52 escape sequence to set clipboard contents.
 ///
 /// See module documentation for more information.
@@ -95,22 +319,40 @@ impl ClipboardProvider for Osc52ClipboardContext {
     }
 
     fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.set_selection(Selection::Clipboard, contents)
+    }
+}
+
+/// Terminals may ignore selections other than the regular clipboard.
+impl SelectionClipboardProviderExt for Osc52ClipboardContext {
+    fn supports_selection(&self, _selection: Selection) -> bool {
+        true
+    }
+
+    fn get_selection(&mut self, _selection: Selection) -> crate::ClipResult<String> {
+        Err(Error::Unsupported.into())
+    }
+
+    fn set_selection(&mut self, selection: Selection, contents: String) -> crate::ClipResult<()> {
         // Use OSC 52 escape sequence to set clipboard through stdout
-        print!(
-            "\x1B]52;c;{}\x07",
-            base64::engine::general_purpose::STANDARD.encode(&contents)
-        );
+        print!("{}", encode(&contents, selection, Terminator::Bel));
         Ok(())
     }
 }
 
+impl ClipboardProviderNew for Osc52ClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        Ok(Self)
+    }
+}
+
 impl ClipboardProviderExt for Osc52ClipboardContext {
     fn display_server(&self) -> Option<DisplayServer> {
         Some(DisplayServer::Tty)
     }
 
-    fn has_bin_lifetime(&self) -> bool {
-        false
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
     }
 }
 
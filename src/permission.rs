@@ -0,0 +1,206 @@
+//! Ask for permission before accessing the clipboard.
+//!
+//! [`PermissionClipboardContext`] wraps any clipboard context, and invokes a callback before each
+//! get and set operation. The callback decides whether access is allowed, such as by prompting the
+//! user or by checking an enterprise policy. Denied operations fail with [`Error::Denied`], and
+//! never reach the inner context.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::permission::{AccessOperation, PermissionClipboardContext};
+//! use copypasta_ext::testing::DryRunClipboardContext;
+//!
+//! // Allow copying, but never reading the clipboard
+//! let mut ctx = PermissionClipboardContext::new(DryRunClipboardContext::new(), |request| {
+//!     request.operation == AccessOperation::Set
+//! });
+//! ctx.set_contents("some string".into()).unwrap();
+//! assert!(ctx.get_contents().is_err());
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ContentLifetime, Selection, ThreadAffinity};
+
+/// A clipboard operation to ask permission for.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum AccessOperation {
+    /// Read the clipboard contents.
+    Get,
+
+    /// Write the clipboard contents.
+    Set,
+}
+
+impl fmt::Display for AccessOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccessOperation::Get => write!(f, "get"),
+            AccessOperation::Set => write!(f, "set"),
+        }
+    }
+}
+
+/// Metadata of a clipboard operation to ask permission for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AccessRequest {
+    /// The operation.
+    pub operation: AccessOperation,
+
+    /// Size in bytes of the contents to set, `None` when getting.
+    pub size: Option<usize>,
+}
+
+/// Decides whether a clipboard operation is allowed.
+type Callback = Box<dyn FnMut(&AccessRequest) -> bool + Send>;
+
+/// Ask for permission before each operation on the wrapped context.
+///
+/// Getting the contents in any way, including through
+/// [`content_hash`](ClipboardProviderExt::content_hash) and streaming, requires permission.
+/// Querying metadata such as [`change_token`](ClipboardProviderExt::change_token) and
+/// [`is_empty`](ClipboardProviderExt::is_empty) does not.
+///
+/// See module documentation for more information.
+pub struct PermissionClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    context: C,
+    callback: Callback,
+}
+
+impl<C> PermissionClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context, asking the given callback for permission.
+    ///
+    /// The callback returns whether the requested operation is allowed.
+    pub fn new<F>(context: C, callback: F) -> Self
+    where
+        F: FnMut(&AccessRequest) -> bool + Send + 'static,
+    {
+        Self {
+            context,
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Ask the callback for permission, returning an error if denied.
+    fn check(&mut self, operation: AccessOperation, size: Option<usize>) -> Result<(), Error> {
+        if (self.callback)(&AccessRequest { operation, size }) {
+            Ok(())
+        } else {
+            Err(Error::Denied(operation))
+        }
+    }
+
+    /// Get a reference to the inner clipboard context.
+    pub fn inner(&self) -> &C {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+}
+
+impl<C> ClipboardProvider for PermissionClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.check(AccessOperation::Get, None)?;
+        self.context.get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.check(AccessOperation::Set, Some(contents.len()))?;
+        self.context.set_contents(contents)
+    }
+}
+
+/// Streaming is not forwarded to the inner context, so each operation asks for permission.
+impl<C> ClipboardProviderExt for PermissionClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.context.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.context.change_token()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.context.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.context.available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.context.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.check(AccessOperation::Set, Some(0))?;
+        self.context.clear_contents()
+    }
+}
+
+/// Represents clipboard permission related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Permission for the operation was denied.
+    Denied(AccessOperation),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Denied(operation) => {
+                write!(f, "Permission to {} clipboard contents denied", operation)
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,116 @@
+//! Persist clipboard contents beyond the lifetime of the current process.
+//!
+//! Native X11 and Wayland clipboard contexts serve clipboard contents from the current process,
+//! and contents are lost when it exits. Applications using such a fast native context may call
+//! [`persist`](crate::ClipboardProviderExt::persist) once at shutdown to hand off the current
+//! contents to a mechanism that outlives the process.
+//!
+//! The best available mechanism is used:
+//!
+//! - X11: adopt contents in a forked process (`x11-fork`), or re-set them through
+//!   `xclip`/`xsel` (`x11-bin`)
+//! - Wayland: re-set contents through `wl-copy` (`wayland-bin`)
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::copypasta::x11_clipboard::X11ClipboardContext;
+//!
+//! let mut ctx: X11ClipboardContext = X11ClipboardContext::new().unwrap();
+//! ctx.set_contents("some string".into()).unwrap();
+//!
+//! // Keep contents available after exit
+//! ctx.persist().unwrap();
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+#[allow(unused_imports)]
+use std::time::Duration;
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+/// Time to wait for clipboard binaries to take ownership when persisting.
+#[allow(dead_code)]
+const OWNERSHIP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Persist the given contents on the X11 clipboard through the best available mechanism.
+#[cfg(all(
+    feature = "x11",
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "ios",
+        target_os = "emscripten"
+    ))
+))]
+#[allow(unused_variables)]
+pub(crate) fn x11(contents: String) -> crate::ClipResult<()> {
+    #[cfg(feature = "x11-fork")]
+    {
+        if let Ok(mut context) = crate::x11_fork::X11ForkClipboardContext::new() {
+            return context.set_contents(contents);
+        }
+    }
+    #[cfg(feature = "x11-bin")]
+    {
+        if let Ok(context) = crate::x11_bin::X11BinClipboardContext::new() {
+            return context
+                .with_wait_for_ownership(OWNERSHIP_TIMEOUT)
+                .set_contents(contents);
+        }
+    }
+    Err(Error::Unsupported.into())
+}
+
+/// Persist the given contents on the Wayland clipboard through the best available mechanism.
+#[cfg(all(
+    any(feature = "wayland", feature = "wayland-native"),
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "ios",
+        target_os = "emscripten"
+    ))
+))]
+#[allow(unused_variables)]
+pub(crate) fn wayland(contents: String) -> crate::ClipResult<()> {
+    #[cfg(feature = "wayland-bin")]
+    {
+        if let Ok(context) = crate::wayland_bin::WaylandBinClipboardContext::new() {
+            return context
+                .with_wait_for_ownership(OWNERSHIP_TIMEOUT)
+                .set_contents(contents);
+        }
+    }
+    Err(Error::Unsupported.into())
+}
+
+/// Represents clipboard persistence related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No mechanism is available to persist clipboard contents for this clipboard context.
+    Unsupported,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unsupported => write!(
+                f,
+                "Cannot persist clipboard contents, no supported mechanism available"
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,29 @@
+//! Prepare child processes spawned by clipboard contexts.
+
+use std::mem::MaybeUninit;
+
+/// Signals to reset, the standard signals numbered 1 to 31.
+///
+/// Resetting `SIGKILL` and `SIGSTOP` fails, which is harmless.
+const SIGNALS: std::ops::Range<libc::c_int> = 1..32;
+
+/// Reset the dispositions of all signals to their default, and unblock them.
+///
+/// Called in a child process after forking. A child inherits the signal handlers and mask of its
+/// parent. Ignored signals, such as a `SIGCHLD` or `SIGPIPE` ignored by a daemon, stay ignored
+/// after executing another binary, which breaks binaries that wait on their own children. A fork
+/// that doesn't execute another binary runs the handlers of the parent instead, which may rely on
+/// threads that don't exist in the fork.
+///
+/// Only calls async-signal-safe functions, so it is safe to call between forking and executing.
+pub(crate) fn reset_signals() {
+    for signal in SIGNALS {
+        unsafe { libc::signal(signal, libc::SIG_DFL) };
+    }
+
+    unsafe {
+        let mut set = MaybeUninit::<libc::sigset_t>::uninit();
+        libc::sigemptyset(set.as_mut_ptr());
+        libc::sigprocmask(libc::SIG_SETMASK, set.as_ptr(), std::ptr::null_mut());
+    }
+}
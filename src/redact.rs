@@ -0,0 +1,147 @@
+//! Redact clipboard contents in diagnostic output.
+//!
+//! Clipboard contents often hold secrets such as copied passwords. With the `log` feature this
+//! crate logs clipboard operations, formatting contents through [`Redacted`] only, which applies
+//! the global [`RedactionPolicy`]. By default contents are never shown, so enabling diagnostics
+//! can't leak them into logs.
+//!
+//! Use [`Redacted`] in your own diagnostics to follow the same policy.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use copypasta_ext::redact::{self, Redacted, RedactionPolicy};
+//!
+//! assert_eq!(Redacted("hunter2").to_string(), "<redacted>");
+//!
+//! redact::set_policy(RedactionPolicy::Length);
+//! assert_eq!(Redacted("hunter2").to_string(), "<7 bytes>");
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::Hasher;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The global policy, encoded by [`RedactionPolicy::encode`].
+static POLICY: AtomicUsize = AtomicUsize::new(0);
+
+/// How to show clipboard contents in diagnostic output.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum RedactionPolicy {
+    /// Never show anything about the contents. The default.
+    Hidden,
+
+    /// Show the content length only.
+    Length,
+
+    /// Show the content length and hash, allows correlating operations.
+    Hash,
+
+    /// Show the first number of characters, and the content length.
+    Prefix(usize),
+}
+
+impl RedactionPolicy {
+    /// Format the given contents according to this policy.
+    ///
+    /// ```rust
+    /// use copypasta_ext::redact::RedactionPolicy;
+    ///
+    /// let redacted = RedactionPolicy::Prefix(3).redact("hunter2");
+    /// assert_eq!(redacted.to_string(), "\"hun\"… <7 bytes>");
+    /// ```
+    pub fn redact(self, contents: &str) -> impl fmt::Display + '_ {
+        PolicyRedacted {
+            policy: self,
+            contents,
+        }
+    }
+
+    /// Encode as number, for atomic storage.
+    fn encode(self) -> usize {
+        match self {
+            RedactionPolicy::Hidden => 0,
+            RedactionPolicy::Length => 1,
+            RedactionPolicy::Hash => 2,
+            RedactionPolicy::Prefix(chars) => chars.saturating_add(3),
+        }
+    }
+
+    /// Decode from number, as encoded by [`encode`](RedactionPolicy::encode).
+    fn decode(policy: usize) -> Self {
+        match policy {
+            0 => RedactionPolicy::Hidden,
+            1 => RedactionPolicy::Length,
+            2 => RedactionPolicy::Hash,
+            chars => RedactionPolicy::Prefix(chars - 3),
+        }
+    }
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        RedactionPolicy::Hidden
+    }
+}
+
+/// Set the global redaction policy, used by [`Redacted`].
+pub fn set_policy(policy: RedactionPolicy) {
+    POLICY.store(policy.encode(), Ordering::Relaxed);
+}
+
+/// Get the global redaction policy.
+pub fn policy() -> RedactionPolicy {
+    RedactionPolicy::decode(POLICY.load(Ordering::Relaxed))
+}
+
+/// Clipboard contents, formatted according to the global [`RedactionPolicy`].
+///
+/// See module documentation for more information.
+#[derive(Copy, Clone)]
+pub struct Redacted<'a>(pub &'a str);
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        policy().redact(self.0).fmt(f)
+    }
+}
+
+/// Never show the contents through debug formatting either.
+impl fmt::Debug for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Contents formatted according to a specific policy.
+struct PolicyRedacted<'a> {
+    policy: RedactionPolicy,
+    contents: &'a str,
+}
+
+impl fmt::Display for PolicyRedacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let len = self.contents.len();
+        match self.policy {
+            RedactionPolicy::Hidden => write!(f, "<redacted>"),
+            RedactionPolicy::Length => write!(f, "<{} bytes>", len),
+            RedactionPolicy::Hash => {
+                let mut hasher = DefaultHasher::new();
+                hasher.write(self.contents.as_bytes());
+                write!(f, "<{} bytes, hash {:016x}>", len, hasher.finish())
+            }
+            RedactionPolicy::Prefix(chars) => {
+                let end = self
+                    .contents
+                    .char_indices()
+                    .nth(chars)
+                    .map(|(i, _)| i)
+                    .unwrap_or(len);
+                let ellipsis = if end < len { "…" } else { "" };
+                write!(f, "{:?}{} <{} bytes>", &self.contents[..end], ellipsis, len)
+            }
+        }
+    }
+}
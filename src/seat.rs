@@ -0,0 +1,314 @@
+//! Manage the clipboards of multiple displays and seats from one process.
+//!
+//! A single process may reach several clipboards at once, such as the clipboard of each X11
+//! display it has access to, or of each seat of a Wayland compositor. Session managers and
+//! similar tools use this to manage all of them, rather than only the one of the current
+//! environment.
+//!
+//! [`targets`] enumerates the clipboards that are reachable, and [`ClipboardTarget::context`]
+//! constructs a clipboard context bound to one of them. Contexts are backed by binaries, so X11
+//! targets require the `x11-bin` feature and Wayland targets require the `wayland-bin` feature.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::seat;
+//!
+//! for target in seat::targets() {
+//!     let mut ctx = target.context().unwrap();
+//!     println!("{}: {:?}", target, ctx.get_contents());
+//! }
+//! ```
+
+use std::env;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+
+use crate::config::ClipboardConfig;
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::selection::{Selection, SelectionClipboardProviderExt};
+
+/// Directory holding the sockets of local X11 displays.
+const X11_SOCKET_DIR: &str = "/tmp/.X11-unix";
+
+/// A clipboard reachable from this process.
+///
+/// ```rust
+/// use copypasta_ext::seat::ClipboardTarget;
+///
+/// let target = ClipboardTarget::Wayland {
+///     display: "wayland-1".into(),
+///     seat: Some("seat1".into()),
+/// };
+/// assert_eq!(target.to_string(), "Wayland display wayland-1, seat seat1");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ClipboardTarget {
+    /// The clipboard of an X11 display, such as `:0`.
+    X11 {
+        /// The display, as used in `DISPLAY`.
+        display: String,
+    },
+
+    /// The clipboard of a seat of a Wayland compositor.
+    Wayland {
+        /// The display, as used in `WAYLAND_DISPLAY`.
+        display: String,
+
+        /// The name of the seat, `None` for the first seat.
+        seat: Option<String>,
+    },
+}
+
+impl ClipboardTarget {
+    /// The display server of this target.
+    pub fn display_server(&self) -> DisplayServer {
+        match self {
+            ClipboardTarget::X11 { .. } => DisplayServer::X11,
+            ClipboardTarget::Wayland { .. } => DisplayServer::Wayland,
+        }
+    }
+
+    /// Construct a clipboard context bound to this target.
+    ///
+    /// Uses the configuration from the environment, see [`ClipboardConfig::from_env`]. Errors if
+    /// it is invalid.
+    pub fn context(&self) -> crate::ClipResult<Box<dyn ClipboardProviderExt>> {
+        self.context_with_config(&ClipboardConfig::from_env()?)
+    }
+
+    /// Construct a clipboard context bound to this target, with the given configuration.
+    ///
+    /// Applies the configured binary paths, search path, environment sanitizing, ownership
+    /// timeout and selection. The configured backends and display server are ignored.
+    #[allow(unused_variables)]
+    pub fn context_with_config(
+        &self,
+        config: &ClipboardConfig,
+    ) -> crate::ClipResult<Box<dyn ClipboardProviderExt>> {
+        match self {
+            #[cfg(feature = "x11-bin")]
+            ClipboardTarget::X11 { display } => {
+                let context = crate::x11_bin::X11BinClipboardContext::new()?
+                    .with_config(config)
+                    .with_display(display);
+                select(context, config.selection)
+            }
+            #[cfg(feature = "wayland-bin")]
+            ClipboardTarget::Wayland { display, seat } => {
+                let mut context = crate::wayland_bin::WaylandBinClipboardContext::new()?
+                    .with_config(config)
+                    .with_display(display);
+                if let Some(seat) = seat {
+                    context = context.with_seat(seat);
+                }
+                select(context, config.selection)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::NoContext(self.display_server()).into()),
+        }
+    }
+}
+
+impl fmt::Display for ClipboardTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClipboardTarget::X11 { display } => write!(f, "X11 display {}", display),
+            ClipboardTarget::Wayland {
+                display,
+                seat: None,
+            } => write!(f, "Wayland display {}", display),
+            ClipboardTarget::Wayland {
+                display,
+                seat: Some(seat),
+            } => write!(f, "Wayland display {}, seat {}", display, seat),
+        }
+    }
+}
+
+/// Enumerate the clipboards reachable from this process.
+///
+/// Lists the X11 display in `DISPLAY`, followed by other local X11 displays with a socket in
+/// `/tmp/.X11-unix`. Then lists the Wayland display in `WAYLAND_DISPLAY`, followed by other
+/// Wayland displays with a socket in `XDG_RUNTIME_DIR`.
+///
+/// With the `wayland-native` feature, Wayland displays are connected to, to list a target for
+/// each of their seats. Displays that can't be connected to are skipped. Without it, or if a
+/// compositor doesn't name its seats, a single target for the first seat is listed.
+pub fn targets() -> Vec<ClipboardTarget> {
+    let mut targets: Vec<_> = x11_displays()
+        .into_iter()
+        .map(|display| ClipboardTarget::X11 { display })
+        .collect();
+    for display in wayland_displays() {
+        targets.extend(wayland_targets(display));
+    }
+    targets
+}
+
+/// Box the given context, accessing the given selection.
+#[allow(dead_code)]
+fn select<C>(context: C, selection: Selection) -> crate::ClipResult<Box<dyn ClipboardProviderExt>>
+where
+    C: ClipboardProviderExt + SelectionClipboardProviderExt + 'static,
+{
+    match selection {
+        Selection::Clipboard => Ok(Box::new(context)),
+        _ if context.supports_selection(selection) => Ok(Box::new(
+            crate::selection::SelectedClipboardContext::new(context, selection),
+        )),
+        _ => Err(Error::UnsupportedSelection(selection).into()),
+    }
+}
+
+/// Local X11 displays, the one in `DISPLAY` first.
+fn x11_displays() -> Vec<String> {
+    let mut displays: Vec<String> = env::var("DISPLAY")
+        .ok()
+        .filter(|display| !display.is_empty())
+        .into_iter()
+        .collect();
+
+    let mut numbers: Vec<u32> = sockets(Path::new(X11_SOCKET_DIR))
+        .iter()
+        .filter_map(|name| name.strip_prefix('X')?.parse().ok())
+        .collect();
+    numbers.sort_unstable();
+    for number in numbers {
+        // Skip the display in DISPLAY, which may include a screen number
+        let listed = displays.iter().any(|display| {
+            let display = display.split('.').next().unwrap_or_default();
+            display.strip_prefix(':') == Some(number.to_string().as_str())
+        });
+        if !listed {
+            displays.push(format!(":{}", number));
+        }
+    }
+    displays
+}
+
+/// Wayland displays, the one in `WAYLAND_DISPLAY` first.
+fn wayland_displays() -> Vec<String> {
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    let mut displays: Vec<String> = env::var("WAYLAND_DISPLAY")
+        .ok()
+        .filter(|display| !display.is_empty())
+        .into_iter()
+        .collect();
+    let runtime_dir = match runtime_dir {
+        Some(dir) => dir,
+        None => return displays,
+    };
+
+    let mut names: Vec<String> = sockets(&runtime_dir)
+        .into_iter()
+        .filter(|name| name.starts_with("wayland-"))
+        .collect();
+    names.sort();
+    for name in names {
+        // The display in WAYLAND_DISPLAY may be an absolute path
+        let path = runtime_dir.join(&name);
+        if !displays
+            .iter()
+            .any(|display| runtime_dir.join(display) == path)
+        {
+            displays.push(name);
+        }
+    }
+    displays
+}
+
+/// Targets for the seats of the given Wayland display.
+#[cfg(feature = "wayland-native")]
+fn wayland_targets(display: String) -> Vec<ClipboardTarget> {
+    match crate::wayland_native::seats(display.as_ref()) {
+        Ok(seats) if !seats.is_empty() => seats
+            .into_iter()
+            .map(|seat| ClipboardTarget::Wayland {
+                display: display.clone(),
+                seat: Some(seat),
+            })
+            .collect(),
+        Ok(_) => vec![ClipboardTarget::Wayland {
+            display,
+            seat: None,
+        }],
+        Err(_err) => {
+            debug!("Skipping Wayland display {}: {}", display, _err);
+            vec![]
+        }
+    }
+}
+
+/// Targets for the seats of the given Wayland display.
+#[cfg(not(feature = "wayland-native"))]
+fn wayland_targets(display: String) -> Vec<ClipboardTarget> {
+    vec![ClipboardTarget::Wayland {
+        display,
+        seat: None,
+    }]
+}
+
+/// Names of the sockets in the given directory.
+fn sockets(dir: &Path) -> Vec<String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_type()
+                .map(|file_type| file_type.is_socket())
+                .unwrap_or(false)
+        })
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+/// Represents clipboard target related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No clipboard context for targets of this display server is enabled.
+    NoContext(DisplayServer),
+
+    /// The clipboard context of the target doesn't support the configured selection.
+    UnsupportedSelection(Selection),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoContext(DisplayServer::X11) => write!(
+                f,
+                "No clipboard context for X11 targets, requires the x11-bin feature"
+            ),
+            Error::NoContext(DisplayServer::Wayland) => write!(
+                f,
+                "No clipboard context for Wayland targets, requires the wayland-bin feature"
+            ),
+            Error::NoContext(server) => {
+                write!(f, "No clipboard context for {:?} targets", server)
+            }
+            Error::UnsupportedSelection(selection) => write!(
+                f,
+                "Clipboard target doesn't support the {} selection",
+                selection
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,235 @@
+//! Access other selections than the regular clipboard.
+//!
+//! Besides the regular clipboard, X11 has a primary selection holding the last selected text,
+//! pasted with the middle mouse button, and a rarely used secondary selection. Wayland and
+//! terminals supporting OSC 52 have a primary selection as well.
+//!
+//! [`SelectionClipboardProviderExt`] accesses any [`Selection`] through the same context, so
+//! selection-aware code doesn't need to branch per backend. It is implemented by the X11, Wayland
+//! and OSC 52 contexts. Selections a context doesn't support fail with [`Error::Unsupported`].
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::Selection;
+//! use copypasta_ext::selection::SelectionClipboardProviderExt;
+//! use copypasta_ext::x11_bin::X11BinClipboardContext;
+//!
+//! let mut ctx = X11BinClipboardContext::new().unwrap();
+//! println!("{:?}", ctx.get_selection(Selection::Primary));
+//! ctx.set_selection(Selection::Primary, "some string".into()).unwrap();
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use crate::combined::CombinedClipboardContext;
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ContentLifetime, ThreadAffinity};
+
+/// A clipboard selection.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+pub enum Selection {
+    /// The regular clipboard, set by explicit copy actions. The default.
+    Clipboard,
+
+    /// The primary selection, holding the last selected text.
+    Primary,
+
+    /// The X11 secondary selection.
+    Secondary,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Selection::Clipboard
+    }
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Selection::Clipboard => write!(f, "clipboard"),
+            Selection::Primary => write!(f, "primary"),
+            Selection::Secondary => write!(f, "secondary"),
+        }
+    }
+}
+
+/// Get and set contents of any selection through the same context.
+///
+/// See module documentation for more information.
+pub trait SelectionClipboardProviderExt: ClipboardProvider {
+    /// Whether the given selection can be accessed through this context.
+    ///
+    /// Getting may still fail for contexts that can't get contents at all, such as OSC 52.
+    fn supports_selection(&self, selection: Selection) -> bool;
+
+    /// Get the contents of the given selection.
+    fn get_selection(&mut self, selection: Selection) -> crate::ClipResult<String>;
+
+    /// Set the contents of the given selection.
+    fn set_selection(&mut self, selection: Selection, contents: String) -> crate::ClipResult<()>;
+}
+
+/// Gets from the first context, and sets through the second.
+impl<G, S> SelectionClipboardProviderExt for CombinedClipboardContext<G, S>
+where
+    G: SelectionClipboardProviderExt,
+    S: SelectionClipboardProviderExt,
+{
+    fn supports_selection(&self, selection: Selection) -> bool {
+        self.0.supports_selection(selection) && self.1.supports_selection(selection)
+    }
+
+    fn get_selection(&mut self, selection: Selection) -> crate::ClipResult<String> {
+        self.0.get_selection(selection)
+    }
+
+    fn set_selection(&mut self, selection: Selection, contents: String) -> crate::ClipResult<()> {
+        self.1.set_selection(selection, contents)
+    }
+}
+
+/// Access a fixed selection through the regular clipboard provider interface.
+///
+/// Wraps a context implementing [`SelectionClipboardProviderExt`], and gets and sets the given
+/// selection instead of the regular clipboard. Useful to pass a selection to code taking any
+/// clipboard context.
+///
+/// ```rust,no_run
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::Selection;
+/// use copypasta_ext::selection::SelectedClipboardContext;
+/// use copypasta_ext::x11_bin::X11BinClipboardContext;
+///
+/// let context = X11BinClipboardContext::new().unwrap();
+/// let mut ctx = SelectedClipboardContext::new(context, Selection::Primary);
+/// println!("{:?}", ctx.get_contents());
+/// ```
+pub struct SelectedClipboardContext<C>
+where
+    C: SelectionClipboardProviderExt,
+{
+    context: C,
+    selection: Selection,
+}
+
+impl<C> SelectedClipboardContext<C>
+where
+    C: SelectionClipboardProviderExt,
+{
+    /// Wrap the given clipboard context, accessing the given selection.
+    pub fn new(context: C, selection: Selection) -> Self {
+        Self { context, selection }
+    }
+
+    /// The accessed selection.
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    /// Get a reference to the inner clipboard context.
+    pub fn inner(&self) -> &C {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+}
+
+impl<C> ClipboardProvider for SelectedClipboardContext<C>
+where
+    C: SelectionClipboardProviderExt,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.context.get_selection(self.selection)
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.context.set_selection(self.selection, contents)
+    }
+}
+
+/// Contents are only forwarded through the selection, metadata describes the inner context.
+impl<C> ClipboardProviderExt for SelectedClipboardContext<C>
+where
+    C: SelectionClipboardProviderExt + ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.selection
+    }
+}
+
+impl<C> SelectionClipboardProviderExt for SelectedClipboardContext<C>
+where
+    C: SelectionClipboardProviderExt,
+{
+    fn supports_selection(&self, selection: Selection) -> bool {
+        self.context.supports_selection(selection)
+    }
+
+    fn get_selection(&mut self, selection: Selection) -> crate::ClipResult<String> {
+        self.context.get_selection(selection)
+    }
+
+    fn set_selection(&mut self, selection: Selection, contents: String) -> crate::ClipResult<()> {
+        self.context.set_selection(selection, contents)
+    }
+}
+
+/// Represents selection related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The selection is not supported by this context.
+    Unsupported(Selection),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unsupported(selection) => write!(
+                f,
+                "The {} selection is not supported through this context",
+                selection
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,283 @@
+//! Wrappers for sharing clipboard contexts between threads.
+//!
+//! All clipboard contexts are [`Send`], as required by [`ClipboardProvider`]. Not all of them are
+//! [`Sync`] though. The wrappers in this module add internal locking where needed so they can be
+//! stored in shared application state, or shared between subsystems through cloned handles.
+
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::content::ClipboardContent;
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ContentLifetime, Selection, ThreadAffinity};
+
+/// Make any clipboard context [`Sync`].
+///
+/// Wraps the context in a lock. Getting and setting contents requires mutable access, which is
+/// already exclusive, so the lock is only taken when querying context properties through a shared
+/// reference.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::shared::SyncClipboardContext;
+/// use copypasta_ext::x11_bin::ClipboardContext;
+///
+/// fn assert_sync<T: Sync>(_: &T) {}
+///
+/// let mut ctx = SyncClipboardContext::new(ClipboardContext::new().unwrap());
+/// assert_sync(&ctx);
+/// ctx.set_contents("some string".into()).unwrap();
+/// ```
+pub struct SyncClipboardContext<C>(Mutex<C>)
+where
+    C: ClipboardProvider;
+
+impl<C> SyncClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context.
+    pub fn new(context: C) -> Self {
+        Self(Mutex::new(context))
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.0.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the inner context, ignoring poisoning.
+    fn lock(&self) -> MutexGuard<'_, C> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get mutable access to the inner context, ignoring poisoning.
+    fn inner_mut(&mut self) -> &mut C {
+        self.0.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<C> ClipboardProvider for SyncClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.inner_mut().get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.inner_mut().set_contents(contents)
+    }
+}
+
+impl<C> ClipboardProviderExt for SyncClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.lock().display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.lock().content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.lock().requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.lock().thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.lock().selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.inner_mut().change_token()
+    }
+
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        self.inner_mut().content_hash()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.inner_mut().is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.inner_mut().available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.inner_mut().persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.inner_mut().clear_contents()
+    }
+
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> crate::ClipResult<()> {
+        self.inner_mut().set_contents_from(reader)
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> crate::ClipResult<()> {
+        self.inner_mut().get_contents_to(writer)
+    }
+
+    fn get_content(&mut self) -> crate::ClipResult<ClipboardContent> {
+        self.inner_mut().get_content()
+    }
+
+    fn set_content(&mut self, content: ClipboardContent) -> crate::ClipResult<()> {
+        self.inner_mut().set_content(content)
+    }
+}
+
+/// Cloneable handle to a clipboard context shared between threads.
+///
+/// Wraps the context in a reference counted lock. Clones share the same context, so multiple
+/// subsystems, such as a UI thread and a background worker, can use it without each constructing
+/// their own backend. Each operation takes the lock, use [`lock`](SharedClipboardContext::lock)
+/// to run multiple operations without other handles interleaving.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::thread;
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::shared::SharedClipboardContext;
+/// use copypasta_ext::testing::DryRunClipboardContext;
+///
+/// let mut ctx = SharedClipboardContext::new(DryRunClipboardContext::new());
+/// let mut worker_ctx = ctx.clone();
+/// thread::spawn(move || worker_ctx.set_contents("some string".into()).unwrap())
+///     .join()
+///     .unwrap();
+/// assert_eq!(ctx.get_contents().unwrap(), "some string");
+/// ```
+pub struct SharedClipboardContext<C>(Arc<Mutex<C>>)
+where
+    C: ClipboardProvider;
+
+impl<C> SharedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context.
+    pub fn new(context: C) -> Self {
+        Self(Arc::new(Mutex::new(context)))
+    }
+
+    /// Lock the inner context, ignoring poisoning.
+    ///
+    /// Other handles block on operations until the returned guard is dropped.
+    pub fn lock(&self) -> MutexGuard<'_, C> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Unwrap, returning the inner clipboard context if this is the only handle.
+    ///
+    /// Returns this handle as error if other handles exist.
+    pub fn try_into_inner(self) -> Result<C, Self> {
+        match Arc::try_unwrap(self.0) {
+            Ok(context) => Ok(context.into_inner().unwrap_or_else(PoisonError::into_inner)),
+            Err(shared) => Err(Self(shared)),
+        }
+    }
+}
+
+impl<C> Clone for SharedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<C> ClipboardProvider for SharedClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.lock().get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.lock().set_contents(contents)
+    }
+}
+
+impl<C> ClipboardProviderExt for SharedClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.lock().display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.lock().content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.lock().requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.lock().thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.lock().selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.lock().change_token()
+    }
+
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        self.lock().content_hash()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.lock().is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.lock().available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.lock().persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.lock().clear_contents()
+    }
+
+    fn swap_contents(&mut self, contents: String) -> crate::ClipResult<String> {
+        self.lock().swap_contents(contents)
+    }
+
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> crate::ClipResult<()> {
+        self.lock().set_contents_from(reader)
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> crate::ClipResult<()> {
+        self.lock().get_contents_to(writer)
+    }
+
+    fn get_content(&mut self) -> crate::ClipResult<ClipboardContent> {
+        self.lock().get_content()
+    }
+
+    fn set_content(&mut self, content: ClipboardContent) -> crate::ClipResult<()> {
+        self.lock().set_content(content)
+    }
+}
@@ -0,0 +1,192 @@
+//! Clean up the clipboard when the process is interrupted.
+//!
+//! Pressing `Ctrl+C` kills the process without running destructors or exit handlers. Secrets set
+//! through [`ClearOnExit`][ClearOnExit] are left on the clipboard, and contents set through
+//! contexts that serve them from this process are lost.
+//!
+//! [`install`] sets up handlers for `SIGINT`, `SIGTERM` and `SIGHUP`. When one is received, all
+//! pending [`ClearOnExit`][ClearOnExit] contents are cleared, contexts registered with
+//! [`persist_on_signal`] are [persisted](ClipboardProviderExt::persist), and the process is then
+//! terminated by the same signal as before.
+//!
+//! Applications with their own signal handling should not call [`install`], and should call
+//! [`cleanup`] from their handler instead.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::guard::ClearOnExit;
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! copypasta_ext::signal::install().unwrap();
+//!
+//! let ctx = ClipboardContext::new().unwrap();
+//! let _clear = ClearOnExit::set(ctx, "123456".into()).unwrap();
+//!
+//! // Cleared even if the user presses Ctrl+C here
+//! std::thread::sleep(std::time::Duration::from_secs(30));
+//! ```
+//!
+//! [ClearOnExit]: ../guard/struct.ClearOnExit.html
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::Error as IoError;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+use once_cell::sync::{Lazy, OnceCell};
+
+use crate::prelude::*;
+
+/// Signals to clean up on.
+const SIGNALS: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+/// Write end of the pipe signal handlers report received signals through.
+static PIPE_WRITE: AtomicI32 = AtomicI32::new(-1);
+
+/// Set once the signal handlers are installed.
+static INSTALLED: OnceCell<()> = OnceCell::new();
+
+/// Contexts to persist when a signal is received.
+static PERSIST: Lazy<Mutex<Vec<Box<dyn ClipboardProviderExt>>>> = Lazy::new(Default::default);
+
+/// Install handlers for `SIGINT`, `SIGTERM` and `SIGHUP` that clean up the clipboard.
+///
+/// After cleaning up, the default signal action is restored and the signal is raised again, so
+/// the process terminates as it otherwise would have. Installing more than once has no effect.
+///
+/// This replaces any handlers installed before for these signals. If your application handles
+/// them itself, call [`cleanup`] from your handler instead.
+pub fn install() -> crate::ClipResult<()> {
+    INSTALLED.get_or_try_init(install_handlers)?;
+    Ok(())
+}
+
+/// Persist the given context when the process is interrupted by a signal.
+///
+/// Use this for contexts that serve their contents from this process, such as native X11 and
+/// Wayland contexts, so contents are kept available after `Ctrl+C`. See
+/// [`persist`](ClipboardProviderExt::persist).
+pub fn persist_on_signal<C>(context: C)
+where
+    C: ClipboardProviderExt + 'static,
+{
+    lock(&PERSIST).push(Box::new(context));
+}
+
+/// Clean up the clipboard, for a process about to be terminated.
+///
+/// Clears all pending [`ClearOnExit`][ClearOnExit] contents, and persists all contexts registered
+/// through [`persist_on_signal`]. Called by the handlers set up by [`install`], call it from your
+/// own signal handling otherwise. This is not async-signal-safe, don't call it from within a raw
+/// signal handler.
+///
+/// [ClearOnExit]: ../guard/struct.ClearOnExit.html
+pub fn cleanup() {
+    crate::guard::clear_all();
+    let contexts: Vec<_> = lock(&PERSIST).drain(..).collect();
+    for mut context in contexts {
+        let _ = context.persist();
+    }
+}
+
+/// Install the signal handlers, along with the thread cleaning up on signals.
+fn install_handlers() -> Result<(), Error> {
+    let mut fds = [0 as libc::c_int; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(Error::Pipe(IoError::last_os_error()));
+    }
+    let [read, write] = fds;
+    PIPE_WRITE.store(write, Ordering::SeqCst);
+
+    thread::Builder::new()
+        .name("copypasta-ext-signal".into())
+        .spawn(move || handle_signals(read))
+        .map_err(Error::Pipe)?;
+
+    for &signal in &SIGNALS {
+        let mut action = unsafe { MaybeUninit::<libc::sigaction>::zeroed().assume_init() };
+        action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        unsafe { libc::sigemptyset(&mut action.sa_mask) };
+        if unsafe { libc::sigaction(signal, &action, std::ptr::null_mut()) } != 0 {
+            return Err(Error::Handler(IoError::last_os_error()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Signal handler, reports the signal to the cleanup thread.
+///
+/// Only writes to a pipe, as that is async-signal-safe.
+extern "C" fn on_signal(signal: libc::c_int) {
+    let byte = signal as u8;
+    unsafe {
+        libc::write(
+            PIPE_WRITE.load(Ordering::SeqCst),
+            &byte as *const u8 as *const libc::c_void,
+            1,
+        )
+    };
+}
+
+/// Wait for signals reported through the given pipe, clean up and re-raise the first.
+fn handle_signals(read: libc::c_int) {
+    let mut byte = 0u8;
+    loop {
+        let n = unsafe { libc::read(read, &mut byte as *mut u8 as *mut libc::c_void, 1) };
+        if n == 1 {
+            break;
+        }
+        if n < 0 && IoError::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+            continue;
+        }
+        return;
+    }
+
+    cleanup();
+
+    let signal = libc::c_int::from(byte);
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+/// Lock the given mutex, ignoring poisoning.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Represents signal handling related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to set up the pipe or thread to handle signals with.
+    Pipe(IoError),
+
+    /// Failed to install a signal handler.
+    Handler(IoError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Pipe(err) => write!(f, "Failed to set up signal handling: {}", err),
+            Error::Handler(err) => write!(f, "Failed to install signal handler: {}", err),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Pipe(err) | Error::Handler(err) => Some(err),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Stream clipboard contents from and to files and writers.
+//!
+//! Convenience functions on top of the streaming methods of [`ClipboardProviderExt`], so large
+//! contents don't have to be read into a string first where the clipboard context supports
+//! streaming.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::stream;
+//! use copypasta_ext::x11_bin::X11BinClipboardContext;
+//!
+//! let mut ctx = X11BinClipboardContext::new().unwrap();
+//! stream::copy_file_contents(&mut ctx, "/var/log/app.log").unwrap();
+//! stream::paste_into(&mut ctx, std::io::stdout()).unwrap();
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Error as IoError, Write};
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+
+/// Copy the contents of the file at the given path to the clipboard.
+///
+/// The file is streamed into the clipboard through
+/// [`set_contents_from`](ClipboardProviderExt::set_contents_from), which avoids reading it into a
+/// string first on clipboard contexts supporting streaming. Errors with [`Error::Open`] if the
+/// file could not be opened.
+pub fn copy_file_contents<C, P>(context: &mut C, path: P) -> crate::ClipResult<()>
+where
+    C: ClipboardProviderExt + ?Sized,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|err| Error::Open(path.into(), err))?;
+    context.set_contents_from(&mut BufReader::new(file))
+}
+
+/// Paste the clipboard contents into the given writer, such as stdout or a file.
+///
+/// The contents are streamed into the writer through
+/// [`get_contents_to`](ClipboardProviderExt::get_contents_to), which avoids reading them into a
+/// string first on clipboard contexts supporting streaming. The writer is flushed afterwards.
+///
+/// ```rust
+/// use copypasta_ext::stream;
+/// use copypasta_ext::testing::DryRunClipboardContext;
+///
+/// let mut ctx = DryRunClipboardContext::with_contents("some string");
+/// let mut out = Vec::new();
+/// stream::paste_into(&mut ctx, &mut out).unwrap();
+/// assert_eq!(out, b"some string");
+/// ```
+pub fn paste_into<C, W>(context: &mut C, writer: W) -> crate::ClipResult<()>
+where
+    C: ClipboardProviderExt + ?Sized,
+    W: Write,
+{
+    let mut writer = BufWriter::new(writer);
+    context.get_contents_to(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Represents clipboard streaming related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to open the file at the given path.
+    Open(PathBuf, IoError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Open(path, err) => {
+                write!(f, "Failed to open file '{}': {}", path.display(), err)
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Open(_, err) => Some(err),
+        }
+    }
+}
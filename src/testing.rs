@@ -0,0 +1,259 @@
+//! Clipboard contexts for testing.
+//!
+//! These contexts don't touch any real clipboard, and allow testing code that uses the clipboard
+//! deterministically.
+//!
+//! - [`ScriptedClipboardContext`]: pre-programmed results for each get and set, to test retry
+//!   and fallback logic around clipboard failures
+//! - [`DryRunClipboardContext`]: records all operations, useful for `--dry-run` modes and to
+//!   assert what would have been copied
+
+use std::collections::VecDeque;
+use std::error::Error as StdError;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+
+/// Scripted clipboard context with pre-programmed results.
+///
+/// The result of each get and set call is programmed up front, in order. A call may be delayed
+/// to simulate a slow clipboard. Calls that weren't programmed return [`Error::Unscripted`].
+///
+/// The contents of each set call are recorded, and may be inspected afterwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::testing::ScriptedClipboardContext;
+///
+/// let mut ctx = ScriptedClipboardContext::new()
+///     .on_get(Err("clipboard owner did not respond".into()))
+///     .on_get_delayed(Duration::from_millis(10), Ok("some string".into()))
+///     .on_set(Ok(()));
+///
+/// assert!(ctx.get_contents().is_err());
+/// assert_eq!(ctx.get_contents().unwrap(), "some string");
+/// ctx.set_contents("other string".into()).unwrap();
+/// assert!(ctx.set_contents("unscripted".into()).is_err());
+///
+/// assert_eq!(ctx.set_calls(), ["other string", "unscripted"]);
+/// assert!(ctx.is_done());
+/// ```
+#[derive(Default)]
+pub struct ScriptedClipboardContext {
+    gets: VecDeque<Step<String>>,
+    sets: VecDeque<Step<()>>,
+    set_calls: Vec<String>,
+    display_server: Option<DisplayServer>,
+}
+
+/// A scripted call result, with optional delay.
+struct Step<T> {
+    delay: Option<Duration>,
+    result: crate::ClipResult<T>,
+}
+
+impl<T> Step<T> {
+    /// Wait for the delay, then return the result.
+    fn run(self) -> crate::ClipResult<T> {
+        if let Some(delay) = self.delay {
+            thread::sleep(delay);
+        }
+        self.result
+    }
+}
+
+impl ScriptedClipboardContext {
+    /// Construct without any scripted calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Script the result of the next unscripted get call.
+    pub fn on_get(self, result: crate::ClipResult<String>) -> Self {
+        self.script_get(None, result)
+    }
+
+    /// Script the result of the next unscripted get call, returned after the given delay.
+    pub fn on_get_delayed(self, delay: Duration, result: crate::ClipResult<String>) -> Self {
+        self.script_get(Some(delay), result)
+    }
+
+    /// Script the result of the next unscripted set call.
+    pub fn on_set(self, result: crate::ClipResult<()>) -> Self {
+        self.script_set(None, result)
+    }
+
+    /// Script the result of the next unscripted set call, returned after the given delay.
+    pub fn on_set_delayed(self, delay: Duration, result: crate::ClipResult<()>) -> Self {
+        self.script_set(Some(delay), result)
+    }
+
+    /// Set the display server this context reports.
+    pub fn with_display_server(mut self, display_server: Option<DisplayServer>) -> Self {
+        self.display_server = display_server;
+        self
+    }
+
+    fn script_get(mut self, delay: Option<Duration>, result: crate::ClipResult<String>) -> Self {
+        self.gets.push_back(Step { delay, result });
+        self
+    }
+
+    fn script_set(mut self, delay: Option<Duration>, result: crate::ClipResult<()>) -> Self {
+        self.sets.push_back(Step { delay, result });
+        self
+    }
+
+    /// Contents passed to each set call so far, in order, including failed calls.
+    pub fn set_calls(&self) -> &[String] {
+        &self.set_calls
+    }
+
+    /// Whether all scripted calls have been made.
+    pub fn is_done(&self) -> bool {
+        self.gets.is_empty() && self.sets.is_empty()
+    }
+}
+
+impl ClipboardProvider for ScriptedClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        match self.gets.pop_front() {
+            Some(step) => step.run(),
+            None => Err(Error::Unscripted("get").into()),
+        }
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.set_calls.push(contents);
+        match self.sets.pop_front() {
+            Some(step) => step.run(),
+            None => Err(Error::Unscripted("set").into()),
+        }
+    }
+}
+
+impl ClipboardProviderExt for ScriptedClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.display_server
+    }
+}
+
+/// Dry run clipboard context, recording all operations.
+///
+/// Never touches a real clipboard. Set contents are recorded and returned by subsequent gets,
+/// starting with the canned contents it was constructed with.
+///
+/// # Examples
+///
+/// ```rust
+/// use copypasta_ext::prelude::*;
+/// use copypasta_ext::testing::{DryRunClipboardContext, Operation};
+///
+/// let mut ctx = DryRunClipboardContext::with_contents("canned");
+/// assert_eq!(ctx.get_contents().unwrap(), "canned");
+/// ctx.set_contents("some string".into()).unwrap();
+/// assert_eq!(ctx.get_contents().unwrap(), "some string");
+///
+/// assert_eq!(
+///     ctx.operations(),
+///     [
+///         Operation::Get,
+///         Operation::Set("some string".into()),
+///         Operation::Get,
+///     ]
+/// );
+/// ```
+#[derive(Default)]
+pub struct DryRunClipboardContext {
+    contents: String,
+    operations: Vec<Operation>,
+}
+
+impl DryRunClipboardContext {
+    /// Construct with empty contents.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct with the given canned contents.
+    pub fn with_contents<S>(contents: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            contents: contents.into(),
+            operations: Vec::new(),
+        }
+    }
+
+    /// All operations so far, in order.
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Contents of the last set operation, if any.
+    pub fn last_set(&self) -> Option<&str> {
+        self.operations.iter().rev().find_map(|op| match op {
+            Operation::Set(contents) => Some(contents.as_str()),
+            _ => None,
+        })
+    }
+}
+
+impl ClipboardProvider for DryRunClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.operations.push(Operation::Get);
+        Ok(self.contents.clone())
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.operations.push(Operation::Set(contents.clone()));
+        self.contents = contents;
+        Ok(())
+    }
+}
+
+impl ClipboardProviderExt for DryRunClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        None
+    }
+}
+
+/// A recorded clipboard operation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Operation {
+    /// Clipboard contents were get.
+    Get,
+
+    /// Clipboard contents were set, holds the contents.
+    Set(String),
+}
+
+/// Represents testing clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A clipboard operation was called more often than it was scripted for, holds the operation.
+    Unscripted(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unscripted(op) => write!(f, "No scripted result left for clipboard {}", op),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
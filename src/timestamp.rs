@@ -0,0 +1,212 @@
+//! Track when clipboard contents were set.
+//!
+//! [`TimestampClipboardContext`] wraps any clipboard context, and records when contents are set
+//! through it. It tells when the current contents were set, or that they changed since, which is
+//! useful for auto-clear logic and for history views showing "copied 30 seconds ago".
+//!
+//! Only sets through the wrapper are known. None of the supported platforms expose the time
+//! contents were set by other applications as wall-clock time.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::testing::DryRunClipboardContext;
+//! use copypasta_ext::timestamp::TimestampClipboardContext;
+//!
+//! let mut ctx = TimestampClipboardContext::new(DryRunClipboardContext::new());
+//! ctx.set_contents("some string".into()).unwrap();
+//! assert!(ctx.age().unwrap().unwrap() < Duration::from_secs(30));
+//!
+//! // Contents changed by someone else
+//! ctx.inner_mut().set_contents("other string".into()).unwrap();
+//! assert_eq!(ctx.age().unwrap(), None);
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::time::{Duration, SystemTime};
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ClipboardProviderNew, ContentLifetime, Selection, ThreadAffinity};
+
+/// A set through the wrapper.
+#[derive(Copy, Clone, Debug)]
+struct SetRecord {
+    /// When the contents were set.
+    at: SystemTime,
+
+    /// Hash of the set contents, to detect whether they changed since.
+    hash: u64,
+}
+
+/// Record when contents are set through the wrapped context.
+///
+/// See module documentation for more information.
+pub struct TimestampClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    context: C,
+    last_set: Option<SetRecord>,
+}
+
+impl<C> TimestampClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    /// Wrap the given clipboard context.
+    pub fn new(context: C) -> Self {
+        Self {
+            context,
+            last_set: None,
+        }
+    }
+
+    /// When contents were last set through this context.
+    ///
+    /// The contents may have changed since, use [`set_at`](Self::set_at) to only get the time if
+    /// they didn't.
+    pub fn last_set(&self) -> Option<SystemTime> {
+        self.last_set.map(|record| record.at)
+    }
+
+    /// Get a reference to the inner clipboard context.
+    pub fn inner(&self) -> &C {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context.
+    ///
+    /// Contents set through it are not recorded.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> C {
+        self.context
+    }
+}
+
+impl<C> TimestampClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    /// When the current contents were set.
+    ///
+    /// Returns `None` if the current contents weren't set through this context, such as when they
+    /// were changed by another application since. The current contents are compared by their
+    /// [hash](ClipboardProviderExt::content_hash), so they must read back exactly as set.
+    pub fn set_at(&mut self) -> crate::ClipResult<Option<SystemTime>> {
+        let record = match self.last_set {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        if self.context.content_hash()? != record.hash {
+            return Ok(None);
+        }
+        Ok(Some(record.at))
+    }
+
+    /// How long ago the current contents were set.
+    ///
+    /// Returns `None` if the current contents weren't set through this context, see
+    /// [`set_at`](Self::set_at).
+    pub fn age(&mut self) -> crate::ClipResult<Option<Duration>> {
+        Ok(self.set_at()?.map(|at| at.elapsed().unwrap_or_default()))
+    }
+}
+
+impl<C> ClipboardProvider for TimestampClipboardContext<C>
+where
+    C: ClipboardProvider,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.context.get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        let hash = hash(&contents);
+        self.context.set_contents(contents)?;
+        self.last_set = Some(SetRecord {
+            at: SystemTime::now(),
+            hash,
+        });
+        Ok(())
+    }
+}
+
+impl<C> ClipboardProviderNew for TimestampClipboardContext<C>
+where
+    C: ClipboardProviderNew,
+{
+    fn new() -> crate::ClipResult<Self> {
+        Ok(TimestampClipboardContext::new(C::new()?))
+    }
+}
+
+/// Setting rich contents or from a stream is not forwarded to the inner context, so the set
+/// contents are known.
+impl<C> ClipboardProviderExt for TimestampClipboardContext<C>
+where
+    C: ClipboardProviderExt,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn selection(&self) -> Selection {
+        self.context.selection()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.context.change_token()
+    }
+
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        self.context.content_hash()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.context.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        self.context.available_mime_types()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.context.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        self.context.clear_contents()?;
+        self.last_set = Some(SetRecord {
+            at: SystemTime::now(),
+            hash: hash(""),
+        });
+        Ok(())
+    }
+}
+
+/// Hash the given contents, matching [`ClipboardProviderExt::content_hash`].
+fn hash(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(contents.as_bytes());
+    hasher.finish()
+}
@@ -0,0 +1,316 @@
+//! Parse URLs and file paths from clipboard contents.
+//!
+//! Copying a link or files puts them on the clipboard as text, in various forms. [`parse_url`]
+//! and [`parse_file_paths`] extract them, handling the common forms:
+//!
+//! - a single URL, such as copied from a browser address bar
+//! - `text/uri-list` contents, one URI per line with `#` comments, such as copied files
+//! - GNOME file manager contents, prefixed with a `copy` or `cut` line
+//! - `file://` URIs with percent-encoding, including Windows forms such as `file:///C:/dir`
+//! - plain absolute paths, including Windows drive and UNC paths
+//!
+//! See [`get_url`](crate::ClipboardProviderExt::get_url) and
+//! [`get_file_paths`](crate::ClipboardProviderExt::get_file_paths) to get them from a clipboard
+//! context directly. [`format_file_paths`] does the reverse, to put files on the clipboard.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::path::PathBuf;
+//! use copypasta_ext::uri;
+//!
+//! assert_eq!(uri::parse_url(" https://example.com/\n"), Some("https://example.com/"));
+//! assert_eq!(uri::parse_url("some string"), None);
+//!
+//! let paths = uri::parse_file_paths("# copied\r\nfile:///tmp/a%20b.txt\r\nfile:///tmp/c.txt\r\n");
+//! assert_eq!(
+//!     paths,
+//!     Some(vec![PathBuf::from("/tmp/a b.txt"), PathBuf::from("/tmp/c.txt")]),
+//! );
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Parse a single URL from the given contents.
+///
+/// Surrounding whitespace and a `text/uri-list` comment are ignored. Returns `None` if the
+/// contents are anything other than a single absolute URL, such as plain text or a Windows path.
+///
+/// ```rust
+/// use copypasta_ext::uri::parse_url;
+///
+/// assert_eq!(parse_url("mailto:user@example.com"), Some("mailto:user@example.com"));
+/// assert_eq!(parse_url("C:\\Users"), None);
+/// ```
+pub fn parse_url(contents: &str) -> Option<&str> {
+    let mut lines = uri_list_lines(contents);
+    let url = lines.next()?;
+    if lines.next().is_some() || !is_url(url) {
+        return None;
+    }
+    Some(url)
+}
+
+/// Parse a list of file paths from the given contents.
+///
+/// Each line must hold a `file://` URI or an absolute path. Returns `None` if any line holds
+/// something else, or if there are no paths at all.
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use copypasta_ext::uri::parse_file_paths;
+///
+/// assert_eq!(
+///     parse_file_paths("copy\nfile:///home/user/a.txt"),
+///     Some(vec![PathBuf::from("/home/user/a.txt")]),
+/// );
+/// assert_eq!(
+///     parse_file_paths("file:///C:/Program%20Files"),
+///     Some(vec![PathBuf::from("C:/Program Files")]),
+/// );
+/// assert_eq!(parse_file_paths("https://example.com/"), None);
+/// ```
+pub fn parse_file_paths(contents: &str) -> Option<Vec<PathBuf>> {
+    let mut lines = uri_list_lines(contents).peekable();
+
+    // GNOME file managers prefix the list with the operation
+    if let Some(&"copy") | Some(&"cut") = lines.peek() {
+        lines.next();
+    }
+
+    let paths = lines
+        .map(|line| {
+            if is_url(line) {
+                file_uri_path(line)
+            } else if is_absolute_path(line) {
+                Some(PathBuf::from(line))
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if paths.is_empty() {
+        return None;
+    }
+    Some(paths)
+}
+
+/// Format the given file paths as `text/uri-list` contents.
+///
+/// Each path is written as `file://` URI on its own line, as understood by file managers and
+/// [`parse_file_paths`]. Paths should be absolute.
+///
+/// ```rust
+/// use copypasta_ext::uri::format_file_paths;
+///
+/// assert_eq!(
+///     format_file_paths(&["/tmp/a b.txt", "/tmp/c.txt"]),
+///     "file:///tmp/a%20b.txt\r\nfile:///tmp/c.txt\r\n",
+/// );
+/// ```
+pub fn format_file_paths<P>(paths: &[P]) -> String
+where
+    P: AsRef<Path>,
+{
+    paths
+        .iter()
+        .map(|path| file_uri(path.as_ref()) + "\r\n")
+        .collect()
+}
+
+/// Format the given absolute path as `file://` URI.
+///
+/// ```rust
+/// use std::path::Path;
+/// use copypasta_ext::uri::file_uri;
+///
+/// assert_eq!(file_uri(Path::new("/tmp/100%.txt")), "file:///tmp/100%25.txt");
+/// ```
+pub fn file_uri(path: &Path) -> String {
+    let path = path_to_bytes(path);
+    let mut uri = String::from("file://");
+    if is_drive_path(&path) {
+        uri.push('/');
+    } else if path.starts_with(b"//") {
+        // UNC paths hold the host, as in file://server/share
+        uri.push_str(&percent_encode(&path[2..]));
+        return uri;
+    }
+    uri.push_str(&percent_encode(&path));
+    uri
+}
+
+/// Iterate over the non-empty, non-comment lines of `text/uri-list` contents, trimmed.
+fn uri_list_lines(contents: &str) -> impl Iterator<Item = &str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Check whether the given string is an absolute URL, such as `https://example.com`.
+///
+/// Single letter schemes are not accepted, as they are Windows drive letters.
+fn is_url(url: &str) -> bool {
+    let (scheme, rest) = match url.split_once(':') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let mut chars = scheme.chars();
+    scheme.len() > 1
+        && chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+        && !rest.is_empty()
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Check whether the given string is an absolute Unix or Windows path.
+fn is_absolute_path(path: &str) -> bool {
+    path.starts_with('/') || path.starts_with("\\\\") || is_drive_path(path.as_bytes())
+}
+
+/// Check whether the given bytes start with a Windows drive, such as `C:\` or `C:/`.
+fn is_drive_path(path: &[u8]) -> bool {
+    path.len() >= 3
+        && path[0].is_ascii_alphabetic()
+        && path[1] == b':'
+        && (path[2] == b'/' || path[2] == b'\\')
+}
+
+/// Get the path a `file:` URI points to, `None` for other URIs.
+///
+/// URIs with a host other than `localhost` are returned as UNC path, such as `//server/share`.
+fn file_uri_path(uri: &str) -> Option<PathBuf> {
+    let rest = strip_prefix_ignore_case(uri, "file:")?;
+    let path = match rest.strip_prefix("//") {
+        Some(rest) => {
+            let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            if host.is_empty() || host.eq_ignore_ascii_case("localhost") {
+                path.to_string()
+            } else {
+                format!("//{}{}", host, path)
+            }
+        }
+        None => rest.to_string(),
+    };
+    let mut path = percent_decode(path.split(&['?', '#'][..]).next().unwrap_or_default());
+
+    // Windows drive paths are written as /C:/dir
+    if path.len() > 1 && path[0] == b'/' && is_drive_path(&path[1..]) {
+        path.remove(0);
+    }
+    if path.is_empty() {
+        return None;
+    }
+    Some(bytes_to_path(path))
+}
+
+/// Strip the given ASCII prefix, ignoring case.
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    match s.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => Some(&s[prefix.len()..]),
+        _ => None,
+    }
+}
+
+/// Decode percent-encoded bytes, invalid escapes are kept as-is.
+fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Percent-encode the given path bytes, keeping unreserved characters and separators.
+fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Get the bytes of a path, with `/` as separator.
+///
+/// Unix paths may hold any bytes, elsewhere invalid UTF-8 is replaced.
+#[cfg(unix)]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+/// Get the bytes of a path, with `/` as separator.
+///
+/// Unix paths may hold any bytes, elsewhere invalid UTF-8 is replaced.
+#[cfg(not(unix))]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().replace('\\', "/").into_bytes()
+}
+
+/// Build a path from decoded bytes.
+///
+/// Unix paths may hold any bytes, elsewhere invalid UTF-8 is replaced.
+#[cfg(unix)]
+fn bytes_to_path(bytes: Vec<u8>) -> PathBuf {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+/// Build a path from decoded bytes.
+///
+/// Unix paths may hold any bytes, elsewhere invalid UTF-8 is replaced.
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Represents URL and file path parsing related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The clipboard does not hold a single URL.
+    NoUrl,
+
+    /// The clipboard does not hold a list of file paths.
+    NoFilePaths,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoUrl => write!(f, "Clipboard contents are not a URL"),
+            Error::NoFilePaths => write!(f, "Clipboard contents are not a list of file paths"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
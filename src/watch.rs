@@ -0,0 +1,655 @@
+//! Watch clipboard contents for changes.
+//!
+//! A [`Watcher`] monitors a clipboard context on a background thread, and emits a
+//! [`ClipboardEvent`] each time the clipboard contents change. Receive events from the watcher,
+//! or pass them to callbacks through [`Watcher::subscribe`].
+//!
+//! The clipboard is polled at a fixed interval. Each poll fetches the clipboard contents, so keep
+//! the interval reasonable to not put unnecessary load on the system. Use
+//! [`Watcher::new_token`] to poll the cheap [change token](crate::ChangeToken) instead where the
+//! system provides one, such as the pasteboard change count on macOS, and only fetch contents
+//! when it changes. On X11, use [`Watcher::new_x11`] to be notified of changes by the X server
+//! through the XFIXES extension instead of polling, or [`Watcher::new_wayland`] to be notified by
+//! the Wayland compositor. [`Watcher::new_auto`] picks the best way available for a context.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use copypasta_ext::watch::{ClipboardEvent, Watcher};
+//! use copypasta_ext::x11_bin::ClipboardContext;
+//!
+//! let watcher = Watcher::new(ClipboardContext::new().unwrap(), Duration::from_millis(500));
+//! for event in watcher.iter() {
+//!     match event {
+//!         ClipboardEvent::Changed(contents) => println!("Clipboard changed: {}", contents),
+//!         _ => {}
+//!     }
+//! }
+//! ```
+
+#[cfg(all(
+    any(feature = "x11-native", feature = "wayland-native"),
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+#[cfg(all(
+    any(feature = "x11-native", feature = "wayland-native"),
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+use std::os::unix::{
+    io::{AsRawFd, RawFd},
+    net::UnixStream,
+};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, Selection};
+
+/// How the watcher thread waits before checking for changes again.
+enum Wait {
+    /// Wait for the given interval.
+    Interval(Duration),
+
+    /// Wait until the given file descriptor is readable, such as the connection to the display
+    /// server delivering selection events.
+    #[cfg(all(
+        any(feature = "x11-native", feature = "wayland-native"),
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    Readable(RawFd),
+}
+
+/// A clipboard event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClipboardEvent {
+    /// The clipboard contents changed, holds the new contents.
+    Changed(String),
+}
+
+/// Watch a clipboard context for changes.
+///
+/// Polls the clipboard context on a background thread. The thread is stopped when the watcher is
+/// dropped.
+///
+/// See module documentation for more information.
+pub struct Watcher {
+    events: Receiver<ClipboardEvent>,
+    subscribers: Arc<Mutex<Subscribers>>,
+    stop: Option<Sender<()>>,
+    #[cfg(all(
+        any(feature = "x11-native", feature = "wayland-native"),
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    stop_socket: Option<UnixStream>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Start watching the given clipboard context, polling at the given interval.
+    ///
+    /// Only changes after the watcher is started are emitted, the current clipboard contents are
+    /// not.
+    pub fn new<C>(context: C, interval: Duration) -> Self
+    where
+        C: ClipboardProvider + 'static,
+    {
+        let mut context = context;
+        let mut detector = ChangeDetector::new(&mut context);
+        Self::spawn(interval, move || detector.poll(&mut context))
+    }
+
+    /// Start watching the given clipboard context, polling its change token at the given interval.
+    ///
+    /// Uses [`change_token`](ClipboardProviderExt::change_token) to detect changes, and only
+    /// fetches the clipboard contents when the token changes. This is much cheaper on systems
+    /// providing a change token, such as macOS and Windows, allowing a short interval. Emits the
+    /// same events as [`new`](Watcher::new).
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use copypasta_ext::watch::Watcher;
+    ///
+    /// let ctx = copypasta_ext::try_context().expect("failed to get clipboard context");
+    /// let watcher = Watcher::new_token(ctx, Duration::from_millis(100));
+    /// println!("{:?}", watcher.recv());
+    /// ```
+    pub fn new_token<C>(context: C, interval: Duration) -> Self
+    where
+        C: ClipboardProviderExt + 'static,
+    {
+        Self::spawn(interval, token_poll(context))
+    }
+
+    /// Start watching the given clipboard context, the best way available.
+    ///
+    /// Uses native selection events where supported, like [`new_x11`](Watcher::new_x11) on X11
+    /// and [`new_wayland`](Watcher::new_wayland) on Wayland, based on the
+    /// [display server](ClipboardProviderExt::display_server) of the context. Falls back to
+    /// polling the change token at the given interval like [`new_token`](Watcher::new_token)
+    /// otherwise, such as for OSC 52 or on other platforms. This makes watching work everywhere.
+    ///
+    /// Native events are for the [selection](ClipboardProviderExt::selection) of the context,
+    /// such as the primary selection. The secondary selection is always polled.
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use copypasta_ext::watch::Watcher;
+    ///
+    /// let ctx = copypasta_ext::try_context().expect("failed to get clipboard context");
+    /// let watcher = Watcher::new_auto(ctx, Duration::from_millis(500));
+    /// println!("{:?}", watcher.recv());
+    /// ```
+    pub fn new_auto<C>(context: C, interval: Duration) -> Self
+    where
+        C: ClipboardProviderExt + 'static,
+    {
+        let display_server = context.display_server();
+        let selection = context.selection();
+        Self::spawn_with(move || {
+            let mut context = context;
+            let poll: (Wait, Box<dyn FnMut() -> Option<String>>) =
+                match native_events(display_server, selection) {
+                    Some((wait, mut changed)) => {
+                        let mut detector = ChangeDetector::new(&mut context);
+                        let poll = move || {
+                            if changed() {
+                                detector.poll(&mut context)
+                            } else {
+                                None
+                            }
+                        };
+                        (wait, Box::new(poll))
+                    }
+                    None => (Wait::Interval(interval), Box::new(token_poll(context))),
+                };
+            Ok(poll)
+        })
+        .expect("failed to start clipboard watcher")
+    }
+
+    /// Start watching the given X11 clipboard context, using XFIXES selection events.
+    ///
+    /// The X server notifies the watcher each time the selection of the context, such as
+    /// `CLIPBOARD` or `PRIMARY`, gets a new owner. Clipboard contents are only fetched then, so
+    /// changes are emitted near instantly without polling the clipboard. Emits the same events as
+    /// [`new`](Watcher::new).
+    ///
+    /// Fails if the X server could not be reached, or if it doesn't support XFIXES.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::watch::Watcher;
+    /// use copypasta_ext::x11_bin::{Primary, X11BinClipboardContext};
+    ///
+    /// let ctx: X11BinClipboardContext<Primary> = X11BinClipboardContext::new_selection().unwrap();
+    /// let watcher = Watcher::new_x11(ctx).unwrap();
+    /// println!("{:?}", watcher.recv());
+    /// ```
+    #[cfg(all(
+        feature = "x11-native",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    pub fn new_x11<C>(context: C) -> crate::ClipResult<Self>
+    where
+        C: crate::x11_native::X11ClipboardProviderExt + ClipboardProvider + 'static,
+    {
+        let events = crate::x11_native::SelectionEvents::new::<C::Selection>()?;
+        let mut context = context;
+        let mut detector = ChangeDetector::new(&mut context);
+        Self::spawn_with(move || {
+            Ok((Wait::Readable(events.as_raw_fd()), move || {
+                match events.changed() {
+                    Ok(true) => detector.poll(&mut context),
+                    Ok(false) | Err(_) => None,
+                }
+            }))
+        })
+    }
+
+    /// Start watching the regular Wayland clipboard, using data-control selection events.
+    ///
+    /// The compositor notifies the watcher each time the clipboard is set. Clipboard contents
+    /// are only fetched from the given context then, so changes are emitted near instantly
+    /// without polling the clipboard. Emits the same events as [`new`](Watcher::new).
+    ///
+    /// Fails if the compositor could not be reached, or if it doesn't support the
+    /// [`wlr-data-control`](crate::wayland_native) protocol.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::watch::Watcher;
+    /// use copypasta_ext::wayland_bin::WaylandBinClipboardContext;
+    ///
+    /// let ctx = WaylandBinClipboardContext::new().unwrap();
+    /// let watcher = Watcher::new_wayland(ctx).unwrap();
+    /// println!("{:?}", watcher.recv());
+    /// ```
+    #[cfg(all(
+        feature = "wayland-native",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    pub fn new_wayland<C>(context: C) -> crate::ClipResult<Self>
+    where
+        C: ClipboardProvider + 'static,
+    {
+        Self::wayland(context, false)
+    }
+
+    /// Start watching the Wayland primary selection, using data-control selection events.
+    ///
+    /// Like [`new_wayland`](Watcher::new_wayland), but watches the primary selection. The given
+    /// context must get the primary selection contents. Fails if the compositor doesn't support
+    /// primary selection through data-control.
+    #[cfg(all(
+        feature = "wayland-native",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    pub fn new_wayland_primary<C>(context: C) -> crate::ClipResult<Self>
+    where
+        C: ClipboardProvider + 'static,
+    {
+        Self::wayland(context, true)
+    }
+
+    /// Start watching the regular Wayland clipboard or primary selection.
+    #[cfg(all(
+        feature = "wayland-native",
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    fn wayland<C>(context: C, primary: bool) -> crate::ClipResult<Self>
+    where
+        C: ClipboardProvider + 'static,
+    {
+        // The Wayland connection can't be sent, create it on the watcher thread
+        Self::spawn_with(move || {
+            let mut events = crate::wayland_native::SelectionEvents::new(primary)?;
+            let mut context = context;
+            let mut detector = ChangeDetector::new(&mut context);
+            Ok((Wait::Readable(events.as_raw_fd()), move || {
+                match events.changed() {
+                    Ok(true) => detector.poll(&mut context),
+                    Ok(false) | Err(_) => None,
+                }
+            }))
+        })
+    }
+
+    /// Spawn the watcher thread, calling `poll` each interval to check for new contents.
+    fn spawn<F>(interval: Duration, poll: F) -> Self
+    where
+        F: FnMut() -> Option<String> + Send + 'static,
+    {
+        Self::spawn_with(move || Ok((Wait::Interval(interval), poll)))
+            .expect("failed to start clipboard watcher")
+    }
+
+    /// Spawn the watcher thread, calling `init` on it to build the `poll` function and how to
+    /// wait in between.
+    ///
+    /// Allows `poll` to hold state that can't be sent between threads. Waits for `init` to
+    /// complete, and returns its error if it fails. When waiting for a file descriptor, `poll`
+    /// is called once before the first wait, and must handle everything pending so the file
+    /// descriptor isn't readable anymore.
+    fn spawn_with<I, F>(init: I) -> crate::ClipResult<Self>
+    where
+        I: FnOnce() -> crate::ClipResult<(Wait, F)> + Send + 'static,
+        F: FnMut() -> Option<String>,
+    {
+        let (events_tx, events) = mpsc::channel();
+        let (stop, stop_rx) = mpsc::channel();
+        let (ready_tx, ready) = mpsc::channel();
+        let subscribers = Arc::new(Mutex::new(Subscribers::default()));
+        let thread_subscribers = subscribers.clone();
+
+        // Closing the stop socket wakes the thread while it waits for a file descriptor
+        #[cfg(all(
+            any(feature = "x11-native", feature = "wayland-native"),
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        ))]
+        let (stop_socket, stop_socket_rx) = UnixStream::pair()?;
+
+        let thread = thread::spawn(move || {
+            let (wait, mut poll) = match init() {
+                Ok(init) => {
+                    let _ = ready_tx.send(Ok(()));
+                    init
+                }
+                Err(err) => {
+                    let _ = ready_tx.send(Err(err));
+                    return;
+                }
+            };
+
+            // Emit changed contents, returns false if the receiver disconnected
+            let emit = |contents: Option<String>| {
+                let event = match contents {
+                    Some(contents) => ClipboardEvent::Changed(contents),
+                    None => return true,
+                };
+
+                // Pass to subscribers if there are any, send to receiver otherwise
+                let mut subscribers = thread_subscribers
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                if subscribers.callbacks.is_empty() {
+                    return events_tx.send(event).is_ok();
+                }
+                for (_, callback) in subscribers.callbacks.iter_mut() {
+                    callback(event.clone());
+                }
+                true
+            };
+
+            match wait {
+                // Poll each interval, until stopped or disconnected
+                Wait::Interval(interval) => {
+                    while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                        if !emit(poll()) {
+                            break;
+                        }
+                    }
+                }
+
+                // Poll each time the file descriptor is readable, until stopped or disconnected
+                #[cfg(all(
+                    any(feature = "x11-native", feature = "wayland-native"),
+                    unix,
+                    not(any(
+                        target_os = "macos",
+                        target_os = "android",
+                        target_os = "emscripten"
+                    ))
+                ))]
+                Wait::Readable(fd) => while emit(poll()) && wait_readable(fd, &stop_socket_rx) {},
+            }
+        });
+
+        // Disconnects without result if initialization panicked
+        let result = ready.recv().map_err(|err| err.into()).and_then(|r| r);
+        if let Err(err) = result {
+            let _ = thread.join();
+            return Err(err);
+        }
+
+        Ok(Self {
+            events,
+            subscribers,
+            stop: Some(stop),
+            #[cfg(all(
+                any(feature = "x11-native", feature = "wayland-native"),
+                unix,
+                not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+            ))]
+            stop_socket: Some(stop_socket),
+            thread: Some(thread),
+        })
+    }
+
+    /// Subscribe to clipboard events with a callback.
+    ///
+    /// The callback is invoked on the watcher thread for each event, until unsubscribed through
+    /// the returned [`Subscription`] or until the watcher is dropped. This is useful for
+    /// applications that don't want to run their own receive loop.
+    ///
+    /// While there are any subscribers, events are passed to them only, and are not available
+    /// through [`recv`](Watcher::recv) and related methods. Callbacks must not subscribe or
+    /// unsubscribe themselves, as this would deadlock.
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use copypasta_ext::watch::{ClipboardEvent, Watcher};
+    /// use copypasta_ext::x11_bin::ClipboardContext;
+    ///
+    /// let watcher = Watcher::new(ClipboardContext::new().unwrap(), Duration::from_millis(500));
+    /// let subscription = watcher.subscribe(|event| match event {
+    ///     ClipboardEvent::Changed(contents) => println!("Clipboard changed: {}", contents),
+    ///     _ => {}
+    /// });
+    ///
+    /// // Later, stop receiving events
+    /// subscription.unsubscribe();
+    /// ```
+    pub fn subscribe<F>(&self, callback: F) -> Subscription
+    where
+        F: FnMut(ClipboardEvent) + Send + 'static,
+    {
+        let mut subscribers = self.lock_subscribers();
+        let id = subscribers.next_id;
+        subscribers.next_id += 1;
+        subscribers.callbacks.push((id, Box::new(callback)));
+        Subscription {
+            id,
+            subscribers: Arc::downgrade(&self.subscribers),
+        }
+    }
+
+    /// Lock the subscribers, ignoring poisoning.
+    fn lock_subscribers(&self) -> MutexGuard<'_, Subscribers> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wait for the next clipboard event.
+    ///
+    /// Returns `None` if the watcher stopped.
+    pub fn recv(&self) -> Option<ClipboardEvent> {
+        self.events.recv().ok()
+    }
+
+    /// Wait for the next clipboard event, up to the given timeout.
+    ///
+    /// Returns `None` if no event was received in time, or if the watcher stopped.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<ClipboardEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Get the next clipboard event if there is any, without blocking.
+    pub fn try_recv(&self) -> Option<ClipboardEvent> {
+        match self.events.try_recv() {
+            Ok(event) => Some(event),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Iterate over clipboard events, blocking while waiting for the next.
+    pub fn iter(&self) -> impl Iterator<Item = ClipboardEvent> + '_ {
+        self.events.iter()
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        // Dropping the stop sender disconnects, making the thread exit
+        self.stop.take();
+        #[cfg(all(
+            any(feature = "x11-native", feature = "wayland-native"),
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        ))]
+        self.stop_socket.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Handle to a watcher subscription.
+///
+/// Returned by [`Watcher::subscribe`]. Dropping the handle does not unsubscribe, so it may be
+/// ignored if the subscription should last as long as the watcher.
+#[must_use = "dropping the subscription does not unsubscribe, use `unsubscribe` for that"]
+pub struct Subscription {
+    id: usize,
+    subscribers: Weak<Mutex<Subscribers>>,
+}
+
+impl Subscription {
+    /// Unsubscribe, the callback is not invoked anymore.
+    ///
+    /// Does nothing if the watcher was already dropped.
+    pub fn unsubscribe(self) {
+        if let Some(subscribers) = self.subscribers.upgrade() {
+            subscribers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .callbacks
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+/// A callback subscribed to watcher events.
+type Callback = Box<dyn FnMut(ClipboardEvent) + Send>;
+
+/// Callbacks subscribed to watcher events, by subscription ID.
+#[derive(Default)]
+struct Subscribers {
+    next_id: usize,
+    callbacks: Vec<(usize, Callback)>,
+}
+
+/// Build a poll function for the given context, fetching contents when its change token changes.
+fn token_poll<C>(context: C) -> impl FnMut() -> Option<String>
+where
+    C: ClipboardProviderExt,
+{
+    let mut context = context;
+    let mut token = context.change_token().ok();
+    let mut detector = ChangeDetector::new(&mut context);
+    move || {
+        let current: Option<ChangeToken> = context.change_token().ok();
+        if current.is_none() || current == token {
+            return None;
+        }
+        token = current;
+        detector.poll(&mut context)
+    }
+}
+
+/// Wait until `fd` is readable, or until the other end of `stop` is closed.
+///
+/// Returns `false` if stopped, or if `fd` failed such as when the display server went away.
+#[cfg(all(
+    any(feature = "x11-native", feature = "wayland-native"),
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+fn wait_readable(fd: RawFd, stop: &UnixStream) -> bool {
+    let mut fds = [
+        libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: stop.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    while unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } < 0 {
+        if IoError::last_os_error().kind() != IoErrorKind::Interrupted {
+            return false;
+        }
+    }
+    fds[1].revents == 0 && fds[0].revents & (libc::POLLERR | libc::POLLHUP | libc::POLLNVAL) == 0
+}
+
+/// Listen for native events of the given selection on the given display server.
+///
+/// Returns how to wait for events, and a function reporting whether the selection changed since
+/// the last check. Returns `None` if not supported, in which case the clipboard must be polled.
+#[allow(unused_variables)]
+fn native_events(
+    display_server: Option<DisplayServer>,
+    selection: Selection,
+) -> Option<(Wait, Box<dyn FnMut() -> bool>)> {
+    match display_server {
+        #[cfg(all(
+            feature = "x11-native",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        ))]
+        Some(DisplayServer::X11) => {
+            let events = match selection {
+                Selection::Clipboard => {
+                    crate::x11_native::SelectionEvents::new::<copypasta::x11_clipboard::Clipboard>()
+                }
+                Selection::Primary => {
+                    crate::x11_native::SelectionEvents::new::<copypasta::x11_clipboard::Primary>()
+                }
+                _ => return None,
+            }
+            .ok()?;
+            Some((
+                Wait::Readable(events.as_raw_fd()),
+                Box::new(move || events.changed().unwrap_or(false)),
+            ))
+        }
+        #[cfg(all(
+            feature = "wayland-native",
+            unix,
+            not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+        ))]
+        Some(DisplayServer::Wayland) => {
+            let mut events = match selection {
+                Selection::Clipboard => crate::wayland_native::SelectionEvents::new(false),
+                Selection::Primary => crate::wayland_native::SelectionEvents::new(true),
+                _ => return None,
+            }
+            .ok()?;
+            Some((
+                Wait::Readable(events.as_raw_fd()),
+                Box::new(move || events.changed().unwrap_or(false)),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Detect clipboard content changes by polling.
+///
+/// Remembers the last seen clipboard contents, and reports when new contents are different.
+/// Failing to get the clipboard contents, such as when the clipboard is empty, is not considered
+/// a change.
+pub(crate) struct ChangeDetector {
+    last: Option<String>,
+}
+
+impl ChangeDetector {
+    /// Construct detector, using the current clipboard contents as baseline.
+    pub fn new(context: &mut dyn ClipboardProvider) -> Self {
+        Self {
+            last: context.get_contents().ok(),
+        }
+    }
+
+    /// Poll the clipboard, returning the new contents if they changed.
+    pub fn poll(&mut self, context: &mut dyn ClipboardProvider) -> Option<String> {
+        let contents = context.get_contents().ok()?;
+        if self.last.as_ref() == Some(&contents) {
+            return None;
+        }
+        self.last = Some(contents.clone());
+        Some(contents)
+    }
+
+    /// Assume the clipboard now holds the given contents, so they aren't reported as change.
+    pub fn assume(&mut self, contents: String) {
+        self.last = Some(contents);
+    }
+}
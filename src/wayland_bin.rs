@@ -7,7 +7,10 @@
 //! [wl-clipboard][wl-clipboard] clipboard manager.
 //!
 //! The `wl-copy` or `wl-paste` must be in `PATH`. Alternatively the paths of either may be set at
-//! compile time using the `WL_COPY_PATH` and `WL_PASTE_PATH` environment variables.
+//! compile time using the `WL_COPY_PATH` and `WL_PASTE_PATH` environment variables. To search
+//! other directories than `PATH` or set binary paths at runtime, use
+//! [`with_search_path`](WaylandBinClipboardContext::with_search_path) or
+//! [`with_config`](WaylandBinClipboardContext::with_config).
 //!
 //! Use the provided `ClipboardContext` type alias to use this clipboard context on supported
 //! platforms, but fall back to the standard clipboard on others.
@@ -47,14 +50,29 @@
 //!
 //! [wl-clipboard]: https://github.com/bugaevc/wl-clipboard
 
+use std::collections::hash_map::DefaultHasher;
 use std::error::Error as StdError;
+use std::ffi::OsString;
 use std::fmt;
-use std::io::{Error as IoError, ErrorKind as IoErrorKind, Write};
-use std::process::{Command, Stdio};
+use std::hash::Hasher;
+use std::io::{BufRead, BufReader, Error as IoError, ErrorKind as IoErrorKind, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
 use std::string::FromUtf8Error;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::command::{BinaryLookup, CommandOptions, CommandRunner, SearchPath};
+use crate::config::ClipboardConfig;
 use crate::display::DisplayServer;
 use crate::prelude::*;
+use crate::selection::SelectionClipboardProviderExt;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Interval to poll the clipboard at while waiting for ownership.
+const OWNERSHIP_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 /// Platform specific context.
 ///
@@ -67,21 +85,301 @@ pub type ClipboardContext = WaylandBinClipboardContext;
 /// See module documentation for more information.
 ///
 /// [wl-clipboard]: https://github.com/bugaevc/wl-clipboard
-pub struct WaylandBinClipboardContext(ClipboardType);
+pub struct WaylandBinClipboardContext {
+    /// The clipboard binaries to use.
+    clipboard: ClipboardType,
+
+    /// Wait until the new contents are served after setting, up to this timeout.
+    ownership_timeout: Option<Duration>,
+
+    /// Options for spawning clipboard binaries.
+    command: CommandOptions,
+
+    /// Cache contents through a `wl-paste --watch` process.
+    watch: bool,
+
+    /// The `wl-paste --watch` process, started on first get.
+    watcher: Option<PasteWatcher>,
+
+    /// Buffer reused for raw contents, such as when hashing them.
+    buffer: Vec<u8>,
+}
 
 impl WaylandBinClipboardContext {
     pub fn new() -> crate::ClipResult<Self> {
-        Ok(Self(ClipboardType::select()))
+        let command = CommandOptions::default();
+        Ok(Self {
+            clipboard: ClipboardType::select(&command),
+            ownership_timeout: None,
+            command,
+            watch: false,
+            watcher: None,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Wait until the new contents are served after setting, up to the given timeout.
+    ///
+    /// `wl-copy` serves the clipboard contents from a background process. Without this option
+    /// `set_contents` may return before it does, and getting the contents immediately after may
+    /// still yield the old contents. This breaks copy-then-exit flows in short lived
+    /// applications.
+    ///
+    /// With this option, `set_contents` reads the clipboard back through `wl-paste` until it
+    /// holds the new contents. If that doesn't happen within the given timeout,
+    /// [`Error::OwnershipTimeout`] is returned. Contents set through
+    /// [`set_contents_from`](ClipboardProviderExt::set_contents_from) are not confirmed, as they
+    /// are streamed.
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::wayland_bin::WaylandBinClipboardContext;
+    ///
+    /// let mut ctx = WaylandBinClipboardContext::new()
+    ///     .unwrap()
+    ///     .with_wait_for_ownership(Duration::from_secs(1));
+    /// ctx.set_contents("some string".into()).unwrap();
+    /// ```
+    pub fn with_wait_for_ownership(mut self, timeout: Duration) -> Self {
+        self.ownership_timeout = Some(timeout);
+        self
+    }
+
+    /// Apply the options of the given configuration.
+    ///
+    /// Uses the configured binary paths, search path, environment sanitizing and ownership
+    /// timeout. See [`ClipboardConfig`].
+    pub fn with_config(mut self, config: &ClipboardConfig) -> Self {
+        self.command = self.command.with_config(config);
+        self.clipboard = ClipboardType::select(&self.command);
+        if let Some(timeout) = config.ownership_timeout {
+            self.ownership_timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// Keep a `wl-paste --watch` process alive, and serve contents from memory.
+    ///
+    /// Without this option each `get_contents` spawns `wl-paste`. With it, a single
+    /// `wl-paste --watch` process is started on the first get, and reports each clipboard change.
+    /// Contents are then fetched once per change and cached, so repeated gets are cheap in-memory
+    /// reads. Useful for applications reading the clipboard frequently, such as to poll it.
+    ///
+    /// Only [`get_contents`](ClipboardProvider::get_contents) is served from the cache. The
+    /// watch process is spawned directly, not through a custom
+    /// [runner](WaylandBinClipboardContext::with_command_runner), and is killed when this
+    /// context is dropped.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::wayland_bin::WaylandBinClipboardContext;
+    ///
+    /// let mut ctx = WaylandBinClipboardContext::new().unwrap().with_watch();
+    /// loop {
+    ///     println!("{:?}", ctx.get_contents());
+    ///     std::thread::sleep(std::time::Duration::from_millis(100));
+    /// }
+    /// ```
+    pub fn with_watch(mut self) -> Self {
+        self.watch = true;
+        self
+    }
+
+    /// Sanitize the environment of spawned clipboard binaries.
+    ///
+    /// By default spawned binaries inherit the full environment of the current process. With
+    /// this option they only receive the variables required to reach the display server, see
+    /// [`command::sanitize_env`](crate::command::sanitize_env). This prevents variables such as `LD_PRELOAD` from being passed
+    /// on, which is recommended for security sensitive applications.
+    pub fn with_sanitized_env(mut self) -> Self {
+        self.command.sanitize_env = true;
+        self
+    }
+
+    /// Access the clipboard of the given Wayland display, such as `wayland-1`, instead of
+    /// `$WAYLAND_DISPLAY`.
+    ///
+    /// Sets `WAYLAND_DISPLAY` for spawned clipboard binaries. Either a socket name in
+    /// `$XDG_RUNTIME_DIR`, or an absolute socket path.
+    pub fn with_display<D>(mut self, display: D) -> Self
+    where
+        D: Into<OsString>,
+    {
+        self.command
+            .env
+            .push(("WAYLAND_DISPLAY".into(), display.into()));
+        self
+    }
+
+    /// Access the clipboard of the seat with the given name, instead of the first seat.
+    ///
+    /// Passes `--seat` to `wl-copy` and `wl-paste`. Arguments set afterwards through
+    /// [`with_get_args`](Self::with_get_args) or [`with_set_args`](Self::with_set_args)
+    /// replace it.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::wayland_bin::WaylandBinClipboardContext;
+    ///
+    /// let mut ctx = WaylandBinClipboardContext::new()
+    ///     .unwrap()
+    ///     .with_display("wayland-1")
+    ///     .with_seat("seat1");
+    /// println!("{:?}", ctx.get_contents());
+    /// ```
+    pub fn with_seat<N>(mut self, seat: N) -> Self
+    where
+        N: Into<OsString>,
+    {
+        let seat = seat.into();
+        for args in [&mut self.command.get_args, &mut self.command.set_args] {
+            args.push("--seat".into());
+            args.push(seat.clone());
+        }
+        self
+    }
+
+    /// Spawn clipboard binaries through the given runner.
+    ///
+    /// By default binaries are spawned as regular child processes through
+    /// [`SystemCommandRunner`](crate::command::SystemCommandRunner). A custom runner may be used
+    /// to mock clipboard binaries in tests, or to route execution through a broker.
+    pub fn with_command_runner<R>(mut self, runner: R) -> Self
+    where
+        R: CommandRunner + 'static,
+    {
+        self.command.runner = Arc::new(runner);
+        self
+    }
+
+    /// Append the given arguments to `wl-paste` invocations for getting contents.
+    ///
+    /// Gives access to options this crate doesn't model, such as `--no-newline`. Also passed when
+    /// listing offered types and when [watching](Self::with_watch) the clipboard.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::wayland_bin::WaylandBinClipboardContext;
+    ///
+    /// let ctx = WaylandBinClipboardContext::new()
+    ///     .unwrap()
+    ///     .with_get_args(vec!["--no-newline"]);
+    /// ```
+    pub fn with_get_args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        self.command.get_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Append the given arguments to `wl-copy` invocations for setting contents.
+    ///
+    /// Gives access to options this crate doesn't model, such as `--trim-newline` or `--seat`.
+    pub fn with_set_args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        self.command.set_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Find clipboard binaries in the given directories, instead of `PATH`.
+    ///
+    /// Useful for applications bundling `wl-clipboard`, such as AppImages, and for daemons started
+    /// with a stripped environment. Configured paths still take precedence. See
+    /// [`SearchPath`].
+    pub fn with_search_path<I, P>(self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.with_binary_lookup(SearchPath::new(dirs))
+    }
+
+    /// Find clipboard binaries through the given lookup, instead of `PATH`.
+    ///
+    /// Configured paths still take precedence. See [`BinaryLookup`].
+    pub fn with_binary_lookup<L>(mut self, lookup: L) -> Self
+    where
+        L: BinaryLookup + 'static,
+    {
+        self.command.lookup = Arc::new(lookup);
+        self.clipboard = ClipboardType::select(&self.command);
+        self
+    }
+
+    /// The `wl-copy` and `wl-paste` binaries, along with their resolved paths.
+    ///
+    /// A path is `None` if the binary could not be found. Useful to show in diagnostics.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::wayland_bin::WaylandBinClipboardContext;
+    ///
+    /// let ctx = WaylandBinClipboardContext::new().unwrap();
+    /// for (bin, path) in ctx.binaries().iter() {
+    ///     println!("Using {} at {:?}", bin, path);
+    /// }
+    /// ```
+    pub fn binaries(&self) -> [(&'static str, Option<PathBuf>); 2] {
+        self.clipboard.binaries()
+    }
+
+    /// Get the raw clipboard contents into the reused buffer, without validating them as UTF-8.
+    fn get_bytes(&mut self) -> Result<&[u8], Error> {
+        self.buffer.clear();
+        self.clipboard.get_to(&self.command, &mut self.buffer)?;
+        Ok(&self.buffer)
+    }
+
+    /// Drop cached contents, if watching, as the clipboard is about to change.
+    fn invalidate_watch(&self) {
+        if let Some(watcher) = &self.watcher {
+            watcher.invalidate();
+        }
     }
 }
 
 impl ClipboardProvider for WaylandBinClipboardContext {
     fn get_contents(&mut self) -> crate::ClipResult<String> {
-        Ok(self.0.get()?)
+        if self.watch && self.watcher.is_none() {
+            self.watcher = Some(PasteWatcher::spawn(&self.clipboard, &self.command)?);
+        }
+        let contents = match &self.watcher {
+            Some(watcher) => watcher.get(&self.clipboard, &self.command)?,
+            None => self
+                .clipboard
+                .get(&self.command, crate::Selection::Clipboard)?,
+        };
+        debug!("wayland-bin: got {}", crate::redact::Redacted(&contents));
+        Ok(contents)
     }
 
     fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
-        Ok(self.0.set(&contents)?)
+        debug!(
+            "wayland-bin: setting {}",
+            crate::redact::Redacted(&contents)
+        );
+        self.invalidate_watch();
+        self.clipboard
+            .set(&self.command, crate::Selection::Clipboard, &contents)?;
+        if let Some(timeout) = self.ownership_timeout {
+            self.clipboard.wait_for(
+                &self.command,
+                crate::Selection::Clipboard,
+                &contents,
+                timeout,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl ClipboardProviderNew for WaylandBinClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        WaylandBinClipboardContext::new()
     }
 }
 
@@ -90,108 +388,440 @@ impl ClipboardProviderExt for WaylandBinClipboardContext {
         Some(DisplayServer::Wayland)
     }
 
-    fn has_bin_lifetime(&self) -> bool {
-        false
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Binary
+    }
+
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> crate::ClipResult<()> {
+        self.invalidate_watch();
+        Ok(self.clipboard.set_from(&self.command, reader)?)
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> crate::ClipResult<()> {
+        Ok(self.clipboard.get_to(&self.command, writer)?)
+    }
+
+    /// Hashes the raw contents in a reused buffer, without validating them as UTF-8.
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(self.get_bytes()?);
+        Ok(hasher.finish())
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        Ok(self.clipboard.types(&self.command)?.is_empty())
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        let types = self.clipboard.types(&self.command)?;
+        Ok(crate::content::mime_types(types.iter().map(|t| t.as_str())))
+    }
+}
+
+/// Only the regular clipboard and the primary selection are supported.
+impl SelectionClipboardProviderExt for WaylandBinClipboardContext {
+    fn supports_selection(&self, selection: crate::Selection) -> bool {
+        selection != crate::Selection::Secondary
+    }
+
+    fn get_selection(&mut self, selection: crate::Selection) -> crate::ClipResult<String> {
+        if !self.supports_selection(selection) {
+            return Err(crate::selection::Error::Unsupported(selection).into());
+        }
+        Ok(self.clipboard.get(&self.command, selection)?)
+    }
+
+    fn set_selection(
+        &mut self,
+        selection: crate::Selection,
+        contents: String,
+    ) -> crate::ClipResult<()> {
+        if !self.supports_selection(selection) {
+            return Err(crate::selection::Error::Unsupported(selection).into());
+        }
+        if selection == crate::Selection::Clipboard {
+            self.invalidate_watch();
+        }
+        self.clipboard.set(&self.command, selection, &contents)?;
+        if let Some(timeout) = self.ownership_timeout {
+            self.clipboard
+                .wait_for(&self.command, selection, &contents, timeout)?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps a `wl-paste --watch` process alive, caching the contents on each clipboard change.
+struct PasteWatcher {
+    /// The watch process.
+    child: Child,
+
+    /// The cache, shared with the thread handling clipboard changes.
+    cache: Arc<Mutex<PasteCache>>,
+}
+
+/// Cached clipboard contents.
+#[derive(Default)]
+struct PasteCache {
+    /// Incremented on each clipboard change, to not cache contents fetched before it.
+    generation: u64,
+
+    /// The cached contents, `None` if they must be fetched.
+    contents: Option<String>,
+
+    /// Set once the watch process stopped reporting changes.
+    stopped: bool,
+}
+
+impl PasteWatcher {
+    /// Spawn the watch process, along with a thread fetching contents on each change.
+    fn spawn(clipboard: &ClipboardType, command: &CommandOptions) -> Result<Self, Error> {
+        // wl-paste runs the command on each change, which prints a line for each. It drains the
+        // offered contents first, so the source application doesn't write into a closed pipe
+        let (bin, mut cmd) = clipboard.get_cmd(command, crate::Selection::Clipboard);
+        cmd.args(["--watch", "sh", "-c", "cat >/dev/null; echo"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        let mut child = cmd.spawn().map_err(|err| match err.kind() {
+            IoErrorKind::NotFound => Error::NoBinary,
+            _ => Error::BinaryIo(bin, err),
+        })?;
+        let stdout = child
+            .stdout
+            .take()
+            .expect("failed to get wl-paste watch output");
+
+        let cache = Arc::new(Mutex::new(PasteCache::default()));
+        let (thread_cache, clipboard, command) =
+            (Arc::clone(&cache), clipboard.clone(), command.clone());
+        let spawned = thread::Builder::new()
+            .name("copypasta-ext-wl-paste-watch".into())
+            .spawn(move || watch_changes(stdout, &thread_cache, &clipboard, &command));
+        if let Err(err) = spawned {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Error::BinaryIo(bin, err));
+        }
+
+        Ok(Self { child, cache })
+    }
+
+    /// Get the contents from cache, or fetch and cache them.
+    fn get(&self, clipboard: &ClipboardType, command: &CommandOptions) -> Result<String, Error> {
+        let generation = {
+            let cache = lock(&self.cache);
+            if let Some(contents) = &cache.contents {
+                return Ok(contents.clone());
+            }
+            cache.generation
+        };
+
+        let contents = clipboard.get(command, crate::Selection::Clipboard)?;
+        let mut cache = lock(&self.cache);
+        if !cache.stopped && cache.generation == generation {
+            cache.contents = Some(contents.clone());
+        }
+        Ok(contents)
+    }
+
+    /// Drop the cached contents.
+    fn invalidate(&self) {
+        lock(&self.cache).invalidate();
+    }
+}
+
+impl Drop for PasteWatcher {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
     }
 }
 
+impl PasteCache {
+    /// Drop the cached contents, returning the new generation.
+    fn invalidate(&mut self) -> u64 {
+        self.generation += 1;
+        self.contents = None;
+        self.generation
+    }
+}
+
+/// Fetch and cache the contents on each change reported by the watch process, until it exits.
+fn watch_changes(
+    stdout: ChildStdout,
+    cache: &Mutex<PasteCache>,
+    clipboard: &ClipboardType,
+    command: &CommandOptions,
+) {
+    for line in BufReader::new(stdout).split(b'\n') {
+        if line.is_err() {
+            break;
+        }
+        let generation = lock(cache).invalidate();
+        let contents = clipboard.get(command, crate::Selection::Clipboard).ok();
+        let mut cache = lock(cache);
+        if cache.generation == generation {
+            cache.contents = contents;
+        }
+    }
+
+    // Changes are not reported anymore, stop caching
+    let mut cache = lock(cache);
+    cache.invalidate();
+    cache.stopped = true;
+}
+
+/// Lock the given mutex, ignoring poisoning.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 /// Available clipboard management binaries.
 ///
 /// Invoke `ClipboardType::select()` to select the best variant to use determined at runtime.
+#[derive(Clone)]
 enum ClipboardType {
     /// Use `wl-copy` and `wl-paste` from `wl-clipboard`.
     ///
-    /// May contain a binary path if specified at compile time through the `XCLIP_PATH` variable.
-    WlClipboard(Option<String>, Option<String>),
+    /// Holds the binary paths if configured, see [`BinaryPaths`](crate::config::BinaryPaths), or
+    /// if found.
+    WlClipboard(Option<PathBuf>, Option<PathBuf>),
 }
 
 impl ClipboardType {
-    /// Select the clipboard type to use.
-    pub fn select() -> Self {
-        if option_env!("WL_COPY_PATH").is_some() || option_env!("WL_PASTE_PATH").is_some() {
-            ClipboardType::WlClipboard(
-                option_env!("WL_COPY_PATH")
-                    .filter(|p| !p.trim().is_empty())
-                    .map(|p| p.into()),
-                option_env!("WL_PASTE_PATH")
-                    .filter(|p| !p.trim().is_empty())
-                    .map(|p| p.into()),
-            )
-        // TODO: return WlClipboard if wl-copy/wl-paste are found, error otherwise
-        // } else if which("wl-copy").is_ok() || which("wl-paste").is_ok() {
-        //     ClipboardType::WlClipboard(None, None)
-        } else {
-            ClipboardType::WlClipboard(None, None)
+    /// Select the clipboard type to use, finding binaries through the given options.
+    pub fn select(command: &CommandOptions) -> Self {
+        // TODO: error if wl-copy/wl-paste are not found
+        ClipboardType::WlClipboard(
+            command
+                .paths
+                .wl_copy
+                .clone()
+                .or_else(|| command.find("wl-copy")),
+            command
+                .paths
+                .wl_paste
+                .clone()
+                .or_else(|| command.find("wl-paste")),
+        )
+    }
+
+    /// The copy and paste binary names, along with their resolved paths if found.
+    pub fn binaries(&self) -> [(&'static str, Option<PathBuf>); 2] {
+        match self {
+            ClipboardType::WlClipboard(copy, paste) => {
+                [("wl-copy", copy.clone()), ("wl-paste", paste.clone())]
+            }
         }
     }
 
+    /// Build the command for getting clipboard contents, along with the binary name.
+    fn get_cmd(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> (&'static str, Command) {
+        let (bin, mut cmd) = match self {
+            ClipboardType::WlClipboard(_, path) => {
+                ("wl-paste", Command::new(program(path, "wl-paste")))
+            }
+        };
+        if selection == crate::Selection::Primary {
+            cmd.arg("--primary");
+        }
+        cmd.args(&command.get_args);
+        command.prepare(&mut cmd);
+        (bin, cmd)
+    }
+
+    /// Build the command for setting clipboard contents, along with the binary name.
+    fn set_cmd(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> (&'static str, Command) {
+        let (bin, mut cmd) = match self {
+            ClipboardType::WlClipboard(path, _) => {
+                ("wl-copy", Command::new(program(path, "wl-copy")))
+            }
+        };
+        if selection == crate::Selection::Primary {
+            cmd.arg("--primary");
+        }
+        cmd.args(&command.set_args);
+        command.prepare(&mut cmd);
+        (bin, cmd)
+    }
+
     /// Get clipboard contents through the selected clipboard type.
-    pub fn get(&self) -> Result<String, Error> {
-        match self {
-            ClipboardType::WlClipboard(_, path) => sys_cmd_get(
-                "wl-paste",
-                &mut Command::new(path.as_deref().unwrap_or("wl-paste")),
-            ),
+    pub fn get(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> Result<String, Error> {
+        let (bin, mut cmd) = self.get_cmd(command, selection);
+        sys_cmd_get(command.runner.as_ref(), bin, &mut cmd)
+    }
+
+    /// Get clipboard contents through the selected clipboard type into the given writer.
+    pub fn get_to(&self, command: &CommandOptions, writer: &mut dyn Write) -> Result<(), Error> {
+        let (bin, mut cmd) = self.get_cmd(command, crate::Selection::Clipboard);
+        sys_cmd_get_to(command.runner.as_ref(), bin, &mut cmd, writer)
+    }
+
+    /// List the MIME types offered on the clipboard.
+    pub fn types(&self, command: &CommandOptions) -> Result<Vec<String>, Error> {
+        let (bin, mut cmd) = self.get_cmd(command, crate::Selection::Clipboard);
+        cmd.arg("--list-types");
+
+        // wl-paste exits with a failure status if nothing is copied, but also if the compositor
+        // is unreachable
+        let mut output = Vec::new();
+        match sys_cmd_get_to(command.runner.as_ref(), bin, &mut cmd, &mut output) {
+            Ok(()) => Ok(String::from_utf8_lossy(&output)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(Into::into)
+                .collect()),
+            Err(Error::BinaryStatus(..)) if is_display_reachable(command) => Ok(vec![]),
+            Err(err) => Err(err),
         }
     }
 
     /// Set clipboard contents through the selected clipboard type.
-    pub fn set(&self, contents: &str) -> Result<(), Error> {
-        match self {
-            ClipboardType::WlClipboard(path, _) => sys_cmd_set(
-                "wl-copy",
-                &mut Command::new(path.as_deref().unwrap_or("wl-copy")),
-                contents,
-            ),
+    pub fn set(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+        contents: &str,
+    ) -> Result<(), Error> {
+        let (bin, mut cmd) = self.set_cmd(command, selection);
+        sys_cmd_set(command.runner.as_ref(), bin, &mut cmd, contents)
+    }
+
+    /// Set clipboard contents through the selected clipboard type from the given reader.
+    pub fn set_from(&self, command: &CommandOptions, reader: &mut dyn Read) -> Result<(), Error> {
+        let (bin, mut cmd) = self.set_cmd(command, crate::Selection::Clipboard);
+        sys_cmd_set_from(command.runner.as_ref(), bin, &mut cmd, reader)
+    }
+
+    /// Wait until the clipboard holds the given contents, up to the given timeout.
+    pub fn wait_for(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+        contents: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        // Compare raw output in a reused buffer, the contents are UTF-8 if they match
+        let deadline = Instant::now() + timeout;
+        let (bin, mut cmd) = self.get_cmd(command, selection);
+        let mut output = Vec::with_capacity(contents.len() + 1);
+        loop {
+            output.clear();
+            // wl-paste appends a newline to text contents
+            let served = sys_cmd_get_to(command.runner.as_ref(), bin, &mut cmd, &mut output)
+                .is_ok()
+                && (output == contents.as_bytes()
+                    || output.strip_suffix(b"\n") == Some(contents.as_bytes()));
+            if served {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::OwnershipTimeout);
+            }
+            thread::sleep(OWNERSHIP_POLL_INTERVAL);
         }
     }
 }
 
-/// Get clipboard contents using a system command.
-fn sys_cmd_get(bin: &'static str, command: &mut Command) -> Result<String, Error> {
-    // Spawn the command process for getting the clipboard
-    let output = match command.output() {
-        Ok(output) => output,
-        Err(err) => {
-            return Err(match err.kind() {
-                IoErrorKind::NotFound => Error::NoBinary,
-                _ => Error::BinaryIo(bin, err),
-            });
+/// Check whether the Wayland compositor binaries spawned with the given options use accepts
+/// connections.
+///
+/// Tells an unreachable compositor apart from an empty clipboard, as binaries exit with a failure
+/// status for both. Only connects to the compositor socket.
+fn is_display_reachable(command: &CommandOptions) -> bool {
+    let display = PathBuf::from(
+        command
+            .env_var("WAYLAND_DISPLAY")
+            .unwrap_or_else(|| "wayland-0".into()),
+    );
+    let path = if display.is_absolute() {
+        display
+    } else {
+        match command.env_var("XDG_RUNTIME_DIR") {
+            Some(dir) => PathBuf::from(dir).join(display),
+            None => return false,
         }
     };
+    UnixStream::connect(path).is_ok()
+}
+
+/// The program to run for a binary, its path if known or its name otherwise.
+fn program<'a>(path: &'a Option<PathBuf>, bin: &'a str) -> &'a Path {
+    path.as_deref().unwrap_or_else(|| Path::new(bin))
+}
+
+/// Get clipboard contents using a system command.
+fn sys_cmd_get(
+    runner: &dyn CommandRunner,
+    bin: &'static str,
+    command: &mut Command,
+) -> Result<String, Error> {
+    let mut contents = Vec::new();
+    sys_cmd_get_to(runner, bin, command, &mut contents)?;
+
+    // Parse output
+    String::from_utf8(contents).map_err(Error::NoUtf8)
+}
 
-    // Check process status code
-    if !output.status.success() {
-        return Err(Error::BinaryStatus(bin, output.status.code().unwrap_or(0)));
+/// Get clipboard contents using a system command, writing them into the given writer.
+fn sys_cmd_get_to(
+    runner: &dyn CommandRunner,
+    bin: &'static str,
+    command: &mut Command,
+    writer: &mut dyn Write,
+) -> Result<(), Error> {
+    // Run the command process for getting the clipboard, stream output into the writer
+    let status = runner
+        .run(command.stderr(Stdio::null()), None, Some(writer))
+        .map_err(|err| match err.kind() {
+            IoErrorKind::NotFound => Error::NoBinary,
+            _ => Error::BinaryIo(bin, err),
+        })?;
+    if !status.success() {
+        return Err(Error::BinaryStatus(bin, status.code().unwrap_or(0)));
     }
 
-    // Get and parse output
-    String::from_utf8(output.stdout).map_err(Error::NoUtf8)
+    Ok(())
 }
 
 /// Set clipboard contents using a system command.
-fn sys_cmd_set(bin: &'static str, command: &mut Command, contents: &str) -> Result<(), Error> {
-    // Spawn the command process for setting the clipboard
-    let mut process = match command.stdin(Stdio::piped()).stdout(Stdio::null()).spawn() {
-        Ok(process) => process,
-        Err(err) => {
-            return Err(match err.kind() {
-                IoErrorKind::NotFound => Error::NoBinary,
-                _ => Error::BinaryIo(bin, err),
-            });
-        }
-    };
-
-    // Write the contents to the xclip process
-    process
-        .stdin
-        .as_mut()
-        .unwrap()
-        .write_all(contents.as_bytes())
-        .map_err(|err| Error::BinaryIo(bin, err))?;
+fn sys_cmd_set(
+    runner: &dyn CommandRunner,
+    bin: &'static str,
+    command: &mut Command,
+    contents: &str,
+) -> Result<(), Error> {
+    sys_cmd_set_from(runner, bin, command, &mut contents.as_bytes())
+}
 
-    // Wait for process to exit
-    let status = process.wait().map_err(|err| Error::BinaryIo(bin, err))?;
+/// Set clipboard contents using a system command, reading them from the given reader.
+fn sys_cmd_set_from(
+    runner: &dyn CommandRunner,
+    bin: &'static str,
+    command: &mut Command,
+    reader: &mut dyn Read,
+) -> Result<(), Error> {
+    // Run the command process for setting the clipboard, stream contents into it
+    let status = runner
+        .run(command, Some(reader), None)
+        .map_err(|err| match err.kind() {
+            IoErrorKind::NotFound => Error::NoBinary,
+            _ => Error::BinaryIo(bin, err),
+        })?;
     if !status.success() {
         return Err(Error::BinaryStatus(bin, status.code().unwrap_or(0)));
     }
@@ -216,6 +846,9 @@ pub enum Error {
 
     /// The clipboard contents could not be parsed as valid UTF-8.
     NoUtf8(FromUtf8Error),
+
+    /// The new clipboard contents were not served in time after setting.
+    OwnershipTimeout,
 }
 
 impl fmt::Display for Error {
@@ -238,6 +871,10 @@ impl fmt::Display for Error {
                 "Failed to parse clipboard contents as valid UTF-8: {}",
                 err
             ),
+            Error::OwnershipTimeout => write!(
+                f,
+                "Timed out waiting for clipboard to hold new contents after setting"
+            ),
         }
     }
 }
@@ -0,0 +1,542 @@
+//! Talk to the Wayland compositor directly for clipboard contents and events.
+//!
+//! Uses the [`wlr-data-control`][data-control] protocol, supported by wlroots based compositors
+//! such as Sway, and by KDE Plasma. Unlike the regular Wayland clipboard protocol, it doesn't
+//! need a window with input focus, so it works for command line and background applications.
+//!
+//! [`DataControlClipboardContext`] gets and sets clipboard contents without invoking any binaries.
+//! Set contents are served by a thread in this process, and are lost when it exits. The compositor
+//! also announces each new selection, so clipboard changes can be detected without polling. See
+//! [`Watcher::new_wayland`](crate::watch::Watcher::new_wayland).
+//!
+//! Use [`set_contents_with`](DataControlClipboardContext::set_contents_with) to only produce the
+//! contents when they are pasted.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::wayland_native::DataControlClipboardContext;
+//!
+//! let mut ctx = DataControlClipboardContext::new().unwrap();
+//! println!("{:?}", ctx.get_contents());
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+//!
+//! [data-control]: https://wayland.app/protocols/wlr-data-control-unstable-v1
+
+use std::cell::{Cell, RefCell};
+use std::error::Error as StdError;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::File;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::rc::Rc;
+use std::string::FromUtf8Error;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use wayland_client::protocol::wl_registry::Event as RegistryEvent;
+use wayland_client::protocol::wl_seat::{Event as SeatEvent, WlSeat};
+use wayland_client::{ConnectError, Display, EventQueue, GlobalError, GlobalManager, Main};
+use wayland_protocols::wlr::unstable::data_control::v1::client::zwlr_data_control_device_v1::{
+    Event as DeviceEvent, ZwlrDataControlDeviceV1,
+};
+use wayland_protocols::wlr::unstable::data_control::v1::client::zwlr_data_control_manager_v1::ZwlrDataControlManagerV1;
+use wayland_protocols::wlr::unstable::data_control::v1::client::zwlr_data_control_offer_v1::{
+    Event as OfferEvent, ZwlrDataControlOfferV1,
+};
+use wayland_protocols::wlr::unstable::data_control::v1::client::zwlr_data_control_source_v1::Event as SourceEvent;
+
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Text MIME types offered when setting, and accepted when getting in order of preference.
+const TEXT_MIME_TYPES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+    "TEXT",
+];
+
+/// Clipboard context using the Wayland data-control protocol.
+///
+/// Works without input focus, but only on compositors supporting the protocol, such as wlroots
+/// based compositors. See [`is_wlroots`](crate::display::is_wlroots).
+///
+/// Each operation uses its own connection to the compositor. Set contents are served by a
+/// background thread until the clipboard changes, and are lost when this process exits. Use
+/// [`persist`](ClipboardProviderExt::persist) to keep them available after exit.
+///
+/// See module documentation for more information.
+pub struct DataControlClipboardContext;
+
+impl DataControlClipboardContext {
+    /// Construct a new context.
+    ///
+    /// Fails if no Wayland compositor is reachable, or if it doesn't support the data-control
+    /// protocol.
+    pub fn new() -> crate::ClipResult<Self> {
+        Connection::new(1)?;
+        Ok(DataControlClipboardContext)
+    }
+
+    /// Set contents produced on paste, offered in the given MIME types.
+    ///
+    /// The MIME types are offered right away, but `provide` is only called with the requested
+    /// MIME type once an application pastes, so expensive contents are not produced unless they
+    /// are actually pasted. It is called again on every paste, and may provide different data per
+    /// MIME type. Return `None` to provide no data.
+    ///
+    /// `provide` is called on the thread serving the contents, until the clipboard changes.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::wayland_native::DataControlClipboardContext;
+    ///
+    /// # fn export() -> String { String::new() }
+    /// let mut ctx = DataControlClipboardContext::new().unwrap();
+    /// ctx.set_contents_with(&["text/plain;charset=utf-8", "UTF8_STRING"], |_mime_type| {
+    ///     Some(export().into_bytes())
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn set_contents_with<F>(
+        &mut self,
+        mime_types: &[&str],
+        mut provide: F,
+    ) -> crate::ClipResult<()>
+    where
+        F: FnMut(&str) -> Option<Vec<u8>> + Send + 'static,
+    {
+        let mime_types = mime_types.iter().map(|m| m.to_string()).collect();
+        spawn_serve(mime_types, move |mime_type, file| {
+            if let Some(data) = provide(mime_type) {
+                let _ = file.write_all(&data);
+            }
+        })
+    }
+}
+
+impl ClipboardProvider for DataControlClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        let mut connection = Connection::new(1)?;
+        let (offer, mime_types) = match connection.selection()? {
+            Some(selection) => selection,
+            None => return Ok(String::new()),
+        };
+        let mime_type = TEXT_MIME_TYPES
+            .iter()
+            .find(|mime_type| mime_types.iter().any(|m| m == *mime_type))
+            .ok_or(Error::NoText)?;
+
+        // Ask the selection owner to write its contents into our socket
+        let (mut read, write) = UnixStream::pair().map_err(Error::Io)?;
+        offer.receive((*mime_type).into(), write.as_raw_fd());
+        connection.display.flush().map_err(Error::Io)?;
+        drop(write);
+        offer.destroy();
+
+        let mut data = Vec::new();
+        read.read_to_end(&mut data).map_err(Error::Io)?;
+        Ok(String::from_utf8(data).map_err(Error::NoUtf8)?)
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        let mime_types = TEXT_MIME_TYPES.iter().map(|m| m.to_string()).collect();
+        spawn_serve(mime_types, move |_, file| {
+            let _ = file.write_all(contents.as_bytes());
+        })
+    }
+}
+
+impl ClipboardProviderNew for DataControlClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        DataControlClipboardContext::new()
+    }
+}
+
+/// Contents are served by a thread in this process.
+impl ClipboardProviderExt for DataControlClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        Some(DisplayServer::Wayland)
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Process
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        crate::persist::wayland(self.get_contents()?)
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        match Connection::new(1)?.selection()? {
+            Some((offer, mime_types)) => {
+                offer.destroy();
+                Ok(mime_types.is_empty())
+            }
+            None => Ok(true),
+        }
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        let mime_types = match Connection::new(1)?.selection()? {
+            Some((offer, mime_types)) => {
+                offer.destroy();
+                mime_types
+            }
+            None => return Ok(Vec::new()),
+        };
+        Ok(crate::content::mime_types(
+            mime_types.iter().map(|m| m.as_str()),
+        ))
+    }
+}
+
+/// Serve a clipboard selection on a new thread, see [`serve`].
+///
+/// Returns once the selection is set.
+fn spawn_serve<W>(mime_types: Vec<String>, write: W) -> crate::ClipResult<()>
+where
+    W: FnMut(&str, &mut File) + Send + 'static,
+{
+    let (ready, ready_rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("copypasta-ext-wl-data-control".into())
+        .spawn(move || serve(mime_types, write, ready))
+        .map_err(Error::Io)?;
+    ready_rx
+        .recv()
+        .unwrap_or_else(|_| Err(Error::Io(IoErrorKind::BrokenPipe.into())))?;
+    Ok(())
+}
+
+/// Serve a clipboard selection offering the given MIME types until it is replaced.
+///
+/// On each paste `write` is called with the requested MIME type, to write the contents to the
+/// given file. Reports whether the selection was set through `ready`.
+fn serve<W>(mime_types: Vec<String>, mut write: W, ready: Sender<Result<(), Error>>)
+where
+    W: FnMut(&str, &mut File) + 'static,
+{
+    let mut connection = match Connection::new(1) {
+        Ok(connection) => connection,
+        Err(err) => {
+            let _ = ready.send(Err(err));
+            return;
+        }
+    };
+
+    let cancelled = Rc::new(Cell::new(false));
+    let source = connection.manager.create_data_source();
+    {
+        let cancelled = cancelled.clone();
+        source.quick_assign(move |source, event, _| match event {
+            SourceEvent::Send { mime_type, fd } => {
+                let mut file = unsafe { File::from_raw_fd(fd) };
+                write(&mime_type, &mut file);
+            }
+            SourceEvent::Cancelled => {
+                cancelled.set(true);
+                source.destroy();
+            }
+            _ => {}
+        });
+    }
+    for mime_type in mime_types {
+        source.offer(mime_type);
+    }
+    connection.device.set_selection(Some(&source));
+
+    if let Err(err) = connection.roundtrip() {
+        let _ = ready.send(Err(err));
+        return;
+    }
+    let _ = ready.send(Ok(()));
+
+    while !cancelled.get() {
+        if connection.queue.dispatch(&mut (), |_, _, _| {}).is_err() {
+            break;
+        }
+    }
+}
+
+/// Names of the seats of the given Wayland display.
+///
+/// Seats announce their name since version 2 of the seat interface, older seats are skipped.
+pub(crate) fn seats(display: &OsStr) -> Result<Vec<String>, Error> {
+    let display = Display::connect_to_name(display).map_err(Error::Connect)?;
+    let mut queue = display.create_event_queue();
+    let attached = (*display).clone().attach(queue.token());
+    let names = Rc::new(RefCell::new(Vec::new()));
+    {
+        let names = names.clone();
+        attached
+            .get_registry()
+            .quick_assign(move |registry, event, _| match event {
+                RegistryEvent::Global {
+                    name,
+                    interface,
+                    version,
+                } if interface == "wl_seat" && version >= 2 => {
+                    let names = names.clone();
+                    registry
+                        .bind::<WlSeat>(2, name)
+                        .quick_assign(move |_, event, _| {
+                            if let SeatEvent::Name { name } = event {
+                                names.borrow_mut().push(name);
+                            }
+                        });
+                }
+                _ => {}
+            });
+    }
+
+    // Seats are announced on the first roundtrip, their names on the second
+    for _ in 0..2 {
+        queue
+            .sync_roundtrip(&mut (), |_, _, _| {})
+            .map_err(Error::Io)?;
+    }
+    Ok(names.take())
+}
+
+/// Connection to the compositor, with a data-control device for the first seat.
+struct Connection {
+    display: Display,
+    queue: EventQueue,
+    manager: Main<ZwlrDataControlManagerV1>,
+    device: Main<ZwlrDataControlDeviceV1>,
+    selection: Rc<RefCell<Option<Selection>>>,
+}
+
+/// Offer of the current selection, along with its MIME types.
+type Selection = (ZwlrDataControlOfferV1, Vec<String>);
+
+impl Connection {
+    /// Connect, requiring at least the given version of the data-control protocol.
+    fn new(version: u32) -> Result<Self, Error> {
+        let display = Display::connect_to_env().map_err(Error::Connect)?;
+        let mut queue = display.create_event_queue();
+        let attached = (*display).clone().attach(queue.token());
+        let globals = GlobalManager::new(&attached);
+        queue
+            .sync_roundtrip(&mut (), |_, _, _| {})
+            .map_err(Error::Io)?;
+
+        let seat = globals
+            .instantiate_exact::<WlSeat>(1)
+            .map_err(|_| Error::NoSeat)?;
+        let manager = globals
+            .instantiate_range::<ZwlrDataControlManagerV1>(version, 2)
+            .map_err(|err| match err {
+                GlobalError::Missing => Error::NoDataControl,
+                GlobalError::VersionTooLow(_) => Error::NoPrimarySelection,
+            })?;
+
+        let device = manager.get_data_device(&seat);
+        let selection = Rc::new(RefCell::new(None));
+        {
+            let selection = selection.clone();
+            let mime_types = Rc::new(RefCell::new(Vec::new()));
+            device.quick_assign(move |_, event, _| match event {
+                DeviceEvent::DataOffer { id } => {
+                    // Offers are introduced right before the selection they belong to
+                    mime_types.borrow_mut().clear();
+                    let mime_types = mime_types.clone();
+                    id.quick_assign(move |_, event, _| {
+                        if let OfferEvent::Offer { mime_type } = event {
+                            mime_types.borrow_mut().push(mime_type);
+                        }
+                    });
+                }
+                DeviceEvent::Selection { id } => {
+                    let previous = selection.replace(id.map(|offer| (offer, mime_types.take())));
+                    if let Some((offer, _)) = previous {
+                        offer.destroy();
+                    }
+                }
+                DeviceEvent::PrimarySelection { id: Some(offer) } => offer.destroy(),
+                _ => {}
+            });
+        }
+
+        Ok(Self {
+            display,
+            queue,
+            manager,
+            device,
+            selection,
+        })
+    }
+
+    /// Process all pending events, waiting for the compositor to handle all requests.
+    fn roundtrip(&mut self) -> Result<(), Error> {
+        self.queue
+            .sync_roundtrip(&mut (), |_, _, _| {})
+            .map(|_| ())
+            .map_err(Error::Io)
+    }
+
+    /// Take the offer of the current selection, `None` if the clipboard is empty.
+    fn selection(&mut self) -> Result<Option<Selection>, Error> {
+        // The current selection is announced right after binding the device
+        self.roundtrip()?;
+        Ok(self.selection.borrow_mut().take())
+    }
+}
+
+/// Listens for selection changes through the Wayland data-control protocol.
+///
+/// Not [`Send`], as the Wayland connection is bound to the thread it was created on.
+pub(crate) struct SelectionEvents {
+    display: Display,
+    queue: EventQueue,
+    changed: Rc<Cell<bool>>,
+    _device: Main<ZwlrDataControlDeviceV1>,
+}
+
+impl SelectionEvents {
+    /// Start listening for changes of the regular clipboard, or of the primary selection.
+    pub fn new(primary: bool) -> Result<Self, Error> {
+        let display = Display::connect_to_env().map_err(Error::Connect)?;
+        let mut queue = display.create_event_queue();
+        let attached = (*display).clone().attach(queue.token());
+        let globals = GlobalManager::new(&attached);
+        queue
+            .sync_roundtrip(&mut (), |_, _, _| {})
+            .map_err(Error::Io)?;
+
+        // Primary selection events are only sent since version 2
+        let seat = globals
+            .instantiate_exact::<WlSeat>(1)
+            .map_err(|_| Error::NoSeat)?;
+        let manager = globals
+            .instantiate_range::<ZwlrDataControlManagerV1>(if primary { 2 } else { 1 }, 2)
+            .map_err(|err| match err {
+                GlobalError::Missing => Error::NoDataControl,
+                GlobalError::VersionTooLow(_) => Error::NoPrimarySelection,
+            })?;
+
+        let changed = Rc::new(Cell::new(false));
+        let device = manager.get_data_device(&seat);
+        {
+            let changed = changed.clone();
+            device.quick_assign(move |_, event, _| match event {
+                DeviceEvent::DataOffer { id } => id.quick_assign(|_, _, _| {}),
+                DeviceEvent::Selection { id } => {
+                    changed.set(changed.get() || !primary);
+                    if let Some(offer) = id {
+                        offer.destroy();
+                    }
+                }
+                DeviceEvent::PrimarySelection { id } => {
+                    changed.set(changed.get() || primary);
+                    if let Some(offer) = id {
+                        offer.destroy();
+                    }
+                }
+                _ => {}
+            });
+        }
+
+        // The current selection is announced right away, that isn't a change
+        queue
+            .sync_roundtrip(&mut (), |_, _, _| {})
+            .map_err(Error::Io)?;
+        changed.set(false);
+
+        Ok(Self {
+            display,
+            queue,
+            changed,
+            _device: device,
+        })
+    }
+
+    /// Check whether the selection changed since the last check, without blocking.
+    ///
+    /// Handles all pending events. Wait for the connection to become readable before checking
+    /// again, see [`AsRawFd`].
+    pub fn changed(&mut self) -> Result<bool, Error> {
+        self.display.flush().map_err(Error::Io)?;
+        if let Some(guard) = self.queue.prepare_read() {
+            match guard.read_events() {
+                Err(err) if err.kind() != IoErrorKind::WouldBlock => return Err(Error::Io(err)),
+                _ => {}
+            }
+        }
+        self.queue
+            .dispatch_pending(&mut (), |_, _, _| {})
+            .map_err(Error::Io)?;
+        Ok(self.changed.replace(false))
+    }
+}
+
+/// The compositor connection, readable when new events arrive.
+impl AsRawFd for SelectionEvents {
+    fn as_raw_fd(&self) -> RawFd {
+        self.display.get_connection_fd()
+    }
+}
+
+/// Represents Wayland native clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to connect to the Wayland compositor.
+    Connect(ConnectError),
+
+    /// Failed to communicate with the Wayland compositor.
+    Io(IoError),
+
+    /// The compositor doesn't provide a seat.
+    NoSeat,
+
+    /// The compositor doesn't support the data-control protocol.
+    NoDataControl,
+
+    /// The compositor doesn't support primary selection through the data-control protocol.
+    NoPrimarySelection,
+
+    /// The clipboard doesn't hold contents in a text format.
+    NoText,
+
+    /// The clipboard contents are not valid UTF-8.
+    NoUtf8(FromUtf8Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Connect(err) => write!(f, "Failed to connect to Wayland compositor: {}", err),
+            Error::Io(err) => write!(f, "Failed to communicate with Wayland compositor: {}", err),
+            Error::NoSeat => write!(f, "Wayland compositor doesn't provide a seat"),
+            Error::NoDataControl => write!(
+                f,
+                "Wayland compositor doesn't support the wlr-data-control protocol"
+            ),
+            Error::NoPrimarySelection => write!(
+                f,
+                "Wayland compositor doesn't support primary selection through wlr-data-control"
+            ),
+            Error::NoText => write!(f, "Wayland clipboard doesn't hold text"),
+            Error::NoUtf8(err) => write!(f, "Wayland clipboard contents not valid UTF-8: {}", err),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Connect(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::NoUtf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
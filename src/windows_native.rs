@@ -0,0 +1,1340 @@
+//! Windows specific clipboard operations, through the Win32 clipboard API directly.
+//!
+//! Provides operations not available through the clipboard provider interface, such as setting
+//! rich HTML and RTF contents, or contents in many formats at once. Use
+//! [`WindowsClipboardProviderExt`] on the native Windows clipboard context.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::copypasta::windows_clipboard::WindowsClipboardContext;
+//! use copypasta_ext::windows_native::WindowsClipboardProviderExt;
+//!
+//! let mut ctx = WindowsClipboardContext::new().unwrap();
+//! ctx.set_html("<b>some string</b>", "some string").unwrap();
+//! ```
+//!
+//! The Windows clipboard can only be opened by one process at a time. Other processes, such as
+//! remote desktop clients and Office, commonly hold it open for a while. Use
+//! [`RetryClipboardContext`] to retry for longer while the clipboard is busy:
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::windows_native::RetryClipboardContext;
+//!
+//! let mut ctx = RetryClipboardContext::new()
+//!     .unwrap()
+//!     .with_retry(20, Duration::from_millis(100));
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::error::Error as StdError;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::os::raw::{c_int, c_uint, c_void};
+use std::os::windows::ffi::OsStrExt;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use copypasta::windows_clipboard::WindowsClipboardContext;
+
+use crate::content::{ClipboardContent, ClipboardItem};
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ChangeToken, ClipboardProviderNew, ContentLifetime, ThreadAffinity};
+
+/// Standard clipboard format for UTF-16 text.
+const CF_UNICODETEXT: c_uint = 13;
+
+/// Registered clipboard format name for HTML contents.
+const CF_HTML_NAME: &str = "HTML Format";
+
+/// Registered clipboard format name for RTF contents.
+const CF_RTF_NAME: &str = "Rich Text Format";
+
+/// Registered clipboard format names marking contents as sensitive, each set to a zero `DWORD`.
+///
+/// Exclude contents from clipboard monitors, from the clipboard history shown with `Win+V`, and
+/// from cloud clipboard sync across devices.
+const SENSITIVE_FORMAT_NAMES: [&str; 3] = [
+    "ExcludeClipboardContentFromMonitorProcessing",
+    "CanIncludeInClipboardHistory",
+    "CanUploadToCloudClipboard",
+];
+
+/// Error code returned when opening the clipboard while another window has it open.
+const ERROR_ACCESS_DENIED: i32 = 5;
+
+/// Default number of attempts to open a busy clipboard.
+const DEFAULT_ATTEMPTS: u32 = 10;
+
+/// Default interval between attempts to open a busy clipboard.
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Allocate moveable memory, as required by `SetClipboardData`.
+const GMEM_MOVEABLE: c_uint = 0x0002;
+
+/// First identifier of formats registered by name, lower identifiers are standard formats.
+const REGISTERED_FORMATS_START: c_uint = 0xC000;
+
+/// Window class of windows owning delayed rendered contents.
+const DELAYED_WINDOW_CLASS: &str = "copypasta-ext-delayed-rendering";
+
+/// Parent for message-only windows.
+const HWND_MESSAGE: isize = -3;
+
+/// Error code returned when registering a window class that already exists.
+const ERROR_CLASS_ALREADY_EXISTS: i32 = 1410;
+
+/// Window messages handled by windows owning delayed rendered contents.
+const WM_DESTROY: c_uint = 0x0002;
+const WM_CLOSE: c_uint = 0x0010;
+const WM_RENDERFORMAT: c_uint = 0x0305;
+const WM_RENDERALLFORMATS: c_uint = 0x0306;
+const WM_DESTROYCLIPBOARD: c_uint = 0x0307;
+
+/// Raw Win32 bindings.
+mod ffi {
+    use std::os::raw::{c_int, c_uint, c_void};
+
+    #[link(name = "user32")]
+    extern "system" {
+        pub fn OpenClipboard(owner: *mut c_void) -> c_int;
+        pub fn CloseClipboard() -> c_int;
+        pub fn EmptyClipboard() -> c_int;
+        pub fn SetClipboardData(format: c_uint, mem: *mut c_void) -> *mut c_void;
+        pub fn GetClipboardData(format: c_uint) -> *mut c_void;
+        pub fn IsClipboardFormatAvailable(format: c_uint) -> c_int;
+        pub fn RegisterClipboardFormatW(name: *const u16) -> c_uint;
+        pub fn EnumClipboardFormats(format: c_uint) -> c_uint;
+        pub fn GetClipboardFormatNameW(format: c_uint, name: *mut u16, max: c_int) -> c_int;
+        pub fn GetClipboardOwner() -> *mut c_void;
+        pub fn RegisterClassW(class: *const WndClass) -> u16;
+        pub fn CreateWindowExW(
+            ex_style: c_uint,
+            class_name: *const u16,
+            window_name: *const u16,
+            style: c_uint,
+            x: c_int,
+            y: c_int,
+            width: c_int,
+            height: c_int,
+            parent: *mut c_void,
+            menu: *mut c_void,
+            instance: *mut c_void,
+            param: *mut c_void,
+        ) -> *mut c_void;
+        pub fn DestroyWindow(window: *mut c_void) -> c_int;
+        pub fn DefWindowProcW(
+            window: *mut c_void,
+            message: c_uint,
+            wparam: usize,
+            lparam: isize,
+        ) -> isize;
+        pub fn GetMessageW(msg: *mut Msg, window: *mut c_void, min: c_uint, max: c_uint) -> c_int;
+        pub fn DispatchMessageW(msg: *const Msg) -> isize;
+        pub fn PostMessageW(
+            window: *mut c_void,
+            message: c_uint,
+            wparam: usize,
+            lparam: isize,
+        ) -> c_int;
+        pub fn PostQuitMessage(exit_code: c_int);
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn GlobalAlloc(flags: c_uint, bytes: usize) -> *mut c_void;
+        pub fn GlobalLock(mem: *mut c_void) -> *mut c_void;
+        pub fn GlobalUnlock(mem: *mut c_void) -> c_int;
+        pub fn GlobalFree(mem: *mut c_void) -> *mut c_void;
+        pub fn GlobalSize(mem: *mut c_void) -> usize;
+        pub fn GetModuleHandleW(name: *const u16) -> *mut c_void;
+    }
+
+    pub type WndProc = unsafe extern "system" fn(
+        window: *mut c_void,
+        message: c_uint,
+        wparam: usize,
+        lparam: isize,
+    ) -> isize;
+
+    #[repr(C)]
+    pub struct WndClass {
+        pub style: c_uint,
+        pub wnd_proc: Option<WndProc>,
+        pub cls_extra: c_int,
+        pub wnd_extra: c_int,
+        pub instance: *mut c_void,
+        pub icon: *mut c_void,
+        pub cursor: *mut c_void,
+        pub background: *mut c_void,
+        pub menu_name: *const u16,
+        pub class_name: *const u16,
+    }
+
+    #[repr(C)]
+    pub struct Msg {
+        pub window: *mut c_void,
+        pub message: c_uint,
+        pub wparam: usize,
+        pub lparam: isize,
+        pub time: u32,
+        pub pt_x: i32,
+        pub pt_y: i32,
+    }
+}
+
+/// Windows specific clipboard operations.
+///
+/// Implemented for the native Windows clipboard context.
+pub trait WindowsClipboardProviderExt {
+    /// Set HTML contents, along with a plain text alternative.
+    ///
+    /// The HTML is put on the clipboard in the `CF_HTML` format, wrapped as fragment with the
+    /// required header, see [`html_fragment`]. The plain text alternative is set as
+    /// `CF_UNICODETEXT` for applications not accepting HTML.
+    fn set_html(&mut self, html: &str, alt_text: &str) -> crate::ClipResult<()>;
+
+    /// Set RTF contents, along with a plain text alternative.
+    ///
+    /// The RTF document is put on the clipboard in the `Rich Text Format` format, accepted by
+    /// word processors such as Word and WordPad. The plain text alternative is set as
+    /// `CF_UNICODETEXT` for applications not accepting RTF.
+    fn set_rtf(&mut self, rtf: &str, alt_text: &str) -> crate::ClipResult<()>;
+
+    /// Get HTML contents.
+    ///
+    /// Reads the `CF_HTML` format, and extracts the fragment along with its source URL, see
+    /// [`parse_html_fragment`]. Returns `None` if the clipboard doesn't hold HTML contents.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::copypasta::windows_clipboard::WindowsClipboardContext;
+    /// use copypasta_ext::windows_native::WindowsClipboardProviderExt;
+    ///
+    /// let mut ctx = WindowsClipboardContext::new().unwrap();
+    /// if let Some(fragment) = ctx.get_html().unwrap() {
+    ///     println!("{} (from {:?})", fragment.html, fragment.source_url);
+    /// }
+    /// ```
+    fn get_html(&mut self) -> crate::ClipResult<Option<HtmlFragment>>;
+
+    /// Get RTF contents.
+    ///
+    /// Returns `None` if the clipboard doesn't hold RTF contents.
+    fn get_rtf(&mut self) -> crate::ClipResult<Option<String>>;
+
+    /// List the formats currently available on the clipboard.
+    ///
+    /// See [`formats`].
+    fn formats(&mut self) -> crate::ClipResult<Vec<Format>>;
+
+    /// Set the given items, each in all of its representations.
+    ///
+    /// The Windows clipboard holds a single item in multiple formats, [`Error::MultipleItems`] is
+    /// returned if more than one item is given. Representations are mapped to formats by MIME
+    /// type: `text/plain` as `CF_UNICODETEXT`, `text/html` as `CF_HTML`, `text/rtf` as
+    /// `Rich Text Format` and `image/png` as `PNG`. Other MIME types are set as registered format
+    /// named after the MIME type.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::content::ClipboardItem;
+    /// use copypasta_ext::copypasta::windows_clipboard::WindowsClipboardContext;
+    /// use copypasta_ext::windows_native::WindowsClipboardProviderExt;
+    ///
+    /// let item = ClipboardItem::new()
+    ///     .with_representation("text/html", b"<b>some string</b>".to_vec())
+    ///     .with_text("some string");
+    /// let mut ctx = WindowsClipboardContext::new().unwrap();
+    /// ctx.set_items(&[item]).unwrap();
+    /// ```
+    fn set_items(&mut self, items: &[ClipboardItem]) -> crate::ClipResult<()>;
+
+    /// Get the clipboard contents as items.
+    ///
+    /// Returns a single item holding all formats with a known MIME type, or no items if the
+    /// clipboard is empty. Standard formats other than `CF_UNICODETEXT` are skipped, as their
+    /// data isn't in the form of their MIME type. HTML is returned as the fragment only, with the
+    /// `CF_HTML` header stripped.
+    fn get_items(&mut self) -> crate::ClipResult<Vec<ClipboardItem>>;
+
+    /// Set sensitive text contents, such as passwords.
+    ///
+    /// Besides the text, formats are set that exclude the contents from clipboard monitors, from
+    /// the clipboard history shown with `Win+V`, and from cloud clipboard sync across devices.
+    /// Clear the clipboard afterwards, these formats don't limit how long the contents are
+    /// available.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::copypasta::windows_clipboard::WindowsClipboardContext;
+    /// use copypasta_ext::windows_native::WindowsClipboardProviderExt;
+    ///
+    /// let mut ctx = WindowsClipboardContext::new().unwrap();
+    /// ctx.set_sensitive("123456").unwrap();
+    /// ```
+    fn set_sensitive(&mut self, contents: &str) -> crate::ClipResult<()>;
+
+    /// Set contents in the given formats, rendered only when another application pastes them.
+    ///
+    /// Formats are given and mapped by MIME type as with [`set_items`](Self::set_items). The
+    /// `render` callback is called with a MIME type once an application requests that format,
+    /// so large contents aren't copied to the clipboard up front. Return `None` to provide no
+    /// data for the format.
+    ///
+    /// Rendering is served by a thread in this process, as long as the returned handle is kept.
+    /// Dropping it renders all formats still on the clipboard, so they remain available.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::copypasta::windows_clipboard::WindowsClipboardContext;
+    /// use copypasta_ext::windows_native::WindowsClipboardProviderExt;
+    ///
+    /// let mut ctx = WindowsClipboardContext::new().unwrap();
+    /// let rendering = ctx
+    ///     .set_delayed(&["text/plain"], |_mime_type| Some(b"some string".to_vec()))
+    ///     .unwrap();
+    ///
+    /// // Contents are rendered on paste until the handle is dropped
+    /// drop(rendering);
+    /// ```
+    fn set_delayed<F>(
+        &mut self,
+        mime_types: &[&str],
+        render: F,
+    ) -> crate::ClipResult<DelayedRendering>
+    where
+        F: FnMut(&str) -> Option<Vec<u8>> + Send + 'static,
+        Self: Sized;
+}
+
+impl WindowsClipboardProviderExt for WindowsClipboardContext {
+    fn set_html(&mut self, html: &str, alt_text: &str) -> crate::ClipResult<()> {
+        set_html(Retry::default(), html, alt_text)
+    }
+
+    fn set_rtf(&mut self, rtf: &str, alt_text: &str) -> crate::ClipResult<()> {
+        set_rich(Retry::default(), CF_RTF_NAME, rtf.as_bytes(), alt_text)
+    }
+
+    fn get_html(&mut self) -> crate::ClipResult<Option<HtmlFragment>> {
+        Ok(get_rich(Retry::default(), CF_HTML_NAME)?.map(|data| parse_html_fragment(&data)))
+    }
+
+    fn get_rtf(&mut self) -> crate::ClipResult<Option<String>> {
+        get_rich(Retry::default(), CF_RTF_NAME)
+    }
+
+    fn formats(&mut self) -> crate::ClipResult<Vec<Format>> {
+        formats_with(Retry::default())
+    }
+
+    fn set_items(&mut self, items: &[ClipboardItem]) -> crate::ClipResult<()> {
+        set_items(Retry::default(), items)
+    }
+
+    fn get_items(&mut self) -> crate::ClipResult<Vec<ClipboardItem>> {
+        get_items(Retry::default())
+    }
+
+    fn set_sensitive(&mut self, contents: &str) -> crate::ClipResult<()> {
+        set_sensitive(Retry::default(), contents)
+    }
+
+    fn set_delayed<F>(
+        &mut self,
+        mime_types: &[&str],
+        render: F,
+    ) -> crate::ClipResult<DelayedRendering>
+    where
+        F: FnMut(&str) -> Option<Vec<u8>> + Send + 'static,
+    {
+        DelayedRendering::set(Retry::default(), mime_types, Box::new(render))
+    }
+}
+
+/// Set HTML contents, see [`WindowsClipboardProviderExt::set_html`].
+fn set_html(retry: Retry, html: &str, alt_text: &str) -> crate::ClipResult<()> {
+    set_rich(
+        retry,
+        CF_HTML_NAME,
+        html_fragment(html).as_bytes(),
+        alt_text,
+    )
+}
+
+/// Set textual contents in the registered format with the given name, along with a plain text
+/// alternative.
+fn set_rich(retry: Retry, format_name: &str, data: &[u8], alt_text: &str) -> crate::ClipResult<()> {
+    let format = register_format(format_name)?;
+    let mut data = data.to_vec();
+    data.push(0);
+
+    let clipboard = Clipboard::open(retry)?;
+    clipboard.empty()?;
+    clipboard.set(format, &data)?;
+    clipboard.set(CF_UNICODETEXT, &utf16_bytes(alt_text))?;
+    Ok(())
+}
+
+/// Get textual contents in the registered format with the given name, `None` if not available.
+fn get_rich(retry: Retry, format_name: &str) -> crate::ClipResult<Option<String>> {
+    let format = register_format(format_name)?;
+    let clipboard = Clipboard::open(retry)?;
+    let data = match clipboard.get(format)? {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+
+    // Data is null terminated, and may be padded
+    let len = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    Ok(Some(String::from_utf8_lossy(&data[..len]).into_owned()))
+}
+
+/// Set items, see [`WindowsClipboardProviderExt::set_items`].
+fn set_items(retry: Retry, items: &[ClipboardItem]) -> crate::ClipResult<()> {
+    if items.len() > 1 {
+        return Err(Error::MultipleItems.into());
+    }
+
+    // Register formats and encode data before taking the clipboard
+    let mut data = Vec::new();
+    for (mime_type, bytes) in items.iter().flat_map(|item| item.representations()) {
+        data.push(encode_representation(mime_type, bytes)?);
+    }
+
+    let clipboard = Clipboard::open(retry)?;
+    clipboard.empty()?;
+    for (format, bytes) in data {
+        clipboard.set(format, &bytes)?;
+    }
+    Ok(())
+}
+
+/// Get items, see [`WindowsClipboardProviderExt::get_items`].
+fn get_items(retry: Retry) -> crate::ClipResult<Vec<ClipboardItem>> {
+    let formats = formats_with(retry)?;
+    let clipboard = Clipboard::open(retry)?;
+    let mut item = ClipboardItem::new();
+    for format in formats {
+        let registered = format.id >= REGISTERED_FORMATS_START;
+        let mime_type = match format.mime_type() {
+            Some(mime_type) if registered || format.id == CF_UNICODETEXT => mime_type.to_string(),
+            _ => continue,
+        };
+        if item.get(&mime_type).is_some() {
+            continue;
+        }
+        if let Some(data) = clipboard.get(format.id)? {
+            item = item.with_representation(
+                &mime_type,
+                decode_representation(format.id, &mime_type, data),
+            );
+        }
+    }
+    Ok(if item.is_empty() { vec![] } else { vec![item] })
+}
+
+/// Set sensitive text, see [`WindowsClipboardProviderExt::set_sensitive`].
+fn set_sensitive(retry: Retry, contents: &str) -> crate::ClipResult<()> {
+    // Register formats before taking the clipboard
+    let formats = SENSITIVE_FORMAT_NAMES
+        .iter()
+        .map(|name| register_format(name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let clipboard = Clipboard::open(retry)?;
+    clipboard.empty()?;
+    for format in formats {
+        clipboard.set(format, &0u32.to_ne_bytes())?;
+    }
+    clipboard.set(CF_UNICODETEXT, &utf16_bytes(contents))?;
+    Ok(())
+}
+
+/// Callback rendering data for a MIME type, see [`WindowsClipboardProviderExt::set_delayed`].
+type Render = Box<dyn FnMut(&str) -> Option<Vec<u8>> + Send>;
+
+/// Delayed rendered contents served by a thread.
+struct Delayed {
+    /// Formats along with their MIME type.
+    formats: Vec<(c_uint, String)>,
+
+    render: Render,
+}
+
+thread_local! {
+    /// The delayed rendered contents served on this thread.
+    static DELAYED: RefCell<Option<Delayed>> = const { RefCell::new(None) };
+}
+
+/// Handle to contents rendered on demand, see [`WindowsClipboardProviderExt::set_delayed`].
+///
+/// Dropping the handle renders all formats still on the clipboard, and stops serving.
+pub struct DelayedRendering {
+    /// Window owning the clipboard contents.
+    window: usize,
+
+    /// Whether the window still owns the clipboard contents.
+    active: Arc<AtomicBool>,
+
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DelayedRendering {
+    /// Set delayed rendered contents in the given formats, served by a new thread.
+    fn set(retry: Retry, mime_types: &[&str], render: Render) -> crate::ClipResult<Self> {
+        let formats = mime_types
+            .iter()
+            .map(|mime_type| Ok((representation_format(mime_type)?, mime_type.to_string())))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let active = Arc::new(AtomicBool::new(true));
+        let (ready, ready_rx) = mpsc::channel();
+        let thread = {
+            let active = active.clone();
+            thread::Builder::new()
+                .name("copypasta-ext-delayed-rendering".into())
+                .spawn(move || {
+                    serve_delayed(retry, formats, render, ready);
+                    active.store(false, Ordering::SeqCst);
+                })?
+        };
+        let window = ready_rx
+            .recv()
+            .unwrap_or_else(|_| Err(Error::Window(IoErrorKind::BrokenPipe.into()).into()))?;
+
+        Ok(Self {
+            window,
+            active,
+            thread: Some(thread),
+        })
+    }
+
+    /// Whether the contents are still on the clipboard.
+    ///
+    /// Becomes false once the clipboard is changed by any application.
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for DelayedRendering {
+    fn drop(&mut self) {
+        if self.is_active() {
+            unsafe { ffi::PostMessageW(self.window as *mut c_void, WM_CLOSE, 0, 0) };
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Take the clipboard with delayed rendered formats, and render them until the clipboard
+/// changes or the window is closed.
+///
+/// Reports the owning window, or why the clipboard couldn't be set, through `ready`.
+fn serve_delayed(
+    retry: Retry,
+    formats: Vec<(c_uint, String)>,
+    render: Render,
+    ready: mpsc::Sender<crate::ClipResult<usize>>,
+) {
+    let window = match create_window() {
+        Ok(window) => window,
+        Err(err) => {
+            let _ = ready.send(Err(err.into()));
+            return;
+        }
+    };
+
+    let result = Clipboard::open_owned(retry, window).and_then(|clipboard| {
+        clipboard.empty()?;
+        for (format, _) in &formats {
+            // Setting without data always returns null, there is no error to report
+            unsafe { ffi::SetClipboardData(*format, ptr::null_mut()) };
+        }
+        Ok(())
+    });
+    if let Err(err) = result {
+        unsafe { ffi::DestroyWindow(window) };
+        let _ = ready.send(Err(err));
+        return;
+    }
+    DELAYED.with(|delayed| *delayed.borrow_mut() = Some(Delayed { formats, render }));
+    let _ = ready.send(Ok(window as usize));
+
+    let mut msg = std::mem::MaybeUninit::<ffi::Msg>::uninit();
+    while unsafe { ffi::GetMessageW(msg.as_mut_ptr(), ptr::null_mut(), 0, 0) } > 0 {
+        unsafe { ffi::DispatchMessageW(msg.as_ptr()) };
+    }
+    DELAYED.with(|delayed| delayed.borrow_mut().take());
+}
+
+/// Create a message-only window to own delayed rendered contents.
+fn create_window() -> Result<*mut c_void, Error> {
+    let class_name: Vec<u16> = OsStr::new(DELAYED_WINDOW_CLASS)
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    unsafe {
+        let instance = ffi::GetModuleHandleW(ptr::null());
+        let class = ffi::WndClass {
+            style: 0,
+            wnd_proc: Some(delayed_window_proc),
+            cls_extra: 0,
+            wnd_extra: 0,
+            instance,
+            icon: ptr::null_mut(),
+            cursor: ptr::null_mut(),
+            background: ptr::null_mut(),
+            menu_name: ptr::null(),
+            class_name: class_name.as_ptr(),
+        };
+        if ffi::RegisterClassW(&class) == 0 {
+            let err = IoError::last_os_error();
+            if err.raw_os_error() != Some(ERROR_CLASS_ALREADY_EXISTS) {
+                return Err(Error::Window(err));
+            }
+        }
+
+        let window = ffi::CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            ptr::null(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE as *mut c_void,
+            ptr::null_mut(),
+            instance,
+            ptr::null_mut(),
+        );
+        if window.is_null() {
+            return Err(Error::Window(IoError::last_os_error()));
+        }
+        Ok(window)
+    }
+}
+
+/// Window procedure of windows owning delayed rendered contents.
+unsafe extern "system" fn delayed_window_proc(
+    window: *mut c_void,
+    message: c_uint,
+    wparam: usize,
+    lparam: isize,
+) -> isize {
+    match message {
+        // Another application pastes, the clipboard is opened by it
+        WM_RENDERFORMAT => render_delayed(|format| format == wparam as c_uint),
+        // The window is destroyed while owning the clipboard
+        WM_RENDERALLFORMATS => {
+            if ffi::OpenClipboard(window) != 0 {
+                if ffi::GetClipboardOwner() == window {
+                    render_delayed(|_| true);
+                }
+                ffi::CloseClipboard();
+            }
+        }
+        // The clipboard is emptied by another application, stop serving
+        WM_DESTROYCLIPBOARD => {
+            ffi::PostMessageW(window, WM_CLOSE, 0, 0);
+        }
+        WM_DESTROY => ffi::PostQuitMessage(0),
+        _ => return ffi::DefWindowProcW(window, message, wparam, lparam),
+    }
+    0
+}
+
+/// Render the delayed formats matching the given filter, and set them on the open clipboard.
+fn render_delayed<F>(filter: F)
+where
+    F: Fn(c_uint) -> bool,
+{
+    DELAYED.with(|delayed| {
+        let mut delayed = match delayed.try_borrow_mut() {
+            Ok(delayed) => delayed,
+            Err(_) => return,
+        };
+        let Delayed { formats, render } = match delayed.as_mut() {
+            Some(delayed) => delayed,
+            None => return,
+        };
+        for (_, mime_type) in formats.iter().filter(|(format, _)| filter(*format)) {
+            // Don't unwind into the system, which called the window procedure
+            let data = match panic::catch_unwind(AssertUnwindSafe(|| render(mime_type))) {
+                Ok(Some(data)) => data,
+                _ => continue,
+            };
+            let result = encode_representation(mime_type, &data)
+                .and_then(|(format, data)| set_data(format, &data));
+            if let Err(_err) = result {
+                debug!(
+                    "failed to render delayed clipboard contents as {}: {}",
+                    mime_type, _err
+                );
+            }
+        }
+    });
+}
+
+/// Empty the clipboard, see [`ClipboardProviderExt::clear_contents`].
+pub(crate) fn clear() -> crate::ClipResult<()> {
+    clear_with(Retry::default())
+}
+
+/// Empty the clipboard, see [`clear`].
+fn clear_with(retry: Retry) -> crate::ClipResult<()> {
+    Clipboard::open(retry)?.empty()?;
+    Ok(())
+}
+
+/// Get the format for a representation of the given MIME type.
+fn representation_format(mime_type: &str) -> Result<c_uint, Error> {
+    match mime_type {
+        "text/plain" => Ok(CF_UNICODETEXT),
+        "text/html" => register_format(CF_HTML_NAME),
+        "text/rtf" => register_format(CF_RTF_NAME),
+        "image/png" => register_format("PNG"),
+        mime_type => register_format(mime_type),
+    }
+}
+
+/// Get the format and clipboard data for a representation of the given MIME type.
+fn encode_representation(mime_type: &str, data: &[u8]) -> Result<(c_uint, Vec<u8>), Error> {
+    let format = representation_format(mime_type)?;
+    let text = || String::from_utf8_lossy(data);
+    let data = match mime_type {
+        "text/plain" => utf16_bytes(&text()),
+        "text/html" => {
+            let mut html = html_fragment(&text()).into_bytes();
+            html.push(0);
+            html
+        }
+        mime_type if mime_type.starts_with("text/") => {
+            data.iter().copied().chain(Some(0)).collect()
+        }
+        _ => data.to_vec(),
+    };
+    Ok((format, data))
+}
+
+/// Decode clipboard data of the given format into the representation of the given MIME type.
+fn decode_representation(format: c_uint, mime_type: &str, mut data: Vec<u8>) -> Vec<u8> {
+    if format == CF_UNICODETEXT {
+        let text: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+            .take_while(|c| *c != 0)
+            .collect();
+        return String::from_utf16_lossy(&text).into_bytes();
+    }
+
+    // Textual data is null terminated, and may be padded
+    if mime_type.starts_with("text/") {
+        if let Some(len) = data.iter().position(|b| *b == 0) {
+            data.truncate(len);
+        }
+    }
+    if mime_type == "text/html" {
+        let html = String::from_utf8_lossy(&data);
+        return parse_html_fragment(&html).html.into_bytes();
+    }
+    data
+}
+
+/// Like [`WindowsClipboardContext`], but retries while the clipboard is busy.
+///
+/// Getting and setting contents is retried while another process holds the clipboard open, up
+/// to 10 attempts 50 milliseconds apart by default. [`Error::Busy`] is returned once all
+/// attempts are exhausted.
+///
+/// See module documentation for more information.
+pub struct RetryClipboardContext {
+    context: WindowsClipboardContext,
+    retry: Retry,
+}
+
+impl RetryClipboardContext {
+    pub fn new() -> crate::ClipResult<Self> {
+        Ok(Self {
+            context: WindowsClipboardContext::new()?,
+            retry: Retry::default(),
+        })
+    }
+
+    /// Set the number of attempts, and the interval between them.
+    ///
+    /// At least one attempt is always made.
+    pub fn with_retry(mut self, attempts: u32, interval: Duration) -> Self {
+        self.retry = Retry { attempts, interval };
+        self
+    }
+}
+
+impl ClipboardProvider for RetryClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        let context = &mut self.context;
+        self.retry.run(|| context.get_contents())
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        let context = &mut self.context;
+        self.retry.run(|| context.set_contents(contents.clone()))
+    }
+}
+
+impl ClipboardProviderNew for RetryClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        RetryClipboardContext::new()
+    }
+}
+
+impl ClipboardProviderExt for RetryClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        self.context.display_server()
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        self.context.content_lifetime()
+    }
+
+    fn requires_event_loop(&self) -> bool {
+        self.context.requires_event_loop()
+    }
+
+    fn thread_affinity(&self) -> ThreadAffinity {
+        self.context.thread_affinity()
+    }
+
+    fn change_token(&mut self) -> crate::ClipResult<ChangeToken> {
+        self.context.change_token()
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        self.context.is_empty()
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        let formats = formats_with(self.retry)?;
+        Ok(crate::content::mime_types(
+            formats.iter().filter_map(|format| format.mime_type()),
+        ))
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        self.context.persist()
+    }
+
+    fn clear_contents(&mut self) -> crate::ClipResult<()> {
+        clear_with(self.retry)
+    }
+
+    fn get_content(&mut self) -> crate::ClipResult<ClipboardContent> {
+        get_content(self)
+    }
+
+    fn set_content(&mut self, content: ClipboardContent) -> crate::ClipResult<()> {
+        self.set_items(&[content.to_item()])
+    }
+}
+
+impl WindowsClipboardProviderExt for RetryClipboardContext {
+    fn set_html(&mut self, html: &str, alt_text: &str) -> crate::ClipResult<()> {
+        set_html(self.retry, html, alt_text)
+    }
+
+    fn set_rtf(&mut self, rtf: &str, alt_text: &str) -> crate::ClipResult<()> {
+        set_rich(self.retry, CF_RTF_NAME, rtf.as_bytes(), alt_text)
+    }
+
+    fn get_html(&mut self) -> crate::ClipResult<Option<HtmlFragment>> {
+        Ok(get_rich(self.retry, CF_HTML_NAME)?.map(|data| parse_html_fragment(&data)))
+    }
+
+    fn get_rtf(&mut self) -> crate::ClipResult<Option<String>> {
+        get_rich(self.retry, CF_RTF_NAME)
+    }
+
+    fn formats(&mut self) -> crate::ClipResult<Vec<Format>> {
+        formats_with(self.retry)
+    }
+
+    fn set_items(&mut self, items: &[ClipboardItem]) -> crate::ClipResult<()> {
+        set_items(self.retry, items)
+    }
+
+    fn get_items(&mut self) -> crate::ClipResult<Vec<ClipboardItem>> {
+        get_items(self.retry)
+    }
+
+    fn set_sensitive(&mut self, contents: &str) -> crate::ClipResult<()> {
+        set_sensitive(self.retry, contents)
+    }
+
+    fn set_delayed<F>(
+        &mut self,
+        mime_types: &[&str],
+        render: F,
+    ) -> crate::ClipResult<DelayedRendering>
+    where
+        F: FnMut(&str) -> Option<Vec<u8>> + Send + 'static,
+    {
+        DelayedRendering::set(self.retry, mime_types, Box::new(render))
+    }
+}
+
+/// Get clipboard contents of any supported kind, from all representations of the item.
+pub(crate) fn get_content<C>(context: &mut C) -> crate::ClipResult<ClipboardContent>
+where
+    C: WindowsClipboardProviderExt,
+{
+    let items = context.get_items()?;
+    Ok(ClipboardContent::from_items(&items).unwrap_or_else(|| String::new().into()))
+}
+
+/// Retry policy for opening a busy clipboard.
+#[derive(Copy, Clone, Debug)]
+struct Retry {
+    attempts: u32,
+    interval: Duration,
+}
+
+impl Retry {
+    /// Run the given operation, retrying while it fails because the clipboard is busy.
+    fn run<T, F>(&self, mut operation: F) -> crate::ClipResult<T>
+    where
+        F: FnMut() -> crate::ClipResult<T>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(err) if is_busy(err.as_ref()) => {
+                    if attempt >= self.attempts {
+                        return Err(Error::Busy.into());
+                    }
+                    attempt += 1;
+                    thread::sleep(self.interval);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_ATTEMPTS,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+}
+
+/// Check whether the given error is caused by another process holding the clipboard open.
+fn is_busy(err: &(dyn StdError + 'static)) -> bool {
+    let err = match err.downcast_ref::<Error>() {
+        Some(Error::Busy) => return true,
+        Some(Error::Open(err)) => err,
+        Some(_) => return false,
+        None => match err.downcast_ref::<IoError>() {
+            Some(err) => err,
+            None => return false,
+        },
+    };
+    err.raw_os_error() == Some(ERROR_ACCESS_DENIED)
+}
+
+/// Names of the standard clipboard formats.
+const STANDARD_FORMATS: &[(c_uint, &str)] = &[
+    (1, "CF_TEXT"),
+    (2, "CF_BITMAP"),
+    (3, "CF_METAFILEPICT"),
+    (4, "CF_SYLK"),
+    (5, "CF_DIF"),
+    (6, "CF_TIFF"),
+    (7, "CF_OEMTEXT"),
+    (8, "CF_DIB"),
+    (9, "CF_PALETTE"),
+    (10, "CF_PENDATA"),
+    (11, "CF_RIFF"),
+    (12, "CF_WAVE"),
+    (CF_UNICODETEXT, "CF_UNICODETEXT"),
+    (14, "CF_ENHMETAFILE"),
+    (15, "CF_HDROP"),
+    (16, "CF_LOCALE"),
+    (17, "CF_DIBV5"),
+    (0x0080, "CF_OWNERDISPLAY"),
+    (0x0081, "CF_DSPTEXT"),
+    (0x0082, "CF_DSPBITMAP"),
+    (0x0083, "CF_DSPMETAFILEPICT"),
+    (0x008E, "CF_DSPENHMETAFILE"),
+];
+
+/// A clipboard format available on the Windows clipboard.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub struct Format {
+    /// Format identifier.
+    pub id: u32,
+
+    /// Format name, such as `CF_UNICODETEXT` for standard formats or `HTML Format` for
+    /// registered formats.
+    pub name: String,
+}
+
+impl Format {
+    /// The MIME type for this format, if known.
+    pub fn mime_type(&self) -> Option<&str> {
+        match self.name.as_str() {
+            "CF_UNICODETEXT" | "CF_TEXT" | "CF_OEMTEXT" => Some("text/plain"),
+            "HTML Format" => Some("text/html"),
+            "Rich Text Format" => Some("text/rtf"),
+            "CF_HDROP" => Some("text/uri-list"),
+            "CF_DIB" | "CF_DIBV5" | "CF_BITMAP" => Some("image/bmp"),
+            "CF_TIFF" => Some("image/tiff"),
+            "PNG" => Some("image/png"),
+            "JFIF" => Some("image/jpeg"),
+            "GIF" => Some("image/gif"),
+            name if name.contains('/') => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// List the formats currently available on the clipboard.
+///
+/// Includes standard formats such as `CF_UNICODETEXT`, and formats registered by applications
+/// such as `HTML Format`. Formats are listed in the order they were put on the clipboard, which
+/// is commonly the order of preference of the source application.
+///
+/// ```rust,no_run
+/// use copypasta_ext::windows_native;
+///
+/// for format in windows_native::formats().unwrap() {
+///     println!("{} ({:?})", format, format.mime_type());
+/// }
+/// ```
+pub fn formats() -> crate::ClipResult<Vec<Format>> {
+    formats_with(Retry::default())
+}
+
+/// List the formats currently available on the clipboard, see [`formats`].
+fn formats_with(retry: Retry) -> crate::ClipResult<Vec<Format>> {
+    let _clipboard = Clipboard::open(retry)?;
+    let mut formats = Vec::new();
+    let mut id = 0;
+    loop {
+        id = unsafe { ffi::EnumClipboardFormats(id) };
+        if id == 0 {
+            // Reaching the end sets no error
+            let err = IoError::last_os_error();
+            if err.raw_os_error().unwrap_or(0) != 0 {
+                return Err(Error::Enumerate(err).into());
+            }
+            break;
+        }
+        formats.push(Format {
+            id,
+            name: format_name(id),
+        });
+    }
+    Ok(formats)
+}
+
+/// Get the name of a standard or registered clipboard format.
+///
+/// Falls back to the hexadecimal identifier for unnamed formats.
+fn format_name(id: c_uint) -> String {
+    if let Some((_, name)) = STANDARD_FORMATS.iter().find(|(i, _)| *i == id) {
+        return (*name).into();
+    }
+
+    let mut buf = [0u16; 256];
+    let len = unsafe { ffi::GetClipboardFormatNameW(id, buf.as_mut_ptr(), buf.len() as c_int) };
+    if len > 0 {
+        String::from_utf16_lossy(&buf[..len as usize])
+    } else {
+        format!("0x{:04X}", id)
+    }
+}
+
+/// Build `CF_HTML` clipboard data for the given HTML fragment.
+///
+/// Wraps the fragment in a document, and prefixes the header with byte offsets of the document
+/// and fragment as specified by the
+/// [HTML clipboard format](https://learn.microsoft.com/en-us/windows/win32/dataxchg/html-clipboard-format).
+pub fn html_fragment(html: &str) -> String {
+    const PREFIX: &str = "<html><body>\r\n<!--StartFragment-->";
+    const SUFFIX: &str = "<!--EndFragment-->\r\n</body></html>";
+
+    // Offsets are zero padded, the header length doesn't depend on them
+    let start_html = html_header(0, 0, 0, 0).len();
+    let start_fragment = start_html + PREFIX.len();
+    let end_fragment = start_fragment + html.len();
+    let end_html = end_fragment + SUFFIX.len();
+
+    let mut data = html_header(start_html, end_html, start_fragment, end_fragment);
+    data.reserve(end_html - start_html);
+    data.push_str(PREFIX);
+    data.push_str(html);
+    data.push_str(SUFFIX);
+    data
+}
+
+/// HTML contents read from the `CF_HTML` format.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub struct HtmlFragment {
+    /// The HTML fragment, without the header and surrounding document.
+    pub html: String,
+
+    /// URL of the document the fragment was copied from, if known.
+    pub source_url: Option<String>,
+}
+
+/// Parse `CF_HTML` clipboard data, the reverse of [`html_fragment`].
+///
+/// Strips the header, and extracts the fragment using the byte offsets it specifies. Falls back
+/// to the `<!--StartFragment-->` and `<!--EndFragment-->` markers, and then to the whole document,
+/// if the offsets are missing or invalid as written by some applications.
+///
+/// ```rust
+/// use copypasta_ext::windows_native::{html_fragment, parse_html_fragment};
+///
+/// let fragment = parse_html_fragment(&html_fragment("<b>some string</b>"));
+/// assert_eq!(fragment.html, "<b>some string</b>");
+/// assert_eq!(fragment.source_url, None);
+/// ```
+pub fn parse_html_fragment(data: &str) -> HtmlFragment {
+    let header_len = data.find('<').unwrap_or(data.len());
+    let mut source_url = None;
+    let (mut start_html, mut end_html, mut start_fragment, mut end_fragment) =
+        (None, None, None, None);
+    for line in data[..header_len].lines() {
+        let (key, value) = match line.split_once(':') {
+            Some(entry) => entry,
+            None => continue,
+        };
+        let offset = value.trim().parse::<usize>().ok();
+        match key.trim() {
+            "StartHTML" => start_html = offset,
+            "EndHTML" => end_html = offset,
+            "StartFragment" => start_fragment = offset,
+            "EndFragment" => end_fragment = offset,
+            "SourceURL" if !value.trim().is_empty() => source_url = Some(value.trim().into()),
+            _ => {}
+        }
+    }
+
+    let range = |start: Option<usize>, end: Option<usize>| match (start, end) {
+        (Some(start), Some(end)) if header_len <= start && start <= end => data.get(start..end),
+        _ => None,
+    };
+    let html = range(start_fragment, end_fragment)
+        .or_else(|| {
+            let start = data.find("<!--StartFragment-->")? + "<!--StartFragment-->".len();
+            let end = start + data[start..].find("<!--EndFragment-->")?;
+            Some(&data[start..end])
+        })
+        .or_else(|| range(start_html, end_html))
+        .unwrap_or(&data[header_len..]);
+
+    HtmlFragment {
+        html: html.into(),
+        source_url,
+    }
+}
+
+/// Build the `CF_HTML` header with the given byte offsets.
+fn html_header(
+    start_html: usize,
+    end_html: usize,
+    start_fragment: usize,
+    end_fragment: usize,
+) -> String {
+    format!(
+        "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+        start_html, end_html, start_fragment, end_fragment,
+    )
+}
+
+/// Encode text as null terminated UTF-16, as used by `CF_UNICODETEXT`.
+fn utf16_bytes(text: &str) -> Vec<u8> {
+    text.encode_utf16()
+        .chain(Some(0))
+        .flat_map(|c| c.to_ne_bytes().to_vec())
+        .collect()
+}
+
+/// Register a clipboard format by name, returning its identifier.
+fn register_format(name: &str) -> Result<c_uint, Error> {
+    let name: Vec<u16> = OsStr::new(name).encode_wide().chain(Some(0)).collect();
+    match unsafe { ffi::RegisterClipboardFormatW(name.as_ptr()) } {
+        0 => Err(Error::Register(IoError::last_os_error())),
+        format => Ok(format),
+    }
+}
+
+/// Opened clipboard, closed when dropped.
+struct Clipboard;
+
+impl Clipboard {
+    /// Open the clipboard, retrying while it is busy.
+    fn open(retry: Retry) -> crate::ClipResult<Self> {
+        Self::open_owned(retry, ptr::null_mut())
+    }
+
+    /// Open the clipboard for the given window, which becomes the owner when emptying it.
+    fn open_owned(retry: Retry, owner: *mut c_void) -> crate::ClipResult<Self> {
+        retry.run(|| {
+            if unsafe { ffi::OpenClipboard(owner) } == 0 {
+                return Err(Error::Open(IoError::last_os_error()).into());
+            }
+            Ok(Clipboard)
+        })
+    }
+
+    /// Empty the clipboard, required before setting new contents.
+    fn empty(&self) -> Result<(), Error> {
+        if unsafe { ffi::EmptyClipboard() } == 0 {
+            return Err(Error::Set(IoError::last_os_error()));
+        }
+        Ok(())
+    }
+
+    /// Set the given data for the given format.
+    ///
+    /// Other formats already set since emptying the clipboard are kept.
+    fn set(&self, format: c_uint, data: &[u8]) -> Result<(), Error> {
+        set_data(format, data)
+    }
+}
+
+/// Set the given data for the given format on the clipboard, which must be open.
+fn set_data(format: c_uint, data: &[u8]) -> Result<(), Error> {
+    unsafe {
+        let mem = ffi::GlobalAlloc(GMEM_MOVEABLE, data.len());
+        if mem.is_null() {
+            return Err(Error::Set(IoError::last_os_error()));
+        }
+        let target = ffi::GlobalLock(mem);
+        if target.is_null() {
+            let err = IoError::last_os_error();
+            ffi::GlobalFree(mem);
+            return Err(Error::Set(err));
+        }
+        ptr::copy_nonoverlapping(data.as_ptr(), target as *mut u8, data.len());
+        ffi::GlobalUnlock(mem);
+
+        // The system owns the memory once set successfully
+        if ffi::SetClipboardData(format, mem).is_null() {
+            let err = IoError::last_os_error();
+            ffi::GlobalFree(mem);
+            return Err(Error::Set(err));
+        }
+    }
+    Ok(())
+}
+
+impl Clipboard {
+    /// Get the data for the given format, `None` if not available.
+    fn get(&self, format: c_uint) -> Result<Option<Vec<u8>>, Error> {
+        unsafe {
+            if ffi::IsClipboardFormatAvailable(format) == 0 {
+                return Ok(None);
+            }
+            let mem = ffi::GetClipboardData(format);
+            if mem.is_null() {
+                return Err(Error::Get(IoError::last_os_error()));
+            }
+            let source = ffi::GlobalLock(mem);
+            if source.is_null() {
+                return Err(Error::Get(IoError::last_os_error()));
+            }
+            let data =
+                std::slice::from_raw_parts(source as *const u8, ffi::GlobalSize(mem)).to_vec();
+            ffi::GlobalUnlock(mem);
+            Ok(Some(data))
+        }
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::CloseClipboard();
+        }
+    }
+}
+
+/// Represents Windows native clipboard related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to open the clipboard.
+    Open(IoError),
+
+    /// The clipboard is held open by another process, and retries were exhausted.
+    Busy,
+
+    /// Failed to register a clipboard format.
+    Register(IoError),
+
+    /// Failed to get clipboard data.
+    Get(IoError),
+
+    /// Failed to set clipboard data.
+    Set(IoError),
+
+    /// Failed to enumerate available clipboard formats.
+    Enumerate(IoError),
+
+    /// Multiple items were given, the clipboard only holds a single item.
+    MultipleItems,
+
+    /// Failed to create a window to own delayed rendered contents.
+    Window(IoError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Open(err) => write!(f, "Failed to open clipboard: {}", err),
+            Error::Busy => write!(f, "Failed to open clipboard, in use by another process"),
+            Error::Register(err) => write!(f, "Failed to register clipboard format: {}", err),
+            Error::Get(err) => write!(f, "Failed to get clipboard data: {}", err),
+            Error::Set(err) => write!(f, "Failed to set clipboard data: {}", err),
+            Error::Enumerate(err) => write!(f, "Failed to enumerate clipboard formats: {}", err),
+            Error::MultipleItems => write!(f, "Windows clipboard only holds a single item"),
+            Error::Window(err) => write!(f, "Failed to create clipboard owner window: {}", err),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Open(err)
+            | Error::Register(err)
+            | Error::Get(err)
+            | Error::Set(err)
+            | Error::Enumerate(err)
+            | Error::Window(err) => Some(err),
+            Error::Busy | Error::MultipleItems => None,
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Invokes Windows clipboard binaries to access the clipboard from WSL.
+//!
+//! This provider allows accessing the Windows clipboard from the Windows Subsystem for Linux,
+//! when no Linux display server is available. With WSLg, the Wayland clipboard is synchronized
+//! with the Windows clipboard by the system, and a Wayland context should be preferred instead.
+//! [`DisplayServer::select`](crate::display::DisplayServer::select) takes care of this.
+//!
+//! When setting the clipboard, `clip.exe` is invoked. When getting the clipboard,
+//! `powershell.exe` is invoked with `Get-Clipboard`. Both are found through `PATH`, to which
+//! WSL adds the Windows binary directories by default, or in their default Windows location.
+//!
+//! Use the provided `ClipboardContext` type alias to use this clipboard context on supported
+//! platforms, but fall back to the standard clipboard on others.
+//!
+//! ## Benefits
+//!
+//! - Shares the clipboard with Windows applications.
+//! - Keeps contents in clipboard even after your application exists.
+//!
+//! ## Drawbacks
+//!
+//! - Requires WSL interoperability with Windows binaries to be enabled.
+//! - Slow due to binary invocation, especially getting through PowerShell.
+//! - Contents retrieved use Windows line endings.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::wsl_bin::WslBinClipboardContext;
+//!
+//! let mut ctx = WslBinClipboardContext::new().unwrap();
+//! println!("{:?}", ctx.get_contents());
+//! ctx.set_contents("some string".into()).unwrap();
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::string::FromUtf8Error;
+use std::sync::Arc;
+
+use crate::command::{BinaryLookup, CommandOptions, CommandRunner, SearchPath};
+use crate::config::ClipboardConfig;
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Default location of `clip.exe` on the Windows drive.
+const CLIP_PATH: &str = "/mnt/c/Windows/System32/clip.exe";
+
+/// Default location of `powershell.exe` on the Windows drive.
+const POWERSHELL_PATH: &str = "/mnt/c/Windows/System32/WindowsPowerShell/v1.0/powershell.exe";
+
+/// PowerShell script to output the clipboard as UTF-8 text.
+const GET_SCRIPT: &str =
+    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Get-Clipboard -Raw";
+
+/// Platform specific context.
+///
+/// Alias for `WslBinClipboardContext` on supported platforms, aliases to standard
+/// `ClipboardContext` provided by `rust-clipboard` on other platforms.
+pub type ClipboardContext = WslBinClipboardContext;
+
+/// Invokes Windows clipboard binaries to access the clipboard from WSL.
+///
+/// See module documentation for more information.
+pub struct WslBinClipboardContext {
+    /// Options for spawning clipboard binaries.
+    command: CommandOptions,
+}
+
+impl WslBinClipboardContext {
+    pub fn new() -> crate::ClipResult<Self> {
+        Ok(Self {
+            command: CommandOptions::default(),
+        })
+    }
+
+    /// Apply the options of the given configuration.
+    ///
+    /// Uses the configured search path and environment sanitizing. See
+    /// [`ClipboardConfig`].
+    pub fn with_config(mut self, config: &ClipboardConfig) -> Self {
+        self.command = self.command.with_config(config);
+        self
+    }
+
+    /// Sanitize the environment of spawned clipboard binaries.
+    ///
+    /// See [`command::sanitize_env`](crate::command::sanitize_env).
+    pub fn with_sanitized_env(mut self) -> Self {
+        self.command.sanitize_env = true;
+        self
+    }
+
+    /// Spawn clipboard binaries through the given runner.
+    ///
+    /// See [`CommandRunner`].
+    pub fn with_command_runner<R>(mut self, runner: R) -> Self
+    where
+        R: CommandRunner + 'static,
+    {
+        self.command.runner = Arc::new(runner);
+        self
+    }
+
+    /// Find clipboard binaries in the given directories, instead of `PATH`.
+    ///
+    /// The default Windows locations under `/mnt/c` are still used as fallback. See
+    /// [`SearchPath`].
+    pub fn with_search_path<I, P>(self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.with_binary_lookup(SearchPath::new(dirs))
+    }
+
+    /// Find clipboard binaries through the given lookup, instead of `PATH`.
+    ///
+    /// See [`BinaryLookup`].
+    pub fn with_binary_lookup<L>(mut self, lookup: L) -> Self
+    where
+        L: BinaryLookup + 'static,
+    {
+        self.command.lookup = Arc::new(lookup);
+        self
+    }
+
+    /// The `clip.exe` and `powershell.exe` binaries, along with their resolved paths.
+    ///
+    /// A path is `None` if the binary could not be found. Useful to show in diagnostics.
+    pub fn binaries(&self) -> [(&'static str, Option<PathBuf>); 2] {
+        [
+            ("clip.exe", self.resolve_bin("clip.exe", CLIP_PATH)),
+            (
+                "powershell.exe",
+                self.resolve_bin("powershell.exe", POWERSHELL_PATH),
+            ),
+        ]
+    }
+
+    /// Resolve the path of a binary through the lookup, falling back to the given default location.
+    fn resolve_bin(&self, bin: &str, default: &str) -> Option<PathBuf> {
+        self.command
+            .find(bin)
+            .or_else(|| Some(PathBuf::from(default)).filter(|path| path.exists()))
+    }
+
+    /// Build the command for the given binary, using its default location if not in `PATH`.
+    fn command(&self, bin: &'static str, default: &str) -> Command {
+        let path = self.resolve_bin(bin, default).unwrap_or_else(|| bin.into());
+        let mut cmd = Command::new(path);
+        self.command.prepare(&mut cmd);
+        cmd
+    }
+}
+
+impl ClipboardProvider for WslBinClipboardContext {
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        let mut cmd = self.command("powershell.exe", POWERSHELL_PATH);
+        cmd.args(["-NoProfile", "-NonInteractive", "-Command", GET_SCRIPT])
+            .stderr(Stdio::null());
+
+        let mut output = Vec::new();
+        let status = self
+            .command
+            .runner
+            .run(&mut cmd, None, Some(&mut output))
+            .map_err(|err| map_io_err("powershell.exe", err))?;
+        if !status.success() {
+            return Err(Error::BinaryStatus("powershell.exe", status.code().unwrap_or(0)).into());
+        }
+
+        // PowerShell terminates its output with a newline
+        let mut contents = String::from_utf8(output).map_err(Error::NoUtf8)?;
+        if contents.ends_with("\r\n") {
+            contents.truncate(contents.len() - 2);
+        }
+        debug!("wsl-bin: got {}", crate::redact::Redacted(&contents));
+        Ok(contents)
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        debug!("wsl-bin: setting {}", crate::redact::Redacted(&contents));
+        // clip.exe only reliably handles non-ASCII text as UTF-16 with byte order mark
+        let input: Vec<u8> = Some(0xFEFF)
+            .into_iter()
+            .chain(contents.encode_utf16())
+            .flat_map(|c| c.to_le_bytes().to_vec())
+            .collect();
+
+        let mut cmd = self.command("clip.exe", CLIP_PATH);
+        let status = self
+            .command
+            .runner
+            .run(&mut cmd, Some(&mut input.as_slice()), None)
+            .map_err(|err| map_io_err("clip.exe", err))?;
+        if !status.success() {
+            return Err(Error::BinaryStatus("clip.exe", status.code().unwrap_or(0)).into());
+        }
+        Ok(())
+    }
+}
+
+impl ClipboardProviderNew for WslBinClipboardContext {
+    fn new() -> crate::ClipResult<Self> {
+        WslBinClipboardContext::new()
+    }
+}
+
+impl ClipboardProviderExt for WslBinClipboardContext {
+    fn display_server(&self) -> Option<DisplayServer> {
+        Some(DisplayServer::Wsl)
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Persistent
+    }
+}
+
+/// Map an error spawning or communicating with a binary.
+fn map_io_err(bin: &'static str, err: IoError) -> Error {
+    match err.kind() {
+        IoErrorKind::NotFound => Error::NoBinary,
+        _ => Error::BinaryIo(bin, err),
+    }
+}
+
+/// Represents WSL binary related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The `clip.exe` or `powershell.exe` binary could not be found, required for clipboard
+    /// support.
+    NoBinary,
+
+    /// An error occurred while using `clip.exe` or `powershell.exe` to manage the clipboard
+    /// contents. This problem probably occurred when starting, or while piping the clipboard
+    /// contents from/to the process.
+    BinaryIo(&'static str, IoError),
+
+    /// `clip.exe` or `powershell.exe` unexpectetly exited with a non-successful status code.
+    BinaryStatus(&'static str, i32),
+
+    /// The clipboard contents could not be parsed as valid UTF-8.
+    NoUtf8(FromUtf8Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoBinary => write!(
+                f,
+                "Could not find clip.exe or powershell.exe binary for clipboard support"
+            ),
+            Error::BinaryIo(cmd, err) => {
+                write!(f, "Failed to access clipboard using {}: {}", cmd, err)
+            }
+            Error::BinaryStatus(cmd, code) => write!(
+                f,
+                "Failed to use clipboard, {} exited with status code {}",
+                cmd, code
+            ),
+            Error::NoUtf8(err) => write!(
+                f,
+                "Failed to parse clipboard contents as valid UTF-8: {}",
+                err
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::BinaryIo(_, err) => Some(err),
+            Error::NoUtf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
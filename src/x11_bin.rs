@@ -9,10 +9,24 @@
 //!
 //! The `xclip` or `xsel` must be in `PATH`. Alternatively the paths of either may be set at
 //! compile time using the `XCLIP_PATH` and `XSEL_PATH` environment variables. If set, the
-//! clipboard context will automatically use those.
+//! clipboard context will automatically use those. To search other directories than `PATH` or
+//! set binary paths at runtime, use [`with_search_path`](X11BinClipboardContext::with_search_path)
+//! or [`with_config`](X11BinClipboardContext::with_config).
 //!
-//! What binary is used is deterimined at runtime on context creation based on the compile time
-//! variables and the runtime environment.
+//! With the `x11-helper` feature, the `copypasta-ext-x11-helper` binary shipped with this crate
+//! is used if neither `xclip` nor `xsel` is available. It is looked up in `PATH`, next to the
+//! current executable, or at the path set at compile time using the `X11_HELPER_PATH` variable.
+//! Install it alongside your application with:
+//!
+//! ```sh
+//! cargo install copypasta-ext --features x11-helper --bin copypasta-ext-x11-helper
+//! ```
+//!
+//! To avoid spawning a process for each operation, the helper can also be kept running to handle
+//! all get and set requests, see `X11BinClipboardContext::with_helper_process`.
+//!
+//! What binary is used is deterimined at runtime on context creation based on the configured
+//! paths and the runtime environment.
 //!
 //! Use the provided `ClipboardContext` type alias to use this clipboard context on supported
 //! platforms, but fall back to the standard clipboard on others.
@@ -66,18 +80,47 @@
 //! [xclip]: https://github.com/astrand/xclip
 //! [xsel]: http://www.vergenet.net/~conrad/software/xsel/
 
+use std::collections::hash_map::DefaultHasher;
 use std::error::Error as StdError;
+use std::ffi::OsString;
 use std::fmt;
-use std::io::{Error as IoError, ErrorKind as IoErrorKind, Write};
+use std::hash::Hasher;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, Read, Write};
+use std::marker::PhantomData;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::string::FromUtf8Error;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
+#[cfg(not(feature = "x11"))]
+pub use self::selection::{Clipboard, Primary, Selection};
+#[cfg(feature = "x11")]
 use copypasta::x11_clipboard::X11ClipboardContext;
-use which::which;
+#[cfg(feature = "x11")]
+pub use copypasta::x11_clipboard::{Clipboard, Primary, Selection};
 
+#[cfg(feature = "x11")]
 use crate::combined::CombinedClipboardContext;
+use crate::command::{BinaryLookup, CommandOptions, CommandRunner, SearchPath};
+use crate::config::ClipboardConfig;
 use crate::display::DisplayServer;
 use crate::prelude::*;
+use crate::selection::SelectionClipboardProviderExt;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Interval to poll the clipboard at while waiting for ownership.
+const OWNERSHIP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Name of the helper binary shipped with this crate, see the `x11-helper` feature.
+#[cfg(feature = "x11-helper")]
+const HELPER: &str = "copypasta-ext-x11-helper";
+
+/// Timeout connecting to remote X11 displays, to check whether they are reachable.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// Platform specific context.
 ///
@@ -87,15 +130,45 @@ pub type ClipboardContext = X11BinClipboardContext;
 
 /// Invokes [`xclip`][xclip]/[`xsel`][xsel] to access clipboard.
 ///
+/// Generic over the X11 selection to access, like
+/// [`X11ForkClipboardContext`](crate::x11_fork::X11ForkClipboardContext). Defaults to the
+/// `CLIPBOARD` selection.
+///
 /// See module documentation for more information.
 ///
 /// [xclip]: https://github.com/astrand/xclip
 /// [xsel]: http://www.vergenet.net/~conrad/software/xsel/
-pub struct X11BinClipboardContext(ClipboardType);
+pub struct X11BinClipboardContext<S = Clipboard>
+where
+    S: BinSelection,
+{
+    /// The clipboard binary to use.
+    clipboard: ClipboardType,
+
+    /// Wait until the new contents are served after setting, up to this timeout.
+    ownership_timeout: Option<Duration>,
+
+    /// Options for spawning clipboard binaries.
+    command: CommandOptions,
+
+    /// The selection to access.
+    selection: PhantomData<S>,
+
+    /// Buffer reused for raw contents, such as when hashing them.
+    buffer: Vec<u8>,
+
+    /// Access the clipboard through a long-lived helper process.
+    #[cfg(feature = "x11-helper")]
+    serve: bool,
+
+    /// The long-lived helper process, started on first use.
+    #[cfg(feature = "x11-helper")]
+    server: Option<HelperServer>,
+}
 
 impl X11BinClipboardContext {
     pub fn new() -> crate::ClipResult<Self> {
-        Ok(Self(ClipboardType::select()))
+        Self::new_selection()
     }
 
     /// Construct combined with [`X11ClipboardContext`][X11ClipboardContext].
@@ -105,44 +178,537 @@ impl X11BinClipboardContext {
     /// This function also constructs a `X11ClipboardContext` for getting clipboard contents and
     /// combines the two to get the best of both worlds.
     ///
+    /// Requires the `x11` feature.
+    ///
     /// [X11ClipboardContext]: https://docs.rs/copypasta/*/copypasta/x11_clipboard/struct.X11ClipboardContext.html
+    #[cfg(feature = "x11")]
     pub fn new_with_x11() -> crate::ClipResult<CombinedClipboardContext<X11ClipboardContext, Self>>
     {
         Self::new()?.with_x11()
     }
+}
+
+impl<S> X11BinClipboardContext<S>
+where
+    S: BinSelection,
+{
+    /// Construct for the selection `S`.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::{Primary, X11BinClipboardContext};
+    ///
+    /// let mut ctx: X11BinClipboardContext<Primary> = X11BinClipboardContext::new_selection().unwrap();
+    /// println!("{:?}", ctx.get_contents());
+    /// ```
+    pub fn new_selection() -> crate::ClipResult<Self> {
+        let command = CommandOptions::default();
+        Ok(Self {
+            clipboard: ClipboardType::select(&command),
+            ownership_timeout: None,
+            command,
+            selection: PhantomData,
+            buffer: Vec::new(),
+            #[cfg(feature = "x11-helper")]
+            serve: false,
+            #[cfg(feature = "x11-helper")]
+            server: None,
+        })
+    }
+
+    /// Wait until the new contents are served after setting, up to the given timeout.
+    ///
+    /// `xclip` and `xsel` take ownership of the clipboard in a background process. Without this
+    /// option `set_contents` may return before that, and getting the contents immediately after
+    /// may still yield the old contents. This breaks copy-then-exit flows in short lived
+    /// applications.
+    ///
+    /// With this option, `set_contents` reads the clipboard back until it holds the new contents.
+    /// If that doesn't happen within the given timeout, [`Error::OwnershipTimeout`] is returned.
+    /// Contents set through [`set_contents_from`](ClipboardProviderExt::set_contents_from) are
+    /// not confirmed, as they are streamed.
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::X11BinClipboardContext;
+    ///
+    /// let mut ctx = X11BinClipboardContext::new()
+    ///     .unwrap()
+    ///     .with_wait_for_ownership(Duration::from_secs(1));
+    /// ctx.set_contents("some string".into()).unwrap();
+    /// ```
+    pub fn with_wait_for_ownership(mut self, timeout: Duration) -> Self {
+        self.ownership_timeout = Some(timeout);
+        self
+    }
+
+    /// Apply the options of the given configuration.
+    ///
+    /// Uses the configured binary paths, search path, environment sanitizing and ownership
+    /// timeout. The clipboard binary to use is selected again. See
+    /// [`ClipboardConfig`].
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::config::ClipboardConfig;
+    /// use copypasta_ext::x11_bin::X11BinClipboardContext;
+    ///
+    /// let config = ClipboardConfig::from_env().unwrap();
+    /// let ctx = X11BinClipboardContext::new().unwrap().with_config(&config);
+    /// ```
+    pub fn with_config(mut self, config: &ClipboardConfig) -> Self {
+        self.command = self.command.with_config(config);
+        self.clipboard = ClipboardType::select(&self.command);
+        if let Some(timeout) = config.ownership_timeout {
+            self.ownership_timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// Access the clipboard through a single long-lived helper process.
+    ///
+    /// Without this option each operation spawns `xclip` or `xsel`, which adds noticeable
+    /// latency to each call. With it, the helper binary shipped with this crate is started once in
+    /// serve mode on first use, and all get and set requests are sent to it over its standard
+    /// input and output. Set contents are still kept available after the application exits.
+    ///
+    /// The helper is found as described in the module documentation, even if `xclip` or `xsel`
+    /// is available, and is spawned directly instead of through a custom
+    /// [runner](X11BinClipboardContext::with_command_runner). Only getting and setting use the
+    /// helper, other operations invoke the selected binary as usual. Requires the `x11-helper`
+    /// feature.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::X11BinClipboardContext;
+    ///
+    /// let mut ctx = X11BinClipboardContext::new().unwrap().with_helper_process();
+    /// ctx.set_contents("some string".into()).unwrap();
+    /// println!("{:?}", ctx.get_contents());
+    /// ```
+    #[cfg(feature = "x11-helper")]
+    pub fn with_helper_process(mut self) -> Self {
+        self.serve = true;
+        self
+    }
+
+    /// Sanitize the environment of spawned clipboard binaries.
+    ///
+    /// By default spawned binaries inherit the full environment of the current process. With
+    /// this option they only receive the variables required to reach the display server, see
+    /// [`command::sanitize_env`](crate::command::sanitize_env). This prevents variables such as `LD_PRELOAD` from being passed
+    /// on, which is recommended for security sensitive applications.
+    pub fn with_sanitized_env(mut self) -> Self {
+        self.command.sanitize_env = true;
+        self
+    }
+
+    /// Access the clipboard of the given X11 display, such as `:1`, instead of `$DISPLAY`.
+    ///
+    /// Sets `DISPLAY` for spawned clipboard binaries, including the helper process.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::X11BinClipboardContext;
+    ///
+    /// let mut ctx = X11BinClipboardContext::new().unwrap().with_display(":1");
+    /// println!("{:?}", ctx.get_contents());
+    /// ```
+    pub fn with_display<D>(mut self, display: D) -> Self
+    where
+        D: Into<OsString>,
+    {
+        self.command.env.push(("DISPLAY".into(), display.into()));
+        self
+    }
+
+    /// Spawn clipboard binaries through the given runner.
+    ///
+    /// By default binaries are spawned as regular child processes through
+    /// [`SystemCommandRunner`](crate::command::SystemCommandRunner). A custom runner may be used
+    /// to mock clipboard binaries in tests, or to route execution through a broker.
+    ///
+    /// ```rust
+    /// use std::io::{self, Read, Write};
+    /// use std::os::unix::process::ExitStatusExt;
+    /// use std::process::{Command, ExitStatus};
+    /// use copypasta_ext::command::CommandRunner;
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::X11BinClipboardContext;
+    ///
+    /// struct MockRunner;
+    ///
+    /// impl CommandRunner for MockRunner {
+    ///     fn run(
+    ///         &self,
+    ///         _command: &mut Command,
+    ///         _stdin: Option<&mut dyn Read>,
+    ///         stdout: Option<&mut dyn Write>,
+    ///     ) -> io::Result<ExitStatus> {
+    ///         if let Some(stdout) = stdout {
+    ///             stdout.write_all(b"mocked")?;
+    ///         }
+    ///         Ok(ExitStatus::from_raw(0))
+    ///     }
+    /// }
+    ///
+    /// let mut ctx = X11BinClipboardContext::new()
+    ///     .unwrap()
+    ///     .with_command_runner(MockRunner);
+    /// assert_eq!(ctx.get_contents().unwrap(), "mocked");
+    /// ```
+    pub fn with_command_runner<R>(mut self, runner: R) -> Self
+    where
+        R: CommandRunner + 'static,
+    {
+        self.command.runner = Arc::new(runner);
+        self
+    }
+
+    /// Append the given arguments to invocations of the binary for getting contents.
+    ///
+    /// Gives access to options of `xclip` or `xsel` this crate doesn't model, such as `-noutf8`.
+    /// The arguments must suit the selected binary, see [`binary`](Self::binary). They are not
+    /// passed to the helper binary.
+    ///
+    /// ```rust
+    /// use std::io::{self, Read, Write};
+    /// use std::os::unix::process::ExitStatusExt;
+    /// use std::process::{Command, ExitStatus};
+    /// use copypasta_ext::command::CommandRunner;
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::x11_bin::X11BinClipboardContext;
+    ///
+    /// struct AssertArgRunner;
+    ///
+    /// impl CommandRunner for AssertArgRunner {
+    ///     fn run(
+    ///         &self,
+    ///         command: &mut Command,
+    ///         _stdin: Option<&mut dyn Read>,
+    ///         _stdout: Option<&mut dyn Write>,
+    ///     ) -> io::Result<ExitStatus> {
+    ///         assert_eq!(command.get_args().last().unwrap(), "-noutf8");
+    ///         Ok(ExitStatus::from_raw(0))
+    ///     }
+    /// }
+    ///
+    /// let mut ctx = X11BinClipboardContext::new()
+    ///     .unwrap()
+    ///     .with_command_runner(AssertArgRunner)
+    ///     .with_get_args(vec!["-noutf8"]);
+    /// ctx.get_contents().unwrap();
+    /// ```
+    pub fn with_get_args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        self.command.get_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Append the given arguments to invocations of the binary for setting contents.
+    ///
+    /// Gives access to options of `xclip` or `xsel` this crate doesn't model, such as `-quiet`.
+    /// The arguments must suit the selected binary, see [`binary`](Self::binary). They are not
+    /// passed to the helper binary.
+    pub fn with_set_args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        self.command.set_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Find clipboard binaries in the given directories, instead of `PATH`.
+    ///
+    /// Useful for applications bundling `xclip` or `xsel`, such as AppImages, and for daemons
+    /// started with a stripped environment. Configured paths still take precedence. See
+    /// [`SearchPath`].
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::x11_bin::X11BinClipboardContext;
+    ///
+    /// let ctx = X11BinClipboardContext::new()
+    ///     .unwrap()
+    ///     .with_search_path(vec!["/usr/bin", "/usr/local/bin"]);
+    /// ```
+    pub fn with_search_path<I, P>(self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.with_binary_lookup(SearchPath::new(dirs))
+    }
+
+    /// Find clipboard binaries through the given lookup, instead of `PATH`.
+    ///
+    /// The clipboard binary to use is selected again. Configured paths still take precedence. See [`BinaryLookup`].
+    pub fn with_binary_lookup<L>(mut self, lookup: L) -> Self
+    where
+        L: BinaryLookup + 'static,
+    {
+        self.command.lookup = Arc::new(lookup);
+        self.clipboard = ClipboardType::select(&self.command);
+        self
+    }
+
+    /// The selected clipboard binary, along with its resolved path.
+    ///
+    /// The path is `None` if the binary could not be found. Useful to show in diagnostics.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::x11_bin::X11BinClipboardContext;
+    ///
+    /// let ctx = X11BinClipboardContext::new().unwrap();
+    /// let (bin, path) = ctx.binary();
+    /// println!("Using {} at {:?}", bin, path);
+    /// ```
+    pub fn binary(&self) -> (&'static str, Option<PathBuf>) {
+        self.clipboard.binary()
+    }
 
     /// Combine this context with [`X11ClipboardContext`][X11ClipboardContext].
     ///
     /// This clipboard context invokes a binary for getting the clipboard contents. This may
     /// be considered inefficient and has other drawbacks as noted in the struct documentation.
-    /// This function constructs a `X11ClipboardContext` for getting clipboard contents and
-    /// combines the two to get the best of both worlds.
+    /// This function constructs a `X11ClipboardContext` for the same selection for getting
+    /// clipboard contents and combines the two to get the best of both worlds.
+    ///
+    /// Requires the `x11` feature.
     ///
     /// [X11ClipboardContext]: https://docs.rs/copypasta/*/copypasta/x11_clipboard/struct.X11ClipboardContext.html
+    #[cfg(feature = "x11")]
     pub fn with_x11(
         self,
-    ) -> crate::ClipResult<CombinedClipboardContext<X11ClipboardContext, Self>> {
+    ) -> crate::ClipResult<CombinedClipboardContext<X11ClipboardContext<S>, Self>> {
         Ok(CombinedClipboardContext(X11ClipboardContext::new()?, self))
     }
+
+    /// Get the contents of the given selection, through the helper process if enabled.
+    fn get(&mut self, selection: crate::Selection) -> Result<String, Error> {
+        #[cfg(feature = "x11-helper")]
+        if self.serve {
+            return self.with_server(|server| server.get(selection));
+        }
+        self.clipboard.get(&self.command, selection)
+    }
+
+    /// Get the raw contents of the given selection into the reused buffer, without validating
+    /// them as UTF-8.
+    fn get_bytes(&mut self, selection: crate::Selection) -> Result<&[u8], Error> {
+        #[cfg(feature = "x11-helper")]
+        if self.serve {
+            self.buffer = self.with_server(|server| server.get_bytes(selection))?;
+            return Ok(&self.buffer);
+        }
+        self.buffer.clear();
+        self.clipboard
+            .get_to(&self.command, selection, &mut self.buffer)?;
+        Ok(&self.buffer)
+    }
+
+    /// Set the contents of the given selection, through the helper process if enabled.
+    fn set(&mut self, selection: crate::Selection, contents: &str) -> Result<(), Error> {
+        #[cfg(feature = "x11-helper")]
+        if self.serve {
+            return self.with_server(|server| server.set(selection, contents));
+        }
+        self.clipboard.set(&self.command, selection, contents)?;
+        if let Some(timeout) = self.ownership_timeout {
+            self.clipboard
+                .wait_for(&self.command, selection, contents, timeout)?;
+        }
+        Ok(())
+    }
+
+    /// Run the given request on the helper process, starting it if not running.
+    ///
+    /// The process is stopped if communicating with it fails, and is started again on next use.
+    #[cfg(feature = "x11-helper")]
+    fn with_server<T, F>(&mut self, request: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut HelperServer) -> Result<T, Error>,
+    {
+        let mut server = match self.server.take() {
+            Some(server) => server,
+            None => HelperServer::spawn(&self.command)?,
+        };
+        let result = request(&mut server);
+        if !matches!(result, Err(Error::BinaryIo(..))) {
+            self.server = Some(server);
+        }
+        result
+    }
 }
 
-impl ClipboardProvider for X11BinClipboardContext {
+impl<S> ClipboardProvider for X11BinClipboardContext<S>
+where
+    S: BinSelection,
+{
     fn get_contents(&mut self) -> crate::ClipResult<String> {
-        Ok(self.0.get()?)
+        let contents = self.get(S::SELECTION)?;
+        debug!("x11-bin: got {}", crate::redact::Redacted(&contents));
+        Ok(contents)
     }
 
     fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
-        Ok(self.0.set(&contents)?)
+        debug!("x11-bin: setting {}", crate::redact::Redacted(&contents));
+        Ok(self.set(S::SELECTION, &contents)?)
     }
 }
 
-impl ClipboardProviderExt for X11BinClipboardContext {
+impl<S> ClipboardProviderNew for X11BinClipboardContext<S>
+where
+    S: BinSelection,
+{
+    fn new() -> crate::ClipResult<Self> {
+        X11BinClipboardContext::new_selection()
+    }
+}
+
+impl<S> ClipboardProviderExt for X11BinClipboardContext<S>
+where
+    S: BinSelection,
+{
     fn display_server(&self) -> Option<DisplayServer> {
         Some(DisplayServer::X11)
     }
 
-    fn has_bin_lifetime(&self) -> bool {
-        false
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Binary
+    }
+
+    fn selection(&self) -> crate::Selection {
+        S::SELECTION
+    }
+
+    fn set_contents_from(&mut self, reader: &mut dyn Read) -> crate::ClipResult<()> {
+        Ok(self
+            .clipboard
+            .set_from(&self.command, S::SELECTION, reader)?)
+    }
+
+    fn get_contents_to(&mut self, writer: &mut dyn Write) -> crate::ClipResult<()> {
+        Ok(self.clipboard.get_to(&self.command, S::SELECTION, writer)?)
+    }
+
+    /// Hashes the raw contents in a reused buffer, without validating them as UTF-8.
+    fn content_hash(&mut self) -> crate::ClipResult<u64> {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(self.get_bytes(S::SELECTION)?);
+        Ok(hasher.finish())
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        Ok(self.clipboard.is_empty(&self.command, S::SELECTION)?)
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        match self.clipboard.targets(&self.command, S::SELECTION)? {
+            Some(targets) => Ok(crate::content::mime_types(
+                targets.iter().map(|t| t.as_str()),
+            )),
+            None if self.is_empty()? => Ok(vec![]),
+            None => Ok(vec![crate::content::ContentType::Text.mime_type().into()]),
+        }
+    }
+}
+
+impl<S> SelectionClipboardProviderExt for X11BinClipboardContext<S>
+where
+    S: BinSelection,
+{
+    fn supports_selection(&self, selection: crate::Selection) -> bool {
+        #[cfg(feature = "x11-helper")]
+        if self.serve {
+            return selection != crate::Selection::Secondary;
+        }
+        self.clipboard.supports(selection)
+    }
+
+    fn get_selection(&mut self, selection: crate::Selection) -> crate::ClipResult<String> {
+        if !self.supports_selection(selection) {
+            return Err(crate::selection::Error::Unsupported(selection).into());
+        }
+        Ok(self.get(selection)?)
+    }
+
+    fn set_selection(
+        &mut self,
+        selection: crate::Selection,
+        contents: String,
+    ) -> crate::ClipResult<()> {
+        if !self.supports_selection(selection) {
+            return Err(crate::selection::Error::Unsupported(selection).into());
+        }
+        Ok(self.set(selection, &contents)?)
+    }
+}
+
+/// X11 selection that can be accessed through [`xclip`][xclip]/[`xsel`][xsel].
+///
+/// Implemented for the selection types also used by
+/// [`X11ClipboardContext`][X11ClipboardContext] and
+/// [`X11ForkClipboardContext`](crate::x11_fork::X11ForkClipboardContext), so contexts for the same
+/// selection can be combined consistently.
+///
+/// [X11ClipboardContext]: https://docs.rs/copypasta/*/copypasta/x11_clipboard/struct.X11ClipboardContext.html
+/// [xclip]: https://github.com/astrand/xclip
+/// [xsel]: http://www.vergenet.net/~conrad/software/xsel/
+pub trait BinSelection: Selection {
+    /// The selection, as accessed through [`SelectionClipboardProviderExt`].
+    const SELECTION: crate::Selection;
+
+    /// Selection name, as used with `xclip -selection`.
+    const XCLIP: &'static str = xclip_selection(Self::SELECTION);
+
+    /// Selection flag, as used with `xsel`.
+    const XSEL: &'static str = xsel_selection(Self::SELECTION);
+}
+
+impl BinSelection for Clipboard {
+    const SELECTION: crate::Selection = crate::Selection::Clipboard;
+}
+
+impl BinSelection for Primary {
+    const SELECTION: crate::Selection = crate::Selection::Primary;
+}
+
+/// Selection types like those of copypasta's X11 context, which requires the `x11` feature.
+#[cfg(not(feature = "x11"))]
+mod selection {
+    /// An X11 selection.
+    pub trait Selection: Send {}
+
+    /// The `PRIMARY` selection.
+    pub struct Primary;
+
+    /// The `CLIPBOARD` selection.
+    pub struct Clipboard;
+
+    impl Selection for Primary {}
+
+    impl Selection for Clipboard {}
+}
+
+/// Selection name, as used with `xclip -selection`.
+const fn xclip_selection(selection: crate::Selection) -> &'static str {
+    match selection {
+        crate::Selection::Clipboard => "clipboard",
+        crate::Selection::Primary => "primary",
+        crate::Selection::Secondary => "secondary",
+    }
+}
+
+/// Selection flag, as used with `xsel`.
+const fn xsel_selection(selection: crate::Selection) -> &'static str {
+    match selection {
+        crate::Selection::Clipboard => "--clipboard",
+        crate::Selection::Primary => "--primary",
+        crate::Selection::Secondary => "--secondary",
     }
 }
 
@@ -152,115 +718,502 @@ impl ClipboardProviderExt for X11BinClipboardContext {
 enum ClipboardType {
     /// Use `xclip`.
     ///
-    /// May contain a binary path if specified at compile time through the `XCLIP_PATH` variable.
-    Xclip(Option<String>),
+    /// Holds the binary path if configured, see [`BinaryPaths`](crate::config::BinaryPaths), or
+    /// if found.
+    Xclip(Option<PathBuf>),
 
     /// Use `xsel`.
     ///
-    /// May contain a binary path if specified at compile time through the `XSEL_PATH` variable.
-    Xsel(Option<String>),
+    /// Holds the binary path if configured, see [`BinaryPaths`](crate::config::BinaryPaths), or
+    /// if found.
+    Xsel(Option<PathBuf>),
+
+    /// Use the helper binary shipped with this crate, holds its path.
+    ///
+    /// Only used if neither `xclip` nor `xsel` is available.
+    #[cfg(feature = "x11-helper")]
+    Helper(PathBuf),
 }
 
 impl ClipboardType {
-    /// Select the clipboard type to use.
-    pub fn select() -> Self {
-        if let Some(path) = option_env!("XCLIP_PATH") {
-            ClipboardType::Xclip(Some(path.to_owned()))
-        } else if let Some(path) = option_env!("XSEL_PATH") {
-            ClipboardType::Xsel(Some(path.to_owned()))
-        } else if which("xclip").is_ok() {
-            ClipboardType::Xclip(None)
-        } else if which("xsel").is_ok() {
-            ClipboardType::Xsel(None)
+    /// Select the clipboard type to use, finding binaries through the given options.
+    pub fn select(command: &CommandOptions) -> Self {
+        if let Some(path) = &command.paths.xclip {
+            ClipboardType::Xclip(Some(path.clone()))
+        } else if let Some(path) = &command.paths.xsel {
+            ClipboardType::Xsel(Some(path.clone()))
+        } else if let Some(path) = command.find("xclip") {
+            ClipboardType::Xclip(Some(path))
+        } else if let Some(path) = command.find("xsel") {
+            ClipboardType::Xsel(Some(path))
         } else {
+            #[cfg(feature = "x11-helper")]
+            if let Some(path) = find_helper(command) {
+                return ClipboardType::Helper(path);
+            }
+
             // TODO: should we error here instead, as no clipboard binary was found?
             ClipboardType::Xclip(None)
         }
     }
 
-    /// Get clipboard contents through the selected clipboard type.
-    pub fn get(&self) -> Result<String, Error> {
+    /// The binary name, along with its resolved path if found.
+    pub fn binary(&self) -> (&'static str, Option<PathBuf>) {
         match self {
-            ClipboardType::Xclip(path) => sys_cmd_get(
-                "xclip",
-                Command::new(path.as_deref().unwrap_or("xclip"))
-                    .arg("-sel")
-                    .arg("clip")
-                    .arg("-out"),
-            ),
-            ClipboardType::Xsel(path) => sys_cmd_get(
-                "xsel",
-                Command::new(path.as_deref().unwrap_or("xsel"))
-                    .arg("--clipboard")
-                    .arg("--output"),
-            ),
+            ClipboardType::Xclip(path) => ("xclip", path.clone()),
+            ClipboardType::Xsel(path) => ("xsel", path.clone()),
+            #[cfg(feature = "x11-helper")]
+            ClipboardType::Helper(path) => (HELPER, Some(path.clone())),
+        }
+    }
+
+    /// Whether the given selection is supported by the selected clipboard type.
+    ///
+    /// The helper binary doesn't support the secondary selection.
+    pub fn supports(&self, selection: crate::Selection) -> bool {
+        match (self, selection) {
+            #[cfg(feature = "x11-helper")]
+            (ClipboardType::Helper(_), crate::Selection::Secondary) => false,
+            _ => true,
+        }
+    }
+
+    /// Build the command for getting clipboard contents, along with the binary name.
+    fn get_cmd(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> (&'static str, Command) {
+        let (bin, mut cmd) = match self {
+            ClipboardType::Xclip(path) => {
+                let mut cmd = Command::new(program(path, "xclip"));
+                cmd.arg("-sel").arg(xclip_selection(selection)).arg("-out");
+                cmd.args(&command.get_args);
+                ("xclip", cmd)
+            }
+            ClipboardType::Xsel(path) => {
+                let mut cmd = Command::new(program(path, "xsel"));
+                cmd.arg(xsel_selection(selection)).arg("--output");
+                cmd.args(&command.get_args);
+                ("xsel", cmd)
+            }
+            #[cfg(feature = "x11-helper")]
+            ClipboardType::Helper(path) => {
+                let mut cmd = Command::new(path);
+                cmd.arg("-sel").arg(xclip_selection(selection)).arg("-out");
+                (HELPER, cmd)
+            }
+        };
+        command.prepare(&mut cmd);
+        (bin, cmd)
+    }
+
+    /// Build the command for setting clipboard contents, along with the binary name.
+    fn set_cmd(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> (&'static str, Command) {
+        let (bin, mut cmd) = match self {
+            ClipboardType::Xclip(path) => {
+                let mut cmd = Command::new(program(path, "xclip"));
+                cmd.arg("-sel").arg(xclip_selection(selection));
+                cmd.args(&command.set_args);
+                ("xclip", cmd)
+            }
+            ClipboardType::Xsel(path) => {
+                let mut cmd = Command::new(program(path, "xsel"));
+                cmd.arg(xsel_selection(selection));
+                cmd.args(&command.set_args);
+                ("xsel", cmd)
+            }
+            #[cfg(feature = "x11-helper")]
+            ClipboardType::Helper(path) => {
+                let mut cmd = Command::new(path);
+                cmd.arg("-sel").arg(xclip_selection(selection));
+                (HELPER, cmd)
+            }
+        };
+        command.prepare(&mut cmd);
+        (bin, cmd)
+    }
+
+    /// Get clipboard contents through the selected clipboard type.
+    ///
+    /// If getting fails with `xclip`, the text target to get is negotiated, see
+    /// [`get_negotiated`](Self::get_negotiated).
+    pub fn get(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> Result<String, Error> {
+        let (bin, mut cmd) = self.get_cmd(command, selection);
+        match sys_cmd_get(command.runner.as_ref(), bin, &mut cmd) {
+            Err(err @ Error::NoUtf8(_)) | Err(err @ Error::BinaryStatus(..))
+                if matches!(self, ClipboardType::Xclip(_)) =>
+            {
+                self.get_negotiated(command, selection)?.ok_or(err)
+            }
+            result => result,
+        }
+    }
+
+    /// Get clipboard contents as the most preferred text target offered, only with `xclip`.
+    ///
+    /// Tries `text/plain;charset=utf-8`, `UTF8_STRING` and then `STRING`, converting the latter
+    /// from Latin-1. Old Motif and Java applications only offer `STRING`, which otherwise fails
+    /// to parse as UTF-8. Returns `None` if none of these targets are offered.
+    fn get_negotiated(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> Result<Option<String>, Error> {
+        let targets = self.targets(command, selection)?.unwrap_or_default();
+        let target = match crate::content::X11_TEXT_TARGETS
+            .iter()
+            .find(|target| targets.iter().any(|t| t == *target))
+        {
+            Some(target) => target,
+            None => return Ok(None),
+        };
+
+        let (bin, mut cmd) = self.get_cmd(command, selection);
+        cmd.arg("-target").arg(target);
+        let mut output = Vec::new();
+        sys_cmd_get_to(command.runner.as_ref(), bin, &mut cmd, &mut output)?;
+        crate::content::decode_x11_text(target, output)
+            .map(Some)
+            .map_err(Error::NoUtf8)
+    }
+
+    /// Get clipboard contents through the selected clipboard type into the given writer.
+    pub fn get_to(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+        writer: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let (bin, mut cmd) = self.get_cmd(command, selection);
+        sys_cmd_get_to(command.runner.as_ref(), bin, &mut cmd, writer)
+    }
+
+    /// List the targets offered on the clipboard through the selected clipboard type.
+    ///
+    /// Only supported with `xclip`, returns `None` otherwise.
+    pub fn targets(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> Result<Option<Vec<String>>, Error> {
+        let mut cmd = match self {
+            ClipboardType::Xclip(path) => Command::new(program(path, "xclip")),
+            _ => return Ok(None),
+        };
+        cmd.arg("-sel")
+            .arg(xclip_selection(selection))
+            .arg("-out")
+            .arg("-target")
+            .arg("TARGETS");
+        command.prepare(&mut cmd);
+
+        // xclip exits with a failure status if the selection has no owner, but also if the
+        // display is unreachable
+        let mut output = Vec::new();
+        match sys_cmd_get_to(command.runner.as_ref(), "xclip", &mut cmd, &mut output) {
+            Ok(()) => Ok(Some(lines(&output))),
+            Err(Error::BinaryStatus(..)) if is_display_reachable(command) => Ok(Some(vec![])),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Check whether the clipboard is empty through the selected clipboard type.
+    ///
+    /// With `xclip` the available targets are queried, others don't support this and get the
+    /// contents instead.
+    pub fn is_empty(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+    ) -> Result<bool, Error> {
+        if let Some(targets) = self.targets(command, selection)? {
+            return Ok(targets.is_empty());
+        }
+
+        // xsel and the helper may exit with a failure status if the selection has no owner
+        let (bin, mut cmd) = self.get_cmd(command, selection);
+        let mut output = Vec::new();
+        match sys_cmd_get_to(command.runner.as_ref(), bin, &mut cmd, &mut output) {
+            Ok(()) => Ok(output.is_empty()),
+            Err(Error::BinaryStatus(..)) if is_display_reachable(command) => Ok(true),
+            Err(err) => Err(err),
         }
     }
 
     /// Set clipboard contents through the selected clipboard type.
-    pub fn set(&self, contents: &str) -> Result<(), Error> {
-        match self {
-            ClipboardType::Xclip(path) => sys_cmd_set(
-                "xclip",
-                Command::new(path.as_deref().unwrap_or("xclip"))
-                    .arg("-sel")
-                    .arg("clip"),
-                contents,
-            ),
-            ClipboardType::Xsel(path) => sys_cmd_set(
-                "xsel",
-                Command::new(path.as_deref().unwrap_or("xsel")).arg("--clipboard"),
-                contents,
-            ),
+    pub fn set(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+        contents: &str,
+    ) -> Result<(), Error> {
+        let (bin, mut cmd) = self.set_cmd(command, selection);
+        sys_cmd_set(command.runner.as_ref(), bin, &mut cmd, contents)
+    }
+
+    /// Set clipboard contents through the selected clipboard type from the given reader.
+    pub fn set_from(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+        reader: &mut dyn Read,
+    ) -> Result<(), Error> {
+        let (bin, mut cmd) = self.set_cmd(command, selection);
+        sys_cmd_set_from(command.runner.as_ref(), bin, &mut cmd, reader)
+    }
+
+    /// Wait until the clipboard holds the given contents, up to the given timeout.
+    pub fn wait_for(
+        &self,
+        command: &CommandOptions,
+        selection: crate::Selection,
+        contents: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        // Compare raw output in a reused buffer, the contents are UTF-8 if they match
+        let deadline = Instant::now() + timeout;
+        let (bin, mut cmd) = self.get_cmd(command, selection);
+        let mut output = Vec::with_capacity(contents.len());
+        loop {
+            output.clear();
+            if sys_cmd_get_to(command.runner.as_ref(), bin, &mut cmd, &mut output).is_ok()
+                && output == contents.as_bytes()
+            {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::OwnershipTimeout);
+            }
+            thread::sleep(OWNERSHIP_POLL_INTERVAL);
         }
     }
 }
 
-/// Get clipboard contents using a system command.
-fn sys_cmd_get(bin: &'static str, command: &mut Command) -> Result<String, Error> {
-    // Spawn the command process for getting the clipboard
-    let output = match command.output() {
-        Ok(output) => output,
-        Err(err) => {
-            return Err(match err.kind() {
-                IoErrorKind::NotFound => Error::NoBinary,
-                _ => Error::BinaryIo(bin, err),
-            });
+/// Find the helper binary shipped with this crate.
+///
+/// Uses the configured path, the lookup of the given options, and looks next to the current
+/// executable.
+#[cfg(feature = "x11-helper")]
+fn find_helper(command: &CommandOptions) -> Option<PathBuf> {
+    if let Some(path) = &command.paths.x11_helper {
+        return Some(path.clone());
+    }
+    command.find(HELPER).or_else(|| {
+        let path = std::env::current_exe().ok()?.with_file_name(HELPER);
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
         }
-    };
+    })
+}
+
+/// The helper binary in serve mode, handling requests over its standard input and output.
+///
+/// A request is a header line, `get <selection>` or `set <selection> <length>` followed by the
+/// contents. A response is `ok <length>` followed by the contents, or `err <message>`.
+#[cfg(feature = "x11-helper")]
+struct HelperServer {
+    child: std::process::Child,
+    stdin: std::process::ChildStdin,
+    stdout: std::io::BufReader<std::process::ChildStdout>,
+}
 
-    // Check process status code
-    if !output.status.success() {
-        return Err(Error::BinaryStatus(bin, output.status.code().unwrap_or(0)));
+#[cfg(feature = "x11-helper")]
+impl HelperServer {
+    /// Find and spawn the helper in serve mode.
+    fn spawn(command: &CommandOptions) -> Result<Self, Error> {
+        let path = find_helper(command).ok_or(Error::NoBinary)?;
+        let mut cmd = Command::new(path);
+        cmd.arg("-serve")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        command.prepare(&mut cmd);
+        let mut child = cmd.spawn().map_err(|err| match err.kind() {
+            IoErrorKind::NotFound => Error::NoBinary,
+            _ => Error::BinaryIo(HELPER, err),
+        })?;
+        let stdin = child.stdin.take().expect("failed to get helper input");
+        let stdout = child.stdout.take().expect("failed to get helper output");
+        Ok(Self {
+            child,
+            stdin,
+            stdout: std::io::BufReader::new(stdout),
+        })
     }
 
-    // Get and parse output
-    String::from_utf8(output.stdout).map_err(Error::NoUtf8)
-}
+    /// Get the contents of the given selection.
+    fn get(&mut self, selection: crate::Selection) -> Result<String, Error> {
+        String::from_utf8(self.get_bytes(selection)?).map_err(Error::NoUtf8)
+    }
 
-/// Set clipboard contents using a system command.
-fn sys_cmd_set(bin: &'static str, command: &mut Command, contents: &str) -> Result<(), Error> {
-    // Spawn the command process for setting the clipboard
-    let mut process = match command.stdin(Stdio::piped()).stdout(Stdio::null()).spawn() {
-        Ok(process) => process,
-        Err(err) => {
-            return Err(match err.kind() {
-                IoErrorKind::NotFound => Error::NoBinary,
-                _ => Error::BinaryIo(bin, err),
-            });
+    /// Get the raw contents of the given selection.
+    fn get_bytes(&mut self, selection: crate::Selection) -> Result<Vec<u8>, Error> {
+        let header = format!("get {}\n", xclip_selection(selection));
+        self.request(&header, &[])
+    }
+
+    /// Set the contents of the given selection.
+    ///
+    /// The helper waits for ownership to be taken before responding.
+    fn set(&mut self, selection: crate::Selection, contents: &str) -> Result<(), Error> {
+        let header = format!("set {} {}\n", xclip_selection(selection), contents.len());
+        self.request(&header, contents.as_bytes())?;
+        Ok(())
+    }
+
+    /// Send a request, and read the response contents.
+    fn request(&mut self, header: &str, contents: &[u8]) -> Result<Vec<u8>, Error> {
+        use std::io::BufRead;
+
+        let io_err = |err| Error::BinaryIo(HELPER, err);
+        self.stdin.write_all(header.as_bytes()).map_err(io_err)?;
+        self.stdin.write_all(contents).map_err(io_err)?;
+        self.stdin.flush().map_err(io_err)?;
+
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line).map_err(io_err)? == 0 {
+            return Err(io_err(IoErrorKind::UnexpectedEof.into()));
+        }
+        let line = line.trim_end();
+        if let Some(message) = line.strip_prefix("err ") {
+            return Err(Error::Helper(message.into()));
         }
+        let len = line
+            .strip_prefix("ok ")
+            .and_then(|len| len.parse().ok())
+            .ok_or_else(|| io_err(IoErrorKind::InvalidData.into()))?;
+        let mut contents = vec![0; len];
+        self.stdout.read_exact(&mut contents).map_err(io_err)?;
+        Ok(contents)
+    }
+}
+
+/// Contents set through the helper are served by forked processes, and are kept.
+#[cfg(feature = "x11-helper")]
+impl Drop for HelperServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Split binary output into trimmed non-empty lines.
+fn lines(output: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(Into::into)
+        .collect()
+}
+
+/// Check whether the X11 display binaries spawned with the given options use accepts connections.
+///
+/// Tells an unreachable display apart from a selection without owner, as binaries exit with a
+/// failure status for both. Only connects to the display socket, without authenticating.
+fn is_display_reachable(command: &CommandOptions) -> bool {
+    let display = match command.env_var("DISPLAY") {
+        Some(display) => display.to_string_lossy().into_owned(),
+        None => return false,
+    };
+    // Such as `:0`, `unix:0` or `localhost:10.0`
+    let parsed = display.rsplit_once(':').and_then(|(host, screen)| {
+        let number: u16 = screen.split('.').next()?.parse().ok()?;
+        Some((host, number))
+    });
+    let (host, number) = match parsed {
+        Some(parsed) => parsed,
+        None => return false,
     };
 
-    // Write the contents to the xclip process
-    process
-        .stdin
-        .as_mut()
-        .unwrap()
-        .write_all(contents.as_bytes())
-        .map_err(|err| Error::BinaryIo(bin, err))?;
+    match host {
+        "" | "unix" => UnixStream::connect(format!("/tmp/.X11-unix/X{}", number)).is_ok(),
+        host => match 6000u16.checked_add(number) {
+            Some(port) => (host, port)
+                .to_socket_addrs()
+                .map(|mut addrs| {
+                    addrs.any(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok())
+                })
+                .unwrap_or(false),
+            None => false,
+        },
+    }
+}
+
+/// The program to run for a binary, its path if known or its name otherwise.
+fn program<'a>(path: &'a Option<PathBuf>, bin: &'a str) -> &'a Path {
+    path.as_deref().unwrap_or_else(|| Path::new(bin))
+}
+
+/// Get clipboard contents using a system command.
+fn sys_cmd_get(
+    runner: &dyn CommandRunner,
+    bin: &'static str,
+    command: &mut Command,
+) -> Result<String, Error> {
+    let mut contents = Vec::new();
+    sys_cmd_get_to(runner, bin, command, &mut contents)?;
+
+    // Parse output
+    String::from_utf8(contents).map_err(Error::NoUtf8)
+}
+
+/// Get clipboard contents using a system command, writing them into the given writer.
+fn sys_cmd_get_to(
+    runner: &dyn CommandRunner,
+    bin: &'static str,
+    command: &mut Command,
+    writer: &mut dyn Write,
+) -> Result<(), Error> {
+    // Run the command process for getting the clipboard, stream output into the writer
+    let status = runner
+        .run(command.stderr(Stdio::null()), None, Some(writer))
+        .map_err(|err| match err.kind() {
+            IoErrorKind::NotFound => Error::NoBinary,
+            _ => Error::BinaryIo(bin, err),
+        })?;
+    if !status.success() {
+        return Err(Error::BinaryStatus(bin, status.code().unwrap_or(0)));
+    }
+
+    Ok(())
+}
+
+/// Set clipboard contents using a system command.
+fn sys_cmd_set(
+    runner: &dyn CommandRunner,
+    bin: &'static str,
+    command: &mut Command,
+    contents: &str,
+) -> Result<(), Error> {
+    sys_cmd_set_from(runner, bin, command, &mut contents.as_bytes())
+}
 
-    // Wait for process to exit
-    let status = process.wait().map_err(|err| Error::BinaryIo(bin, err))?;
+/// Set clipboard contents using a system command, reading them from the given reader.
+fn sys_cmd_set_from(
+    runner: &dyn CommandRunner,
+    bin: &'static str,
+    command: &mut Command,
+    reader: &mut dyn Read,
+) -> Result<(), Error> {
+    // Run the command process for setting the clipboard, stream contents into it
+    let status = runner
+        .run(command, Some(reader), None)
+        .map_err(|err| match err.kind() {
+            IoErrorKind::NotFound => Error::NoBinary,
+            _ => Error::BinaryIo(bin, err),
+        })?;
     if !status.success() {
         return Err(Error::BinaryStatus(bin, status.code().unwrap_or(0)));
     }
@@ -285,6 +1238,12 @@ pub enum Error {
 
     /// The clipboard contents could not be parsed as valid UTF-8.
     NoUtf8(FromUtf8Error),
+
+    /// The new clipboard contents were not served in time after setting.
+    OwnershipTimeout,
+
+    /// The long-lived helper process failed to handle a request, holds its message.
+    Helper(String),
 }
 
 impl fmt::Display for Error {
@@ -307,6 +1266,11 @@ impl fmt::Display for Error {
                 "Failed to parse clipboard contents as valid UTF-8: {}",
                 err
             ),
+            Error::OwnershipTimeout => write!(
+                f,
+                "Timed out waiting for clipboard to hold new contents after setting"
+            ),
+            Error::Helper(message) => write!(f, "Clipboard helper failed: {}", message),
         }
     }
 }
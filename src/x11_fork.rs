@@ -4,7 +4,7 @@
 //! application exists, unlike [`X11ClipboardContext`][X11ClipboardContext].
 //!
 //! When setting the clipboard, the process is forked in which the clipboard is set. The fork is
-//! kept alive until the clipboard content changes, and may outlive your application.
+//! kept alive until another application takes the clipboard, and may outlive your application.
 //!
 //! Use the provided `ClipboardContext` type alias to use this clipboard context on supported
 //! platforms, but fall back to the standard clipboard on others.
@@ -15,11 +15,13 @@
 //!
 //! ## Drawbacks
 //!
-//! - Set contents may not be immediately available, because they are set in a fork.
-//! - Errors when setting the clipboard contents are not catched, the fork will panic
-//!   `set_contents` will return no error.
+//! - Setting blocks until the fork took ownership of the clipboard, up to a timeout. Errors
+//!   after that are not caught.
 //! - The fork might cause weird behaviour for some applications.
 //!
+//! Use [`set_contents_with`](X11ForkClipboardContext::set_contents_with) to only produce the
+//! contents in the fork when they are pasted, such as for expensive exports.
+//!
 //! # Examples
 //!
 //! ```rust,no_run
@@ -45,15 +47,39 @@
 //! [copypasta]: https://docs.rs/copypasta/*/copypasta/x11_clipboard/index.html
 //! [X11ClipboardContext]: https://docs.rs/copypasta/*/copypasta/x11_clipboard/struct.X11ClipboardContext.html
 
+use std::cmp;
+use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::fmt;
+use std::fs::File;
+use std::io::{Error as IoError, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::time::Duration;
 
-use copypasta::x11_clipboard::{Clipboard, Selection, X11ClipboardContext};
+use copypasta::x11_clipboard::{Clipboard, Primary, Selection, X11ClipboardContext};
 use libc::fork;
-use x11_clipboard::Clipboard as X11Clipboard;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xfixes::{ConnectionExt as XfixesConnectionExt, SelectionEventMask};
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt, CreateWindowAux, EventMask, PropMode,
+    Property, SelectionNotifyEvent, Window, WindowClass, SELECTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as WrapperConnectionExt;
+use x11rb::{COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, CURRENT_TIME, NONE};
 
+use crate::config::ClipboardConfig;
 use crate::display::DisplayServer;
 use crate::prelude::*;
+use crate::selection::SelectionClipboardProviderExt;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Default timeout for the fork to take ownership after setting.
+const DEFAULT_OWNERSHIP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Size of the chunks of incremental transfers.
+const INCR_CHUNK_SIZE: usize = 4000;
 
 /// Platform specific context.
 ///
@@ -69,62 +95,545 @@ pub type ClipboardContext = X11ForkClipboardContext;
 /// See module documentation for more information.
 ///
 /// [X11ClipboardContext]: https://docs.rs/copypasta/*/copypasta/x11_clipboard/struct.X11ClipboardContext.html
-pub struct X11ForkClipboardContext<S = Clipboard>(X11ClipboardContext<S>)
+pub struct X11ForkClipboardContext<S = Clipboard>
 where
-    S: Selection;
+    S: ForkSelection,
+{
+    /// Native context, used for getting contents.
+    context: X11ClipboardContext<S>,
+
+    /// Wait for the fork to take ownership after setting, up to this timeout.
+    ownership_timeout: Option<Duration>,
+}
 
 impl X11ForkClipboardContext {
     pub fn new() -> crate::ClipResult<Self> {
-        Ok(Self(X11ClipboardContext::new()?))
+        Self::new_selection()
     }
 }
 
-impl<S> ClipboardProvider for X11ForkClipboardContext<S>
+impl<S> X11ForkClipboardContext<S>
 where
-    S: Selection,
+    S: ForkSelection,
 {
-    fn get_contents(&mut self) -> crate::ClipResult<String> {
-        self.0.get_contents()
+    /// Construct for the selection `S`.
+    pub fn new_selection() -> crate::ClipResult<Self> {
+        Ok(Self {
+            context: X11ClipboardContext::new()?,
+            ownership_timeout: Some(DEFAULT_OWNERSHIP_TIMEOUT),
+        })
     }
 
-    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+    /// Wait for the fork to take ownership after setting, up to the given timeout.
+    ///
+    /// By default `set_contents` waits until the fork has taken ownership of the clipboard, up to
+    /// 2 seconds. This makes sure the contents are available when it returns, so applications
+    /// may exit right after without losing the contents. If the fork doesn't take ownership in
+    /// time, or fails to, [`Error::Ownership`] is returned.
+    pub fn with_wait_for_ownership(mut self, timeout: Duration) -> Self {
+        self.ownership_timeout = Some(timeout);
+        self
+    }
+
+    /// Apply the options of the given configuration.
+    ///
+    /// Uses the configured ownership timeout. See
+    /// [`ClipboardConfig`].
+    pub fn with_config(mut self, config: &ClipboardConfig) -> Self {
+        if let Some(timeout) = config.ownership_timeout {
+            self.ownership_timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// Don't wait for the fork to take ownership after setting.
+    ///
+    /// `set_contents` returns right after forking. Set contents may not be immediately available,
+    /// and failures in the fork are not caught.
+    pub fn without_wait_for_ownership(mut self) -> Self {
+        self.ownership_timeout = None;
+        self
+    }
+
+    /// Adopt the current clipboard contents in a forked process.
+    ///
+    /// Reads whatever is currently on the clipboard, and forks a process to keep serving it. Use
+    /// this when the contents are owned by the current process through another context, such as
+    /// a native [`X11ClipboardContext`], to keep them available after the process exits.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::prelude::*;
+    /// use copypasta_ext::copypasta::x11_clipboard::X11ClipboardContext;
+    /// use copypasta_ext::x11_fork::X11ForkClipboardContext;
+    ///
+    /// let mut native: X11ClipboardContext = X11ClipboardContext::new().unwrap();
+    /// native.set_contents("some string".into()).unwrap();
+    ///
+    /// // Before exiting
+    /// X11ForkClipboardContext::new().unwrap().persist_current().unwrap();
+    /// ```
+    pub fn persist_current(&mut self) -> crate::ClipResult<()> {
+        let contents = self.context.get_contents()?;
+        self.set_contents(contents)
+    }
+
+    /// Set contents produced on paste, in the given targets.
+    ///
+    /// Forks like [`set_contents`](ClipboardProvider::set_contents), but the fork only calls
+    /// `provide` with the requested target once an application pastes, so expensive contents are
+    /// not produced unless they are actually pasted. It is called again on every paste, and may
+    /// provide different data per target. Return `None` to refuse a target.
+    ///
+    /// Targets are X11 target names, such as `UTF8_STRING` for text or MIME types.
+    ///
+    /// `provide` runs in the fork, so state it changes isn't shared with this process. Other
+    /// threads of this process don't exist in the fork, it must not rely on them.
+    ///
+    /// ```rust,no_run
+    /// use copypasta_ext::x11_fork::X11ForkClipboardContext;
+    ///
+    /// # fn export() -> String { String::new() }
+    /// let mut ctx = X11ForkClipboardContext::new().unwrap();
+    /// ctx.set_contents_with(&["UTF8_STRING", "text/plain;charset=utf-8"], |_target| {
+    ///     Some(export().into_bytes())
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn set_contents_with<F>(&mut self, targets: &[&str], provide: F) -> crate::ClipResult<()>
+    where
+        F: FnMut(&str) -> Option<Vec<u8>>,
+    {
+        self.fork(|ready| {
+            serve_with::<S, F>(targets, provide, ready)
+                .expect("failed to serve clipboard contents through forked process");
+        })
+    }
+
+    /// Fork, and run `serve` in the fork to take ownership and serve the clipboard.
+    ///
+    /// `serve` must call the given function once it took ownership. The fork exits when `serve`
+    /// returns.
+    fn fork<F>(&self, serve: F) -> crate::ClipResult<()>
+    where
+        F: FnOnce(&mut dyn FnMut()),
+    {
+        // Pipe for the fork to signal it took ownership
+        let (ready_rx, mut ready_tx) = match self.ownership_timeout {
+            Some(_) => {
+                let (rx, tx) = pipe().map_err(Error::Pipe)?;
+                (Some(rx), Some(tx))
+            }
+            None => (None, None),
+        };
+
         match unsafe { fork() } {
             -1 => Err(Error::Fork.into()),
             0 => {
-                // Obtain new X11 clipboard context, set clipboard contents
-                let clip = X11Clipboard::new().expect("failed to obtain X11 clipboard context");
-                clip.store(
-                    S::atom(&clip.setter.atoms),
-                    clip.setter.atoms.utf8_string,
-                    contents,
-                )
-                .expect("failed to set clipboard contents through forked process");
+                drop(ready_rx);
 
-                // Wait for clipboard to change, then kill fork
-                clip.load_wait(
-                    S::atom(&clip.getter.atoms),
-                    clip.getter.atoms.utf8_string,
-                    clip.getter.atoms.property,
-                )
-                .expect("failed to wait on new clipboard value in forked process");
+                // Don't run signal handlers of the parent, but keep ignoring SIGPIPE as Rust does
+                crate::process::reset_signals();
+                unsafe { libc::signal(libc::SIGPIPE, libc::SIG_IGN) };
+
+                // Signal parent once we took ownership
+                serve(&mut || {
+                    if let Some(mut ready_tx) = ready_tx.take() {
+                        let _ = ready_tx.write_all(&[1]);
+                    }
+                });
 
                 std::process::exit(0)
             }
-            _pid => Ok(()),
+            _pid => {
+                drop(ready_tx);
+                match (ready_rx, self.ownership_timeout) {
+                    (Some(ready_rx), Some(timeout)) => Ok(wait_ready(ready_rx, timeout)?),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Construct a context for selection `T`, with the same options.
+    fn for_selection<T>(&self) -> crate::ClipResult<X11ForkClipboardContext<T>>
+    where
+        T: ForkSelection,
+    {
+        Ok(X11ForkClipboardContext {
+            context: X11ClipboardContext::new()?,
+            ownership_timeout: self.ownership_timeout,
+        })
+    }
+}
+
+impl<S> ClipboardProvider for X11ForkClipboardContext<S>
+where
+    S: ForkSelection,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        self.context.get_contents()
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.fork(|ready| {
+            // Listen for owner changes first, to not miss any after taking ownership
+            let owner_changes =
+                OwnerChanges::new::<S>().expect("failed to listen for X11 clipboard owner changes");
+
+            // Obtain new X11 clipboard context, set clipboard contents
+            let mut clip =
+                X11ClipboardContext::<S>::new().expect("failed to obtain X11 clipboard context");
+            clip.set_contents(contents)
+                .expect("failed to set clipboard contents through forked process");
+            ready();
+
+            // Wait for another application to take the clipboard, then kill fork
+            owner_changes
+                .wait_lost()
+                .expect("failed to wait on X11 clipboard owner change in forked process");
+        })
+    }
+}
+
+/// A transfer of large data in chunks, through the X11 `INCR` mechanism.
+struct Transfer {
+    target: Atom,
+    data: Vec<u8>,
+    pos: usize,
+}
+
+/// Own selection `S`, and serve data produced by `provide` for each request until another
+/// application takes ownership.
+///
+/// Calls `ready` once it took ownership.
+fn serve_with<S, F>(
+    targets: &[&str],
+    mut provide: F,
+    ready: &mut dyn FnMut(),
+) -> crate::ClipResult<()>
+where
+    S: ForkSelection,
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let (connection, screen) = x11rb::connect(None)?;
+    let connection = &connection;
+    let root = connection.setup().roots[screen].root;
+    let window = connection.generate_id()?;
+    connection.create_window(
+        COPY_DEPTH_FROM_PARENT,
+        window,
+        root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_OUTPUT,
+        COPY_FROM_PARENT,
+        &CreateWindowAux::new(),
+    )?;
+
+    let selection = intern_atom(connection, S::ATOM)?;
+    let targets_atom = intern_atom(connection, "TARGETS")?;
+    let incr_atom = intern_atom(connection, "INCR")?;
+    let target_atoms = targets
+        .iter()
+        .map(|target| intern_atom(connection, target))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    connection.set_selection_owner(window, selection, CURRENT_TIME)?;
+    if connection.get_selection_owner(selection)?.reply()?.owner != window {
+        return Err(Error::Ownership.into());
+    }
+    ready();
+
+    // Data not fitting in a single request is transferred incrementally
+    let max_len = connection.maximum_request_bytes() - 24;
+    let mut transfers: HashMap<(Window, Atom), Transfer> = HashMap::new();
+    let mut owner = true;
+    while owner || !transfers.is_empty() {
+        match connection.wait_for_event()? {
+            Event::SelectionRequest(event) if event.selection == selection => {
+                // Obsolete clients don't specify a property, use the target instead
+                let property = if event.property == NONE {
+                    event.target
+                } else {
+                    event.property
+                };
+                let target = target_atoms
+                    .iter()
+                    .position(|atom| *atom == event.target)
+                    .map(|i| targets[i]);
+
+                let provided = if event.target == targets_atom {
+                    let mut atoms = vec![targets_atom];
+                    atoms.extend(&target_atoms);
+                    connection.change_property32(
+                        PropMode::REPLACE,
+                        event.requestor,
+                        property,
+                        AtomEnum::ATOM,
+                        &atoms,
+                    )?;
+                    true
+                } else {
+                    match target.and_then(&mut provide) {
+                        Some(data) if data.len() <= max_len => {
+                            connection.change_property8(
+                                PropMode::REPLACE,
+                                event.requestor,
+                                property,
+                                event.target,
+                                &data,
+                            )?;
+                            true
+                        }
+                        Some(data) => {
+                            connection.change_window_attributes(
+                                event.requestor,
+                                &ChangeWindowAttributesAux::new()
+                                    .event_mask(EventMask::PROPERTY_CHANGE),
+                            )?;
+                            connection.change_property32(
+                                PropMode::REPLACE,
+                                event.requestor,
+                                property,
+                                incr_atom,
+                                &[data.len().min(u32::MAX as usize) as u32],
+                            )?;
+                            transfers.insert(
+                                (event.requestor, property),
+                                Transfer {
+                                    target: event.target,
+                                    data,
+                                    pos: 0,
+                                },
+                            );
+                            true
+                        }
+                        None => false,
+                    }
+                };
+
+                connection.send_event(
+                    false,
+                    event.requestor,
+                    EventMask::NO_EVENT,
+                    SelectionNotifyEvent {
+                        response_type: SELECTION_NOTIFY_EVENT,
+                        sequence: 0,
+                        time: event.time,
+                        requestor: event.requestor,
+                        selection: event.selection,
+                        target: event.target,
+                        property: if provided { property } else { NONE },
+                    },
+                )?;
+                connection.flush()?;
+            }
+
+            // The requestor deleted the property, send the next chunk
+            Event::PropertyNotify(event) if event.state == Property::DELETE => {
+                let key = (event.window, event.atom);
+                let transfer = match transfers.get_mut(&key) {
+                    Some(transfer) => transfer,
+                    None => continue,
+                };
+                let len = cmp::min(INCR_CHUNK_SIZE, transfer.data.len() - transfer.pos);
+                connection.change_property8(
+                    PropMode::REPLACE,
+                    event.window,
+                    event.atom,
+                    transfer.target,
+                    &transfer.data[transfer.pos..][..len],
+                )?;
+                connection.flush()?;
+
+                // An empty chunk ends the transfer
+                transfer.pos += len;
+                if len == 0 {
+                    transfers.remove(&key);
+                }
+            }
+
+            // Finish ongoing transfers after losing ownership
+            Event::SelectionClear(event) if event.selection == selection => owner = false,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Get the atom with the given name, creating it if it doesn't exist.
+fn intern_atom<C>(connection: &C, name: &str) -> crate::ClipResult<Atom>
+where
+    C: Connection,
+{
+    Ok(connection
+        .intern_atom(false, name.as_bytes())?
+        .reply()?
+        .atom)
+}
+
+/// X11 selection that can be set through [`X11ForkClipboardContext`].
+///
+/// Implemented for the selection types of copypasta's X11 context.
+pub trait ForkSelection: Selection {
+    /// Name of the selection atom, such as `CLIPBOARD`.
+    const ATOM: &'static str;
+
+    /// The selection, as reported through [`ClipboardProviderExt::selection`].
+    const SELECTION: crate::Selection;
+}
+
+impl ForkSelection for Clipboard {
+    const ATOM: &'static str = "CLIPBOARD";
+    const SELECTION: crate::Selection = crate::Selection::Clipboard;
+}
+
+impl ForkSelection for Primary {
+    const ATOM: &'static str = "PRIMARY";
+    const SELECTION: crate::Selection = crate::Selection::Primary;
+}
+
+/// Owner changes of an X11 selection, reported by the X server through XFIXES.
+struct OwnerChanges {
+    connection: RustConnection,
+    selection: Atom,
+}
+
+impl OwnerChanges {
+    /// Start listening for owner changes of selection `S`.
+    fn new<S>() -> crate::ClipResult<Self>
+    where
+        S: ForkSelection,
+    {
+        let (connection, screen) = x11rb::connect(None)?;
+        let root = connection.setup().roots[screen].root;
+        let selection = intern_atom(&connection, S::ATOM)?;
+
+        // The version must be negotiated before using the extension
+        connection.xfixes_query_version(5, 0)?.reply()?;
+        connection
+            .xfixes_select_selection_input(
+                root,
+                selection,
+                SelectionEventMask::SET_SELECTION_OWNER
+                    | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                    | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+            )?
+            .check()?;
+
+        Ok(Self {
+            connection,
+            selection,
+        })
+    }
+
+    /// Block until the selection is owned by another window than its current owner.
+    fn wait_lost(&self) -> crate::ClipResult<()> {
+        let owner = self
+            .connection
+            .get_selection_owner(self.selection)?
+            .reply()?
+            .owner;
+        loop {
+            if let Event::XfixesSelectionNotify(event) = self.connection.wait_for_event()? {
+                if event.selection == self.selection && event.owner != owner {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Create a pipe, returning the read and write end.
+fn pipe() -> Result<(File, File), IoError> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(IoError::last_os_error());
+    }
+    Ok(unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) })
+}
+
+/// Wait for the fork to signal it took ownership through the given pipe, up to the timeout.
+fn wait_ready(mut ready_rx: File, timeout: Duration) -> Result<(), Error> {
+    let mut fds = libc::pollfd {
+        fd: ready_rx.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout_ms = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+    match unsafe { libc::poll(&mut fds, 1, timeout_ms) } {
+        0 => return Err(Error::Ownership),
+        n if n < 0 => return Err(Error::Pipe(IoError::last_os_error())),
+        _ => {}
+    }
+
+    // The fork closes the pipe without signalling if it failed
+    let mut buf = [0];
+    match ready_rx.read(&mut buf).map_err(Error::Pipe)? {
+        0 => Err(Error::Ownership),
+        _ => Ok(()),
+    }
+}
+
+/// Other selections are accessed through a new context for that selection, with the same options.
+/// The secondary selection is not supported.
+impl<S> SelectionClipboardProviderExt for X11ForkClipboardContext<S>
+where
+    S: ForkSelection,
+{
+    fn supports_selection(&self, selection: crate::Selection) -> bool {
+        selection != crate::Selection::Secondary
+    }
+
+    fn get_selection(&mut self, selection: crate::Selection) -> crate::ClipResult<String> {
+        match selection {
+            crate::Selection::Clipboard => X11ClipboardContext::<Clipboard>::new()?.get_contents(),
+            crate::Selection::Primary => X11ClipboardContext::<Primary>::new()?.get_contents(),
+            selection => Err(crate::selection::Error::Unsupported(selection).into()),
         }
     }
+
+    fn set_selection(
+        &mut self,
+        selection: crate::Selection,
+        contents: String,
+    ) -> crate::ClipResult<()> {
+        match selection {
+            crate::Selection::Clipboard => {
+                self.for_selection::<Clipboard>()?.set_contents(contents)
+            }
+            crate::Selection::Primary => self.for_selection::<Primary>()?.set_contents(contents),
+            selection => Err(crate::selection::Error::Unsupported(selection).into()),
+        }
+    }
+}
+
+impl<S> ClipboardProviderNew for X11ForkClipboardContext<S>
+where
+    S: ForkSelection,
+{
+    fn new() -> crate::ClipResult<Self> {
+        X11ForkClipboardContext::new_selection()
+    }
 }
 
 impl<S> ClipboardProviderExt for X11ForkClipboardContext<S>
 where
-    S: Selection,
+    S: ForkSelection,
 {
     fn display_server(&self) -> Option<DisplayServer> {
         Some(DisplayServer::X11)
     }
 
-    fn has_bin_lifetime(&self) -> bool {
-        false
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Fork
+    }
+
+    fn selection(&self) -> crate::Selection {
+        S::SELECTION
     }
 }
 
@@ -134,12 +643,23 @@ where
 pub enum Error {
     /// Failed to fork process, to set clipboard in.
     Fork,
+
+    /// Failed to create or read pipe to communicate with fork.
+    Pipe(IoError),
+
+    /// The fork failed to take ownership of the clipboard in time.
+    Ownership,
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::Fork => write!(f, "Failed to fork process to set clipboard"),
+            Error::Pipe(err) => write!(f, "Failed to communicate with fork: {}", err),
+            Error::Ownership => write!(
+                f,
+                "Forked process failed to take clipboard ownership in time"
+            ),
         }
     }
 }
@@ -147,7 +667,8 @@ impl fmt::Display for Error {
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
-            Error::Fork => None,
+            Error::Pipe(err) => Some(err),
+            Error::Fork | Error::Ownership => None,
         }
     }
 }
@@ -0,0 +1,733 @@
+//! Query the X11 clipboard directly through the X11 protocol.
+//!
+//! Provides information not available through the clipboard provider interface, without invoking
+//! any binaries. Use [`X11ClipboardProviderExt`] on any X11 clipboard context, or the free
+//! functions in this module with an explicit selection.
+//!
+//! # Examples
+//!
+//! List the formats offered on the clipboard:
+//!
+//! ```rust,no_run
+//! use copypasta_ext::copypasta::x11_clipboard::X11ClipboardContext;
+//! use copypasta_ext::x11_native::X11ClipboardProviderExt;
+//!
+//! let ctx: X11ClipboardContext = X11ClipboardContext::new().unwrap();
+//! for target in ctx.targets().unwrap() {
+//!     println!("{}", target);
+//! }
+//! ```
+//!
+//! Find out which application owns the clipboard:
+//!
+//! ```rust,no_run
+//! use copypasta_ext::x11_native::X11ClipboardProviderExt;
+//! use copypasta_ext::x11_bin::X11BinClipboardContext;
+//!
+//! let ctx = X11BinClipboardContext::new().unwrap();
+//! match ctx.selection_owner().unwrap() {
+//!     Some(owner) => println!("Clipboard owned by {}", owner),
+//!     None => println!("Clipboard has no owner"),
+//! }
+//! ```
+//!
+//! Get contents without freezing if the selection owner doesn't respond, see
+//! [`X11TimedClipboardContext`]:
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use copypasta_ext::prelude::*;
+//! use copypasta_ext::x11_native::X11TimedClipboardContext;
+//!
+//! let mut ctx = X11TimedClipboardContext::new()
+//!     .unwrap()
+//!     .with_timeout(Duration::from_secs(1));
+//! println!("{:?}", ctx.get_contents());
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::marker::PhantomData;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::string::FromUtf8Error;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use copypasta::x11_clipboard::{Clipboard, Selection, X11ClipboardContext};
+use x11_clipboard::error::Error as X11Error;
+use x11_clipboard::{Atom, Atoms, Clipboard as X11Clipboard, Context, Window};
+use x11rb::connection::Connection;
+use x11rb::errors::{ConnectionError, ReplyError};
+use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+use x11rb::protocol::Event;
+use x11rb::CURRENT_TIME;
+
+use crate::config::ClipboardConfig;
+use crate::display::DisplayServer;
+use crate::prelude::*;
+use crate::{ClipboardProviderNew, ContentLifetime};
+
+/// Time to wait for the selection owner to respond.
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Interval to poll for the selection owner response.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Information about the window owning an X11 selection.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct SelectionOwner {
+    /// Owner window ID.
+    pub window: Window,
+
+    /// Window title, from `_NET_WM_NAME` or `WM_NAME`.
+    pub name: Option<String>,
+
+    /// Application instance name, from `WM_CLASS`.
+    pub instance: Option<String>,
+
+    /// Application class name, from `WM_CLASS`.
+    pub class: Option<String>,
+
+    /// Owner process ID, from `_NET_WM_PID`.
+    pub pid: Option<u32>,
+}
+
+impl fmt::Display for SelectionOwner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "window 0x{:x}", self.window)?;
+        if let Some(class) = self.class.as_ref().or(self.instance.as_ref()) {
+            write!(f, " ({})", class)?;
+        }
+        if let Some(name) = &self.name {
+            write!(f, " '{}'", name)?;
+        }
+        if let Some(pid) = self.pid {
+            write!(f, " pid {}", pid)?;
+        }
+        Ok(())
+    }
+}
+
+/// Query the window owning selection `S`.
+///
+/// Returns `None` if the selection has no owner. Clipboard owners commonly use an unmapped
+/// helper window, which may not have all properties set.
+///
+/// ```rust,no_run
+/// use copypasta_ext::x11_native;
+/// use copypasta_ext::x11_bin::Primary;
+///
+/// println!("{:?}", x11_native::selection_owner::<Primary>().unwrap());
+/// ```
+pub fn selection_owner<S>() -> crate::ClipResult<Option<SelectionOwner>>
+where
+    S: Selection,
+{
+    let context = Context::new(None).map_err(Error::Connect)?;
+    let window = context
+        .connection
+        .get_selection_owner(S::atom(&context.atoms))
+        .map_err(Error::Connection)?
+        .reply()
+        .map_err(Error::Reply)?
+        .owner;
+    if window == x11rb::NONE {
+        return Ok(None);
+    }
+
+    let name = match get_property(&context, window, "_NET_WM_NAME")? {
+        Some(name) => Some(name),
+        None => get_property(&context, window, "WM_NAME")?,
+    }
+    .map(|name| String::from_utf8_lossy(&name).into_owned());
+
+    // WM_CLASS holds two null terminated strings, the instance and class name
+    let (instance, class) = match get_property(&context, window, "WM_CLASS")? {
+        Some(wm_class) => {
+            let mut parts = wm_class
+                .split(|b| *b == 0)
+                .map(|part| String::from_utf8_lossy(part).into_owned());
+            (parts.next(), parts.next().filter(|c| !c.is_empty()))
+        }
+        None => (None, None),
+    };
+
+    let pid = get_property(&context, window, "_NET_WM_PID")?
+        .filter(|pid| pid.len() >= 4)
+        .map(|pid| u32::from_ne_bytes([pid[0], pid[1], pid[2], pid[3]]));
+
+    Ok(Some(SelectionOwner {
+        window,
+        name,
+        instance,
+        class,
+        pid,
+    }))
+}
+
+/// List the targets, the formats, offered on selection `S`.
+///
+/// Requests the `TARGETS` list from the selection owner, and returns the target names such as
+/// `UTF8_STRING`, `text/html` or `image/png`. Returns an empty list if the selection has no
+/// owner.
+///
+/// ```rust,no_run
+/// use copypasta_ext::x11_native;
+/// use copypasta_ext::x11_bin::Clipboard;
+///
+/// let targets = x11_native::targets::<Clipboard>().unwrap();
+/// if targets.iter().any(|t| t == "text/html") {
+///     println!("Clipboard has HTML");
+/// }
+/// ```
+pub fn targets<S>() -> crate::ClipResult<Vec<String>>
+where
+    S: Selection,
+{
+    let context = Context::new(None).map_err(Error::Connect)?;
+    query_targets(&context, S::atom(&context.atoms))
+}
+
+/// List the targets offered on the given selection through the given connection.
+fn query_targets(context: &Context, selection: Atom) -> crate::ClipResult<Vec<String>> {
+    let owner = context
+        .connection
+        .get_selection_owner(selection)
+        .map_err(Error::Connection)?
+        .reply()
+        .map_err(Error::Reply)?
+        .owner;
+    if owner == x11rb::NONE {
+        return Ok(vec![]);
+    }
+
+    // Ask the owner to convert the selection to the target list
+    context
+        .connection
+        .convert_selection(
+            context.window,
+            selection,
+            context.atoms.targets,
+            context.atoms.property,
+            CURRENT_TIME,
+        )
+        .map_err(Error::Connection)?
+        .check()
+        .map_err(Error::Reply)?;
+
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        match context
+            .connection
+            .poll_for_event()
+            .map_err(Error::Connection)?
+        {
+            Some(Event::SelectionNotify(event)) if event.selection == selection => {
+                // Owner refused to convert
+                if event.property == x11rb::NONE {
+                    return Ok(vec![]);
+                }
+                break;
+            }
+            Some(_) => continue,
+            None if Instant::now() >= deadline => return Err(Error::Timeout.into()),
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    }
+
+    let reply = context
+        .connection
+        .get_property(
+            true,
+            context.window,
+            context.atoms.property,
+            AtomEnum::ATOM,
+            0,
+            u32::MAX,
+        )
+        .map_err(Error::Connection)?
+        .reply()
+        .map_err(Error::Reply)?;
+    let atoms: Vec<Atom> = match reply.value32() {
+        Some(atoms) => atoms.collect(),
+        None => return Ok(vec![]),
+    };
+
+    // Send all requests first, then collect replies, to prevent a round trip per atom
+    let cookies = atoms
+        .iter()
+        .map(|atom| context.connection.get_atom_name(*atom))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::Connection)?;
+    let mut targets = Vec::with_capacity(cookies.len());
+    for cookie in cookies {
+        let reply = cookie.reply().map_err(Error::Reply)?;
+        targets.push(String::from_utf8_lossy(&reply.name).into_owned());
+    }
+    Ok(targets)
+}
+
+/// Listens for ownership changes of an X11 selection through the XFIXES extension.
+///
+/// The X server notifies about each new selection owner, and when the owner goes away. Each time
+/// the clipboard is set a new owner is announced, so this is used to detect changes without
+/// polling the clipboard contents.
+pub(crate) struct SelectionEvents {
+    context: Context,
+    selection: Atom,
+}
+
+impl SelectionEvents {
+    /// Start listening for ownership changes of selection `S`.
+    pub fn new<S>() -> Result<Self, Error>
+    where
+        S: Selection,
+    {
+        let context = Context::new(None).map_err(Error::Connect)?;
+        let selection = S::atom(&context.atoms);
+
+        // The version must be negotiated before using the extension
+        context
+            .connection
+            .xfixes_query_version(5, 0)
+            .map_err(Error::Connection)?
+            .reply()
+            .map_err(Error::Reply)?;
+        context
+            .connection
+            .xfixes_select_selection_input(
+                context.window,
+                selection,
+                SelectionEventMask::SET_SELECTION_OWNER
+                    | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                    | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+            )
+            .map_err(Error::Connection)?
+            .check()
+            .map_err(Error::Reply)?;
+
+        Ok(Self { context, selection })
+    }
+
+    /// Check whether the selection owner changed since the last check, without blocking.
+    ///
+    /// Handles all pending events. Wait for the connection to become readable before checking
+    /// again, see [`AsRawFd`].
+    pub fn changed(&self) -> Result<bool, Error> {
+        let mut changed = false;
+        while let Some(event) = self
+            .context
+            .connection
+            .poll_for_event()
+            .map_err(Error::Connection)?
+        {
+            if let Event::XfixesSelectionNotify(event) = event {
+                changed |= event.selection == self.selection;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// The X server connection, readable when new events arrive.
+impl AsRawFd for SelectionEvents {
+    fn as_raw_fd(&self) -> RawFd {
+        self.context.connection.stream().as_raw_fd()
+    }
+}
+
+/// The selection accessed through selection type `S`.
+fn selection<S>() -> crate::Selection
+where
+    S: Selection,
+{
+    // Selection types only pick one of the atoms, tell them apart through placeholder values
+    let atoms = Atoms {
+        primary: 1,
+        clipboard: 2,
+        property: 0,
+        targets: 0,
+        string: 0,
+        utf8_string: 0,
+        incr: 0,
+    };
+    if S::atom(&atoms) == atoms.primary {
+        crate::Selection::Primary
+    } else {
+        crate::Selection::Clipboard
+    }
+}
+
+/// Get the raw value of a window property by name, `None` if not set.
+fn get_property(context: &Context, window: Window, name: &str) -> Result<Option<Vec<u8>>, Error> {
+    let property: Atom = context.get_atom(name).map_err(Error::Connect)?;
+    let reply = context
+        .connection
+        .get_property(false, window, property, AtomEnum::ANY, 0, u32::MAX)
+        .map_err(Error::Connection)?
+        .reply()
+        .map_err(Error::Reply)?;
+    Ok(if reply.type_ == x11rb::NONE || reply.value.is_empty() {
+        None
+    } else {
+        Some(reply.value)
+    })
+}
+
+/// X11 specific clipboard queries, talking to the X server directly.
+///
+/// Implemented for the X11 clipboard contexts, queries apply to the selection of the context.
+pub trait X11ClipboardProviderExt {
+    /// The selection used by this context, such as [`Clipboard`](crate::x11_bin::Clipboard).
+    type Selection: Selection;
+
+    /// Query the window currently owning the selection of this context.
+    ///
+    /// See [`selection_owner`].
+    fn selection_owner(&self) -> crate::ClipResult<Option<SelectionOwner>>;
+
+    /// List the targets, the formats, offered on the selection of this context.
+    ///
+    /// See [`targets`].
+    fn targets(&self) -> crate::ClipResult<Vec<String>>;
+}
+
+impl<S> X11ClipboardProviderExt for X11ClipboardContext<S>
+where
+    S: Selection,
+{
+    type Selection = S;
+
+    fn selection_owner(&self) -> crate::ClipResult<Option<SelectionOwner>> {
+        selection_owner::<S>()
+    }
+
+    fn targets(&self) -> crate::ClipResult<Vec<String>> {
+        targets::<S>()
+    }
+}
+
+#[cfg(feature = "x11-fork")]
+impl<S> X11ClipboardProviderExt for crate::x11_fork::X11ForkClipboardContext<S>
+where
+    S: crate::x11_fork::ForkSelection,
+{
+    type Selection = S;
+
+    fn selection_owner(&self) -> crate::ClipResult<Option<SelectionOwner>> {
+        selection_owner::<S>()
+    }
+
+    fn targets(&self) -> crate::ClipResult<Vec<String>> {
+        targets::<S>()
+    }
+}
+
+#[cfg(feature = "x11-bin")]
+impl<S> X11ClipboardProviderExt for crate::x11_bin::X11BinClipboardContext<S>
+where
+    S: crate::x11_bin::BinSelection,
+{
+    type Selection = S;
+
+    fn selection_owner(&self) -> crate::ClipResult<Option<SelectionOwner>> {
+        selection_owner::<S>()
+    }
+
+    fn targets(&self) -> crate::ClipResult<Vec<String>> {
+        targets::<S>()
+    }
+}
+
+/// Like [`X11ClipboardContext`], but getting contents times out instead of blocking forever.
+///
+/// Getting contents through [`X11ClipboardContext`] waits for the selection owner to respond,
+/// which never happens if the owning application hangs. This context gets contents on a worker
+/// thread, and returns [`Error::Timeout`] if the owner doesn't respond within the timeout, 3
+/// seconds by default. Setting contents is done directly, as it doesn't depend on other
+/// applications.
+///
+/// A worker that timed out is abandoned, along with its X11 connection, and a new one is started
+/// for the next get. The abandoned thread exits once the owner responds, if ever.
+///
+/// Contents are got as `text/plain;charset=utf-8`, `UTF8_STRING` or `STRING`, whichever the owner
+/// offers first in that order. `STRING` is converted from Latin-1, so pasting from old Motif and
+/// Java applications doesn't fail.
+///
+/// See module documentation for more information.
+pub struct X11TimedClipboardContext<S = Clipboard>
+where
+    S: Selection + 'static,
+{
+    /// Native context, used for setting contents.
+    context: X11ClipboardContext<S>,
+
+    /// Time to wait for contents.
+    timeout: Duration,
+
+    /// Worker getting contents, started on first get.
+    worker: Option<GetWorker<S>>,
+}
+
+impl X11TimedClipboardContext {
+    pub fn new() -> crate::ClipResult<Self> {
+        Self::new_selection()
+    }
+}
+
+impl<S> X11TimedClipboardContext<S>
+where
+    S: Selection + 'static,
+{
+    /// Construct for the selection `S`.
+    pub fn new_selection() -> crate::ClipResult<Self> {
+        Ok(Self {
+            context: X11ClipboardContext::new()?,
+            timeout: TIMEOUT,
+            worker: None,
+        })
+    }
+
+    /// Wait for the selection owner to respond with contents up to the given timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Apply the options of the given configuration.
+    ///
+    /// Uses the configured get timeout. See [`ClipboardConfig`].
+    pub fn with_config(mut self, config: &ClipboardConfig) -> Self {
+        if let Some(timeout) = config.get_timeout {
+            self.timeout = timeout;
+        }
+        self
+    }
+
+    /// Get a reference to the inner clipboard context, used for setting.
+    pub fn inner(&self) -> &X11ClipboardContext<S> {
+        &self.context
+    }
+
+    /// Get a mutable reference to the inner clipboard context, used for setting.
+    pub fn inner_mut(&mut self) -> &mut X11ClipboardContext<S> {
+        &mut self.context
+    }
+
+    /// Unwrap, returning the inner clipboard context.
+    pub fn into_inner(self) -> X11ClipboardContext<S> {
+        self.context
+    }
+}
+
+impl<S> ClipboardProvider for X11TimedClipboardContext<S>
+where
+    S: Selection + 'static,
+{
+    fn get_contents(&mut self) -> crate::ClipResult<String> {
+        let worker = match self.worker.take() {
+            Some(worker) => worker,
+            None => GetWorker::spawn()?,
+        };
+        let result = worker.get(self.timeout);
+
+        // Abandon the worker if it hangs
+        if !matches!(result, Err(Error::Timeout)) {
+            self.worker = Some(worker);
+        }
+        result?
+    }
+
+    fn set_contents(&mut self, contents: String) -> crate::ClipResult<()> {
+        self.context.set_contents(contents)
+    }
+}
+
+impl<S> ClipboardProviderNew for X11TimedClipboardContext<S>
+where
+    S: Selection + 'static,
+{
+    fn new() -> crate::ClipResult<Self> {
+        X11TimedClipboardContext::new_selection()
+    }
+}
+
+/// X11 clipboards have process lifetime, not infinite.
+impl<S> ClipboardProviderExt for X11TimedClipboardContext<S>
+where
+    S: Selection + 'static,
+{
+    fn display_server(&self) -> Option<DisplayServer> {
+        Some(DisplayServer::X11)
+    }
+
+    fn content_lifetime(&self) -> ContentLifetime {
+        ContentLifetime::Process
+    }
+
+    fn selection(&self) -> crate::Selection {
+        selection::<S>()
+    }
+
+    fn persist(&mut self) -> crate::ClipResult<()> {
+        crate::persist::x11(self.get_contents()?)
+    }
+
+    fn is_empty(&mut self) -> crate::ClipResult<bool> {
+        Ok(targets::<S>()?.is_empty())
+    }
+
+    fn available_mime_types(&mut self) -> crate::ClipResult<Vec<String>> {
+        let targets = targets::<S>()?;
+        Ok(crate::content::mime_types(
+            targets.iter().map(|t| t.as_str()),
+        ))
+    }
+}
+
+impl<S> X11ClipboardProviderExt for X11TimedClipboardContext<S>
+where
+    S: Selection + 'static,
+{
+    type Selection = S;
+
+    fn selection_owner(&self) -> crate::ClipResult<Option<SelectionOwner>> {
+        selection_owner::<S>()
+    }
+
+    fn targets(&self) -> crate::ClipResult<Vec<String>> {
+        targets::<S>()
+    }
+}
+
+/// Result of getting contents on a worker thread.
+type GetResult = crate::ClipResult<String>;
+
+/// Thread getting contents of selection `S` on request, through its own X11 connection.
+struct GetWorker<S> {
+    requests: Sender<()>,
+    results: Receiver<GetResult>,
+    selection: PhantomData<S>,
+}
+
+impl<S> GetWorker<S>
+where
+    S: Selection + 'static,
+{
+    /// Spawn the worker thread.
+    fn spawn() -> crate::ClipResult<Self> {
+        let (requests, request_rx) = mpsc::channel::<()>();
+        let (result_tx, results) = mpsc::channel::<GetResult>();
+        thread::Builder::new()
+            .name("copypasta-ext-x11-get".into())
+            .spawn(move || {
+                let mut clipboard = None;
+                for () in request_rx {
+                    let result = match &clipboard {
+                        Some(clipboard) => get_text::<S>(clipboard),
+                        None => X11Clipboard::new()
+                            .map_err(|err| Error::Connect(err).into())
+                            .and_then(|new| get_text::<S>(clipboard.get_or_insert(new))),
+                    };
+                    if result_tx.send(result).is_err() {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self {
+            requests,
+            results,
+            selection: PhantomData,
+        })
+    }
+
+    /// Get the contents, up to the given timeout.
+    ///
+    /// A worker that is gone, such as after a panic, is treated like one that hangs.
+    fn get(&self, timeout: Duration) -> Result<GetResult, Error> {
+        self.requests.send(()).map_err(|_| Error::Timeout)?;
+        self.results
+            .recv_timeout(timeout)
+            .map_err(|_| Error::Timeout)
+    }
+}
+
+/// Get the contents of selection `S` as the most preferred text target offered.
+///
+/// Tries `text/plain;charset=utf-8`, `UTF8_STRING` and then `STRING`, converting the latter from
+/// Latin-1. Old Motif and Java applications only offer `STRING`, which otherwise fails to parse
+/// as UTF-8. Gets `UTF8_STRING` if the owner doesn't list its targets.
+fn get_text<S>(clipboard: &X11Clipboard) -> crate::ClipResult<String>
+where
+    S: Selection,
+{
+    let context = &clipboard.getter;
+    let selection = S::atom(&context.atoms);
+    let targets = query_targets(context, selection)?;
+    let target = crate::content::X11_TEXT_TARGETS
+        .iter()
+        .find(|target| targets.iter().any(|t| t == *target))
+        .unwrap_or(&"UTF8_STRING");
+
+    let atom = context.get_atom(target).map_err(Error::Connect)?;
+    let contents = clipboard
+        .load(selection, atom, context.atoms.property, TIMEOUT)
+        .map_err(Error::Load)?;
+    Ok(crate::content::decode_x11_text(target, contents).map_err(Error::NoUtf8)?)
+}
+
+/// Represents X11 native query related error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to connect to the X server.
+    Connect(X11Error),
+
+    /// Failed to send a request to the X server.
+    Connection(ConnectionError),
+
+    /// The X server replied with an error.
+    Reply(ReplyError),
+
+    /// The selection owner didn't respond in time.
+    Timeout,
+
+    /// Failed to get the selection contents.
+    Load(X11Error),
+
+    /// The selection contents could not be parsed as valid UTF-8.
+    NoUtf8(FromUtf8Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Connect(err) => write!(f, "Failed to connect to X server: {}", err),
+            Error::Connection(err) => write!(f, "Failed to send X11 request: {}", err),
+            Error::Reply(err) => write!(f, "X11 request failed: {}", err),
+            Error::Timeout => write!(f, "X11 selection owner didn't respond in time"),
+            Error::Load(err) => write!(f, "Failed to get X11 selection contents: {}", err),
+            Error::NoUtf8(err) => write!(
+                f,
+                "Failed to parse clipboard contents as valid UTF-8: {}",
+                err
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Connect(err) => Some(err),
+            Error::Connection(err) => Some(err),
+            Error::Reply(err) => Some(err),
+            Error::Timeout => None,
+            Error::Load(err) => Some(err),
+            Error::NoUtf8(err) => Some(err),
+        }
+    }
+}